@@ -1,14 +1,21 @@
+mod print;
+
 use std::io::Read;
 use whasm::structure::{module::Module};
-use whasm::binary::{WasmBinary};
-use whasm::validation::{ValidationEntry};
+use whasm::binary::{SliceBinary, offsets::WithOffsets};
+use whasm::validation::Features;
+#[cfg(not(feature = "parallel"))]
+use whasm::validation::ValidationEntry;
+#[cfg(feature = "parallel")]
+use whasm::validation::ParallelValidationEntry;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     let config = Config::new(&args)
         .unwrap_or_else(|err| {
             println!("Problem pargin input arguments: {}", err);
-            println!("Usage: {} [-p|--print] file.wasm", args[0]);
+            println!("Usage: {} [-p|--print] [--features=mvp|2.0|all] file.wasm", args[0]);
+            println!("Parsing and validation reject the proposals that the features do not enable.");
             std::process::exit(1);
         });
 
@@ -27,35 +34,52 @@ fn main() {
             std::process::exit(1);
         });
 
-    let mut iter = buffer.iter().copied();
+    let mut iter = SliceBinary::new(&buffer);
 
-    let module: Module = iter.parse()
+    let module: WithOffsets<Module> = iter.parse_slice_with(config.features)
         .unwrap_or_else(|err| {
             println!("Error parsing file \"{}\".", config.filename);
             println!("{}", err);
             std::process::exit(1);
         });
 
-    module.validate()
+    #[cfg(feature = "parallel")]
+    let result = module.par_validate_with(config.features);
+    #[cfg(not(feature = "parallel"))]
+    let result = module.validate_with(config.features);
+
+    result
         .unwrap_or_else(|err| {
             println!("Error validating file \"{}\".", config.filename);
             println!("{}", err);
             std::process::exit(1);
         });
 
+    let WithOffsets(module, _) = module;
+
     if config.print {
-        println!("{:#?}", module);
+        let names = match module.name_section() {
+            Ok(names) => names.unwrap_or_default(),
+            Err(err) => {
+                println!("Ignoring malformed name section in file \"{}\".", config.filename);
+                println!("{}", err);
+                Default::default()
+            },
+        };
+        print::print_module(&module, &names);
     }
 }
 
 struct Config {
     filename: String,
     print: bool,
+    features: Features,
 }
 
 impl Config {
     fn new(args: &[String]) -> Result<Config, Box<dyn std::error::Error>> {
         let mut print = false;
+        let mut features = Features::default();
         let mut filename = "".into();
 
         let mut args = args.iter();
@@ -65,6 +89,14 @@ impl Config {
                 "-p" | "--print" => {
                     print = true;
                 },
+                other if other.starts_with("--features=") => {
+                    features = match &other["--features=".len()..] {
+                        "mvp" => Features::MVP,
+                        "2.0" => Features::WASM2,
+                        "all" => Features::ALL,
+                        preset => return Err(format!("Unknown feature preset \"{}\".", preset))?,
+                    };
+                },
                 other => {
                     filename = other.into();
                 },
@@ -75,6 +107,6 @@ impl Config {
             return Err("Not enough arguments.")?;
         }
 
-        Ok( Config { filename, print } )
+        Ok( Config { filename, print, features } )
     }
 }
\ No newline at end of file
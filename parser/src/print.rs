@@ -0,0 +1,200 @@
+//! Prints a module for debugging, using the names of its name section in place of the indices
+//! that have one.
+
+use whasm::structure::{module::{self, Module}, instr::{Expr, Instr}, name::NameSection, idx, ty};
+
+pub fn print_module(module: &Module, names: &NameSection) {
+    if let Some(name) = names.module_name() {
+        println!("module ${}", name);
+    }
+    let types: Vec<&ty::Sub> = module.types.iter().flat_map(|rec| rec.types.iter()).collect();
+
+    let mut funcs = 0;
+    let mut globals = 0;
+    for import in module.imports.iter() {
+        print!("import \"{}\" \"{}\" ", import.module, import.name);
+        match &import.desc {
+            module::desc::Import::Func(ty) => {
+                println!("func {} (type {})", named(names.func_name(&idx::Func(funcs)), funcs), ty.0);
+                funcs += 1;
+            },
+            module::desc::Import::Global(ty) => {
+                println!("global {} {:?}", named(names.global_name(&idx::Global(globals)), globals), ty);
+                globals += 1;
+            },
+            desc => println!("{:?}", desc),
+        }
+    }
+
+    for global in module.globals.iter() {
+        let printer = Printer::new(names, idx::Func(funcs));
+        let init: Vec<_> = global.init.0.iter().map(|instr| printer.instr(instr)).collect();
+        println!("global {} {:?} = {}", named(names.global_name(&idx::Global(globals)), globals), global.ty, init.join(" "));
+        globals += 1;
+    }
+
+    for export in module.exports.iter() {
+        let desc = match &export.desc {
+            module::desc::Export::Func(func) => format!("func {}", named(names.func_name(func), func.0)),
+            module::desc::Export::Global(global) => format!("global {}", named(names.global_name(global), global.0)),
+            module::desc::Export::Table(table) => format!("table {}", named(names.table_name(table), table.0)),
+            module::desc::Export::Mem(mem) => format!("memory {}", named(names.mem_name(mem), mem.0)),
+            module::desc::Export::Tag(tag) => format!("tag {}", named(names.tag_name(tag), tag.0)),
+        };
+        println!("export \"{}\" {}", export.name, desc);
+    }
+
+    for func in module.funcs.iter() {
+        let idx = idx::Func(funcs);
+        println!("func {} (type {})", named(names.func_name(&idx), funcs), func.ty.0);
+        let params = match types.get(func.ty.0 as usize).map(|sub| &sub.composite) {
+            Some(ty::Composite::Func(ty)) => &ty.params[..],
+            _ => &[],
+        };
+        let locals = params.iter().map(|val| ("param", val))
+            .chain(func.locals.iter().map(|val| ("local", val)));
+        for (local, (kind, val)) in locals.enumerate() {
+            let local = local as u32;
+            println!("  {} {}: {:?}", kind, named(names.local_name(&idx, &idx::Local(local)), local), val);
+        }
+        match func.body.expr() {
+            Ok(body) => Printer::new(names, idx).expr(body, 1),
+            Err(err) => println!("  malformed body: {}", err),
+        }
+        funcs += 1;
+    }
+}
+
+/// Returns `$name` if the name is known, or the index otherwise.
+fn named(name: Option<&str>, idx: u32) -> String {
+    match name {
+        Some(name) => format!("${}", name),
+        None => idx.to_string(),
+    }
+}
+
+struct Printer<'a> {
+    names: &'a NameSection,
+    func: idx::Func,
+    // the number of blocks found so far, which gives the index of the next label
+    labels: u32,
+    // the labels of the enclosing blocks, the innermost last
+    stack: Vec<u32>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(names: &'a NameSection, func: idx::Func) -> Self {
+        Self { names, func, labels: 0, stack: vec![] }
+    }
+
+    fn expr(&mut self, expr: &Expr, depth: usize) {
+        for instr in expr.0.iter() {
+            self.instr_line(instr, depth);
+        }
+    }
+
+    fn instr_line(&mut self, instr: &Instr, depth: usize) {
+        let indent = "  ".repeat(depth);
+        match instr {
+            Instr::Block(ret, body) => {
+                self.open(&format!("Block({:?})", ret), depth);
+                self.expr(body, depth + 1);
+                self.close(depth);
+            },
+            Instr::Loop(ret, body) => {
+                self.open(&format!("Loop({:?})", ret), depth);
+                self.expr(body, depth + 1);
+                self.close(depth);
+            },
+            Instr::If(ret, then, otherwise) => {
+                self.open(&format!("If({:?})", ret), depth);
+                self.expr(then, depth + 1);
+                if !otherwise.0.is_empty() {
+                    println!("{}Else", indent);
+                    self.expr(otherwise, depth + 1);
+                }
+                self.close(depth);
+            },
+            Instr::Try(ret, body, catches, catch_all) => {
+                self.open(&format!("Try({:?})", ret), depth);
+                self.expr(body, depth + 1);
+                for (tag, handler) in catches.iter() {
+                    println!("{}Catch({})", indent, named(self.names.tag_name(tag), tag.0));
+                    self.expr(handler, depth + 1);
+                }
+                if let Some(handler) = catch_all {
+                    println!("{}CatchAll", indent);
+                    self.expr(handler, depth + 1);
+                }
+                self.close(depth);
+            },
+            Instr::TryDelegate(ret, body, label) => {
+                self.open(&format!("Try({:?})", ret), depth);
+                self.expr(body, depth + 1);
+                self.stack.pop();
+                println!("{}Delegate({})", indent, self.label(label));
+            },
+            Instr::TryTable(ret, catches, body) => {
+                self.open(&format!("TryTable({:?}, {:?})", ret, catches), depth);
+                self.expr(body, depth + 1);
+                self.close(depth);
+            },
+            instr => println!("{}{}", indent, self.instr(instr)),
+        }
+    }
+
+    /// Prints the start of a block, which defines the next label.
+    fn open(&mut self, block: &str, depth: usize) {
+        let label = self.labels;
+        self.labels += 1;
+        self.stack.push(label);
+        match self.names.label_name(&self.func, label) {
+            Some(name) => println!("{}{} ${}", "  ".repeat(depth), block, name),
+            None => println!("{}{}", "  ".repeat(depth), block),
+        }
+    }
+
+    fn close(&mut self, depth: usize) {
+        self.stack.pop();
+        println!("{}End", "  ".repeat(depth));
+    }
+
+    /// Returns the name of the block targeted by a branch to the given relative depth.
+    fn label(&self, label: &idx::Label) -> String {
+        let target = self.stack.len().checked_sub(label.0 as usize + 1).map(|i| self.stack[i]);
+        named(target.and_then(|target| self.names.label_name(&self.func, target)), label.0)
+    }
+
+    fn field(&self, ty: &idx::Type, field: &idx::Field) -> String {
+        let ty_name = named(self.names.type_name(ty), ty.0);
+        format!("{}, {}", ty_name, named(self.names.field_name(ty, field), field.0))
+    }
+
+    fn instr(&self, instr: &Instr) -> String {
+        let names = self.names;
+        match instr {
+            Instr::Br(label) => format!("Br({})", self.label(label)),
+            Instr::BrIf(label) => format!("BrIf({})", self.label(label)),
+            Instr::BrTable(labels, default) => {
+                let labels: Vec<_> = labels.iter().map(|label| self.label(label)).collect();
+                format!("BrTable([{}], {})", labels.join(", "), self.label(default))
+            },
+            Instr::Call(func) => format!("Call({})", named(names.func_name(func), func.0)),
+            Instr::ReturnCall(func) => format!("ReturnCall({})", named(names.func_name(func), func.0)),
+            Instr::RefFunc(func) => format!("RefFunc({})", named(names.func_name(func), func.0)),
+            Instr::LocalGet(local) => format!("LocalGet({})", named(names.local_name(&self.func, local), local.0)),
+            Instr::LocalSet(local) => format!("LocalSet({})", named(names.local_name(&self.func, local), local.0)),
+            Instr::LocalTee(local) => format!("LocalTee({})", named(names.local_name(&self.func, local), local.0)),
+            Instr::GlobalGet(global) => format!("GlobalGet({})", named(names.global_name(global), global.0)),
+            Instr::GlobalSet(global) => format!("GlobalSet({})", named(names.global_name(global), global.0)),
+            Instr::Throw(tag) => format!("Throw({})", named(names.tag_name(tag), tag.0)),
+            Instr::DataDrop(data) => format!("DataDrop({})", named(names.data_name(data), data.0)),
+            Instr::ElemDrop(elem) => format!("ElemDrop({})", named(names.elem_name(elem), elem.0)),
+            Instr::StructGet(ty, field) => format!("StructGet({})", self.field(ty, field)),
+            Instr::StructGetS(ty, field) => format!("StructGetS({})", self.field(ty, field)),
+            Instr::StructGetU(ty, field) => format!("StructGetU({})", self.field(ty, field)),
+            Instr::StructSet(ty, field) => format!("StructSet({})", self.field(ty, field)),
+            instr => format!("{:?}", instr),
+        }
+    }
+}
\ No newline at end of file
@@ -1,4 +1,5 @@
-//! This module defines the deserialization of arrays of up to 8 elements.
+//! This module defines the deserialization of arrays of up to 8 elements, and the serialization of
+//! arrays of any size.
 //! 
 //! An array of size `N` is encoded as `N` consecutive elements of the same type.
 //! An encoded array differs from an encoded `Vec<_>` in that the array does not encode its size.
@@ -12,7 +13,7 @@
 //! assert_eq!(result, [0x01, 0x02, 0x03, 0x04]);
 //! ```
 
-use crate::binary::{WasmBinaryParse, WasmBinary, Result};
+use crate::binary::{WasmBinaryParse, WasmBinary, WasmBinaryEncode, WasmBinarySink, Result};
 
 impl<T: WasmBinaryParse> WasmBinaryParse for [T; 0] {
     fn parse<Binary: WasmBinary>(_: &mut Binary) -> Result<Self> {
@@ -112,9 +113,18 @@ impl<T: WasmBinaryParse> WasmBinaryParse for [T; 8] {
     }
 }
 
+impl<T: WasmBinaryEncode, const N: usize> WasmBinaryEncode for [T; N] {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        for elem in self.iter() {
+            elem.encode(bin)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::binary::{WasmBinary, Byte};
+    use crate::binary::{WasmBinary, WasmBinaryEncode, Byte};
 
     #[test]
     fn can_deserialize_empty_array() {
@@ -129,4 +139,11 @@ mod test {
         let result: [Byte; 4] = iter.parse().unwrap();
         assert_eq!(result, [0x01, 0x02, 0x03, 0x04]);
     }
+
+    #[test]
+    fn can_serialize_4_element_array() {
+        let mut bin = vec![];
+        [Byte(0x01), Byte(0x02), Byte(0x03), Byte(0x04)].encode(&mut bin).unwrap();
+        assert_eq!(bin, [0x01, 0x02, 0x03, 0x04]);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-//! This module implements parsing of bytes.
+//! This module implements parsing and encoding of bytes.
 //! 
 //! The `Byte` proxy type wraps a `u8` changing its parsing from LEB-182 encoding to raw bytes.
 //! 
@@ -8,6 +8,7 @@
 //! calling `next`.
 
 use crate::binary::{WasmBinary, WasmBinaryParse, WasmBinaryParseProxy, Result, Error};
+use crate::binary::{WasmBinarySink, WasmBinaryEncode};
 
 /// `Byte` is a proxy type that wraps a `u8`.
 /// It implements the `WasmBinaryParseProxy<Inner=u8>` trait.
@@ -57,6 +58,13 @@ impl WasmBinaryParseProxy for Byte {
     fn unwrap(self) -> Self::Inner { self.0 }
 }
 
+impl WasmBinaryEncode for Byte {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        bin.extend(Some(self.0));
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for Byte {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "0x{:02X}", self.0)
@@ -97,7 +105,7 @@ impl From<Byte> for char {
 
 #[cfg(test)]
 mod test {
-    use crate::binary::{WasmBinary, WasmBinaryParseProxy, Byte, Result, Error};
+    use crate::binary::{WasmBinary, WasmBinaryEncode, WasmBinaryParseProxy, Byte, Result, Error};
 
     #[test]
     fn can_parse_byte() {
@@ -113,6 +121,13 @@ mod test {
         assert_eq!(result, Err(Error::UnexpectedEndOfFile));
     }
 
+    #[test]
+    fn can_encode_byte() {
+        let mut bin = vec![];
+        Byte(0x8E).encode(&mut bin).unwrap();
+        assert_eq!(bin, [0x8E]);
+    }
+
     #[test]
     fn can_unwrap_byte() {
         let byte = Byte(42);
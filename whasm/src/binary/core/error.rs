@@ -1,5 +1,5 @@
-//! This module defines the different error conditions that can be found while parsing a
-//! WebAssembly binary file.
+//! This module defines the different error conditions that can be found while parsing or encoding
+//! a WebAssembly binary file.
 
 use crate::binary::Byte;
 use err_derive::Error;
@@ -12,6 +12,12 @@ pub enum Error {
     #[error(display = "Unexpected variant id `{}` for type `{}`.", id, ty)]
     InvalidVariantId { id: u8, ty: &'static str },
 
+    #[error(display = "Invalid flags `{:#x}` for segment of type `{}`.", flags, ty)]
+    InvalidSegmentFlags { flags: u32, ty: &'static str },
+
+    #[error(display = "Unexpected subopcode `{}` after prefix `{:#x}` for type `{}`.", subopcode, prefix, ty)]
+    InvalidSubopcode { prefix: u8, subopcode: u32, ty: &'static str },
+
     #[error(display = "Reading out of range signed integer.")]
     OutOfRangeSignedInteger,
 
@@ -44,6 +50,36 @@ pub enum Error {
 
     #[error(display = "The size of the element does not match its content.")]
     SizeMissmatch,
+
+    #[error(display = "The `{}` feature is not enabled.", feature)]
+    FeatureDisabled { feature: &'static str },
+
+    #[error(display = "Value of type `{}` can not be encoded.", ty)]
+    UnencodableValue { ty: &'static str },
+
+    /// Wraps an error found while parsing a module, together with the offset of the byte in the
+    /// input at which it was detected.
+    #[error(display = "{} At byte offset `{:#x}`.", error, offset)]
+    AtOffset { offset: usize, error: Box<Error> },
+}
+
+impl Error {
+    /// Attaches the byte offset at which the error was found.
+    /// Errors that already carry an offset are left untouched.
+    pub fn at(self, offset: usize) -> Self {
+        match self {
+            Error::AtOffset { .. } => self,
+            error => Error::AtOffset { offset, error: Box::new(error) },
+        }
+    }
+
+    /// Returns the byte offset at which the error was found, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::AtOffset { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
 }
 
 impl From<std::string::FromUtf8Error> for Error {
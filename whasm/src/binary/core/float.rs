@@ -1,4 +1,4 @@
-//! This module defines the parsing of floating point numbers.
+//! This module defines the parsing and encoding of floating point numbers.
 //! 
 //! Floating point numbers are encoded with the little-endian IEEE-754 format.
 //! The floating point types are `f32` and `f64`.
@@ -25,7 +25,7 @@
 //! assert!(iter.next().is_none());
 //! ```
 
-use crate::binary::{WasmBinaryParse, WasmBinary, Result, Byte};
+use crate::binary::{WasmBinaryParse, WasmBinary, WasmBinaryEncode, WasmBinarySink, Result, Byte};
 use num_traits::*;
 
 impl WasmBinaryParse for f32 {
@@ -40,6 +40,20 @@ impl WasmBinaryParse for f64 {
     }
 }
 
+impl WasmBinaryEncode for f32 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        bin.extend(self.to_le_bytes().iter().copied());
+        Ok(())
+    }
+}
+
+impl WasmBinaryEncode for f64 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        bin.extend(self.to_le_bytes().iter().copied());
+        Ok(())
+    }
+}
+
 fn parse_float_iee754<T, Binary: WasmBinary>(bin: &mut Binary) -> Result<T>
 where T: Float + Sized {
     let mut result = T::zero();
@@ -53,7 +67,7 @@ where T: Float + Sized {
 
 #[cfg(test)]
 mod test {
-    use crate::binary::WasmBinary;
+    use crate::binary::{WasmBinary, WasmBinaryEncode};
 
     #[test]
     fn can_parse_f32_pi() {
@@ -68,4 +82,18 @@ mod test {
         let result: f64 = iter.parse().unwrap();
         assert_eq!(result, std::f64::consts::PI);
     }
+
+    #[test]
+    fn can_encode_f32_pi() {
+        let mut bin = vec![];
+        std::f32::consts::PI.encode(&mut bin).unwrap();
+        assert_eq!(bin, [0xDB, 0x0F, 0x49, 0x40]);
+    }
+
+    #[test]
+    fn can_encode_f64_pi() {
+        let mut bin = vec![];
+        std::f64::consts::PI.encode(&mut bin).unwrap();
+        assert_eq!(bin, [0x18, 0x2D, 0x44, 0x54, 0xFB, 0x21, 0x09, 0x40]);
+    }
 }
\ No newline at end of file
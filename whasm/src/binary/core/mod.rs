@@ -4,9 +4,11 @@ pub mod array;
 pub mod byte;
 pub mod error;
 pub mod float;
+pub mod position;
 pub mod result;
 pub mod signed;
 pub mod sized;
+pub mod slice;
 pub mod string;
 pub mod traits;
 pub mod unsigned;
@@ -0,0 +1,75 @@
+//! This module defines an iterator adapter that keeps track of the position of the next byte
+//! to be read from a WebAssembly binary file.
+//! 
+//! The `Positioned` adapter wraps any `Iterator<Item=u8>` and counts the number of bytes read
+//! through it. It is used to report the byte offset at which parsing errors are found.
+//! 
+//! # Example
+//! 
+//! ```
+//! # use whasm::binary::{WasmBinary, Byte, Positioned};
+//! let mut iter = Positioned::new([0x2A, 0x2B, 0x2C].iter().copied());
+//! assert_eq!(iter.offset(), 0);
+//! let _: [Byte; 2] = iter.parse().unwrap();
+//! assert_eq!(iter.offset(), 2);
+//! ```
+//! 
+//! Peeking does not advance the position.
+//! 
+//! ```
+//! # use whasm::binary::{WasmBinary, Byte, Positioned};
+//! let mut iter = Positioned::new([0x2A, 0x2B].iter().copied());
+//! assert_eq!(iter.peek(), Some(&0x2A));
+//! assert_eq!(iter.offset(), 0);
+//! ```
+
+pub struct Positioned<I: Iterator<Item=u8>> {
+    iter: std::iter::Peekable<I>,
+    offset: usize,
+}
+
+impl<I: Iterator<Item=u8>> Positioned<I> {
+    pub fn new(iter: I) -> Self {
+        Self { iter: iter.peekable(), offset: 0 }
+    }
+
+    /// Creates an adapter whose offset starts at `offset` rather than 0.
+    pub fn starting_at(iter: I, offset: usize) -> Self {
+        Self { iter: iter.peekable(), offset }
+    }
+
+    /// Returns the number of bytes read so far, i.e., the offset of the next byte.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns a reference to the next byte without consuming it.
+    pub fn peek(&mut self) -> Option<&u8> {
+        self.iter.peek()
+    }
+}
+
+impl<I: Iterator<Item=u8>> Iterator for Positioned<I> {
+    type Item = u8;
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.iter.next()?;
+        self.offset += 1;
+        Some(byte)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary::{WasmBinary, Byte, Positioned};
+
+    #[test]
+    fn can_track_position() {
+        let mut iter = Positioned::new([0xE5, 0x8E, 0x26, 0x2A].iter().copied());
+        let _: u32 = iter.parse().unwrap();
+        assert_eq!(iter.offset(), 3);
+        let _: Byte = iter.parse().unwrap();
+        assert_eq!(iter.offset(), 4);
+        assert!(iter.parse::<Byte>().is_err());
+        assert_eq!(iter.offset(), 4);
+    }
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-//! This module defines the parsing of signed integer numbers.
+//! This module defines the parsing and encoding of signed integer numbers.
 //! 
 //! Integer numbers are encoded with the LEB-128 format.
 //! The signed integer types are `i8`, `i16`, `i32`, `i64` and `isize`.
@@ -32,8 +32,17 @@
 //! let result: Result<i8> = iter.parse();
 //! assert_eq!(result, Err(Error::OutOfRangeSignedInteger));
 //! ```
+//! 
+//! Encoding always uses the shortest LEB-128 representation of the number.
+//! 
+//! ```
+//! # use whasm::binary::WasmBinaryEncode;
+//! let mut bin = vec![];
+//! (-123456i32).encode(&mut bin).unwrap();
+//! assert_eq!(bin, [0xC0, 0xBB, 0x78]);
+//! ```
 
-use crate::binary::{WasmBinaryParse, WasmBinary, Result, Byte, Error};
+use crate::binary::{WasmBinaryParse, WasmBinary, WasmBinaryEncode, WasmBinarySink, Result, Byte, Error};
 use num_traits::*;
 
 impl WasmBinaryParse for i8 {
@@ -42,24 +51,48 @@ impl WasmBinaryParse for i8 {
     }
 }
 
+impl WasmBinaryEncode for i8 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_signed_leb_128(*self, bin)
+    }
+}
+
 impl WasmBinaryParse for i16 {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         parse_signed_leb_128(bin)
     }
 }
 
+impl WasmBinaryEncode for i16 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_signed_leb_128(*self, bin)
+    }
+}
+
 impl WasmBinaryParse for i32 {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         parse_signed_leb_128(bin)
     }
 }
 
+impl WasmBinaryEncode for i32 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_signed_leb_128(*self, bin)
+    }
+}
+
 impl WasmBinaryParse for i64 {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         parse_signed_leb_128(bin)
     }
 }
 
+impl WasmBinaryEncode for i64 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_signed_leb_128(*self, bin)
+    }
+}
+
 fn parse_signed_leb_128<T, Binary: WasmBinary>(bin: &mut Binary) -> Result<T>
 where T: PrimInt + Signed + FromPrimitive {
     let size = 8 * std::mem::size_of::<T>();
@@ -100,9 +133,26 @@ where T: PrimInt + Signed + FromPrimitive {
     Ok(result)
 }
 
+fn encode_signed_leb_128<T, Binary: WasmBinarySink>(val: T, bin: &mut Binary) -> Result<()>
+where T: PrimInt + Signed + FromPrimitive + ToPrimitive {
+    let mask = T::from_u8(0x7F).unwrap();
+    let mut val = val;
+    loop {
+        let byte = (val & mask).to_u8().unwrap();
+        val = val >> 7;
+        let sign = (byte & 0x40) != 0;
+        if (val.is_zero() && !sign) || (val == -T::one() && sign) {
+            Byte(byte).encode(bin)?;
+            break;
+        }
+        Byte(byte | 0x80).encode(bin)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
-    use crate::binary::{WasmBinary, Result, Error};
+    use crate::binary::{WasmBinary, WasmBinaryEncode, Result, Error};
 
     // spec positive examples
     #[test]
@@ -169,4 +219,27 @@ mod test {
         let result: Result<i8> = iter.parse();
         assert_eq!(result, Err(Error::OutOfRangeSignedInteger));
     }
+
+    // encoding
+    #[test]
+    fn can_encode_negative_i8() {
+        let mut bin = vec![];
+        (-2i8).encode(&mut bin).unwrap();
+        assert_eq!(bin, [0x7E]);
+    }
+
+    #[test]
+    fn can_encode_positive_i8_with_sign_bit() {
+        let mut bin = vec![];
+        106i8.encode(&mut bin).unwrap();
+        assert_eq!(bin, [0xEA, 0x00]);
+    }
+
+    #[test]
+    fn can_encode_and_parse_min_i64() {
+        let mut bin = vec![];
+        i64::MIN.encode(&mut bin).unwrap();
+        let result: i64 = bin.iter().copied().parse().unwrap();
+        assert_eq!(result, i64::MIN);
+    }
 }
\ No newline at end of file
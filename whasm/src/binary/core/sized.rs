@@ -1,4 +1,5 @@
-//! This module defines the parsing of elements whose binary encoding includes their size in bytes.
+//! This module defines the parsing and encoding of elements whose binary encoding includes their
+//! size in bytes.
 //! 
 //! The encoding of a `whasm::binary::core::sized::Sized<T>` proxy type starts with an `u32`
 //! indicating the size in bytes of the encoded value.
@@ -39,10 +40,21 @@
 //! let Byte(result) = iter.parse().unwrap();
 //! assert_eq!(result, 0x2C);
 //! ```
+//! 
+//! When encoding, the wrapped value is encoded first to find out its size in bytes.
+//! 
+//! ```
+//! # use whasm::binary::{WasmBinaryEncode, Byte};
+//! # use whasm::binary::core::sized::Sized;
+//! let mut bin = vec![];
+//! Sized([Byte(0x2A), Byte(0x2B)]).encode(&mut bin).unwrap();
+//! assert_eq!(bin, [0x02, 0x2A, 0x2B]);
+//! ```
 
 use crate::binary::{WasmBinary, WasmBinaryParse, WasmBinaryParseProxy, Result, Error};
+use crate::binary::{WasmBinarySink, WasmBinaryEncode};
 
-pub struct Sized<T> ( T );
+pub struct Sized<T> ( pub T );
 impl<T: WasmBinaryParse> WasmBinaryParse for Sized<T> {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let size: u32 = bin.parse()?;
@@ -62,6 +74,15 @@ impl<T: WasmBinaryParse> WasmBinaryParseProxy for Sized<T> {
     type Inner = T;
     fn unwrap(self) -> Self::Inner { self.0 }
 }
+impl<T: WasmBinaryEncode> WasmBinaryEncode for Sized<T> {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        let mut content = vec![];
+        self.0.encode(&mut content)?;
+        (content.len() as u32).encode(bin)?;
+        bin.extend(content);
+        Ok(())
+    }
+}
 
 impl<T: PartialEq> PartialEq<T> for Sized<T> {
     fn eq(&self, other: &T) -> bool {
@@ -90,7 +111,7 @@ impl WasmBinaryParse for Consume {
 
 #[cfg(test)]
 mod test {
-    use crate::binary::{WasmBinary, WasmBinaryParseProxy, Byte, Result, Error};
+    use crate::binary::{WasmBinary, WasmBinaryEncode, WasmBinaryParseProxy, Byte, Result, Error};
     use crate::binary::core::sized::{Sized, Consume};
 
     #[test]
@@ -112,4 +133,11 @@ mod test {
         let mut iter = [0x02, 0x01, 0x02].iter().copied();
         let _: Sized<Consume> = iter.parse().unwrap();
     }
+
+    #[test]
+    fn can_encode_sized_element() {
+        let mut bin = vec![];
+        Sized([Byte(0x01), Byte(0x02)]).encode(&mut bin).unwrap();
+        assert_eq!(bin, [0x02, 0x01, 0x02]);
+    }
 }
\ No newline at end of file
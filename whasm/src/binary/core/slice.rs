@@ -0,0 +1,311 @@
+//! This module defines a `WasmBinary` backed by a slice of bytes.
+//! 
+//! The `SliceBinary` type implements `Iterator<Item=u8>`, and therefore can be used to parse any
+//! element like any other `WasmBinary`.
+//! In addition, it provides fast paths that take advantage of having the whole input in memory:
+//! 
+//! * decoding LEB-128 integers without going through the generic byte-by-byte parser,
+//! * skipping elements in constant time,
+//! * borrowing names and raw bytes from the input (`&'a str` and `&'a [u8]`) without copying,
+//! * reporting the offset of the next byte to be read.
+//! 
+//! Modules parsed with `parse_slice` rather than `parse` use these fast paths for the framing of
+//! sections and function bodies, names and the payloads of custom sections and data segments.
+//! 
+//! Offsets are always relative to the start of the slice the `SliceBinary` was created from, also
+//! for the readers of sized elements returned by `read_sized`.
+//! 
+//! # Example
+//! 
+//! ```
+//! # use whasm::binary::{WasmBinary, SliceBinary};
+//! let bytes = [0x03, 0x61, 0x62, 0x63, 0xE5, 0x8E, 0x26, 0x2A];
+//! let mut bin = SliceBinary::new(&bytes);
+//! assert_eq!(bin.read_name().unwrap(), "abc");
+//! assert_eq!(bin.read_u32().unwrap(), 624485);
+//! assert_eq!(bin.offset(), 7);
+//! let result: u8 = bin.parse().unwrap();
+//! assert_eq!(result, 42);
+//! ```
+//! 
+//! Iterating over the sections of a module skips their content in constant time.
+//! 
+//! ```
+//! # use whasm::binary::SliceBinary;
+//! let bytes = [
+//!     0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00,
+//!     0x00, 0x04, 0x01, 0x61, 0x2A, 0x2B,
+//!     0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+//! ];
+//! let sections: Vec<_> = SliceBinary::new(&bytes).sections().unwrap()
+//!     .collect::<Result<_, _>>().unwrap();
+//! assert_eq!(sections.len(), 2);
+//! assert_eq!(sections[0].id, 0x00);
+//! assert_eq!(sections[0].custom().unwrap(), ("a", &[0x2A, 0x2B][..]));
+//! assert_eq!(sections[1].id, 0x01);
+//! assert_eq!(sections[1].content.offset(), 16);
+//! ```
+
+use crate::binary::{Result, Error};
+use crate::validation::Features;
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct SliceBinary<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    end: usize,
+}
+
+impl<'a> SliceBinary<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0, end: bytes.len() }
+    }
+
+    /// Returns the offset of the next byte to be read.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of bytes left.
+    pub fn remaining(&self) -> usize {
+        self.end - self.offset
+    }
+
+    /// Returns the bytes left without consuming them.
+    pub fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.offset..self.end]
+    }
+
+    /// Reads a raw byte.
+    pub fn read_byte(&mut self) -> Result<u8> {
+        self.next().ok_or(Error::UnexpectedEndOfFile)
+    }
+
+    /// Reads an `u32` encoded using LEB-128.
+    pub fn read_u32(&mut self) -> Result<u32> {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_byte()?;
+            let val = (byte & 0x7F) as u32;
+            if shift >= 32 {
+                // padding bytes are accepted as long as they carry no bits
+                if val != 0 {
+                    return Err(Error::OutOfRangeUnsignedInteger);
+                }
+            } else {
+                if (val << shift) >> shift != val {
+                    return Err(Error::OutOfRangeUnsignedInteger);
+                }
+                result |= val << shift;
+                shift += 7;
+            }
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+
+    /// Borrows the next `count` bytes.
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        if count > self.remaining() {
+            return Err(Error::UnexpectedEndOfFile);
+        }
+        let bytes = &self.bytes[self.offset..self.offset + count];
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    /// Borrows a vector of bytes, encoded as its length followed by its content.
+    pub fn read_vec(&mut self) -> Result<&'a [u8]> {
+        let count = self.read_u32()? as usize;
+        self.read_bytes(count)
+    }
+
+    /// Borrows a name, encoded as its length in bytes followed by its utf-8 encoding.
+    pub fn read_name(&mut self) -> Result<&'a str> {
+        let bytes = self.read_vec()?;
+        std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8Encoding)
+    }
+
+    /// Skips the next `count` bytes.
+    pub fn skip(&mut self, count: usize) -> Result<()> {
+        self.read_bytes(count)?;
+        Ok(())
+    }
+
+    /// Reads the size of a sized element and returns a reader limited to its content.
+    /// The content is skipped in this reader.
+    pub fn read_sized(&mut self) -> Result<SliceBinary<'a>> {
+        let size = self.read_u32()? as usize;
+        let offset = self.offset;
+        self.skip(size)?;
+        Ok(SliceBinary { bytes: self.bytes, offset, end: offset + size })
+    }
+
+    /// Parses an element with its dedicated slice parser, see `WasmSliceParse`.
+    pub fn parse_slice<T: WasmSliceParse>(&mut self) -> Result<T> {
+        T::parse_slice(self, Features::ALL)
+    }
+
+    /// Parses an element with its dedicated slice parser, rejecting the proposals that are not
+    /// enabled in `features`.
+    pub fn parse_slice_with<T: WasmSliceParse>(&mut self, features: Features) -> Result<T> {
+        T::parse_slice(self, features)
+    }
+
+    /// Checks the preamble of a module and returns an iterator over its sections.
+    pub fn sections(mut self) -> Result<Sections<'a>> {
+        if self.read_bytes(4)? != b"\0asm" {
+            return Err(Error::InvalidPreambleMagic);
+        }
+        if self.read_bytes(4)? != [1, 0, 0, 0] {
+            return Err(Error::InvalidPreambleVersion);
+        }
+        Ok(Sections { bin: self })
+    }
+}
+
+/// Elements with a dedicated parser for a `SliceBinary`, which frames sections with `read_sized`
+/// and borrows names and payloads instead of reading them byte by byte.
+/// 
+/// The module types `module::Module`, `Lazy<module::Module>` and `WithOffsets<module::Module>`
+/// implement this trait.
+pub trait WasmSliceParse: std::marker::Sized {
+    fn parse_slice(bin: &mut SliceBinary, features: Features) -> Result<Self>;
+}
+
+impl<'a> Iterator for SliceBinary<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.offset < self.end {
+            self.offset += 1;
+            Some(self.bytes[self.offset - 1])
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+
+    fn count(self) -> usize {
+        self.remaining()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<u8> {
+        self.offset += n.min(self.remaining());
+        self.next()
+    }
+}
+
+impl<'a> ExactSizeIterator for SliceBinary<'a> {
+}
+
+/// A section of a module whose content has not been parsed yet.
+#[derive(Debug)]
+pub struct RawSection<'a> {
+    pub id: u8,
+    /// Offset of the section id.
+    pub offset: usize,
+    pub content: SliceBinary<'a>,
+}
+
+impl<'a> RawSection<'a> {
+    /// Borrows the name and the payload of a custom section.
+    pub fn custom(&self) -> Result<(&'a str, &'a [u8])> {
+        let mut content = self.content.clone();
+        let name = content.read_name()?;
+        Ok((name, content.rest()))
+    }
+}
+
+/// An iterator over the sections of a module, returned by `SliceBinary::sections`.
+pub struct Sections<'a> {
+    bin: SliceBinary<'a>,
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = Result<RawSection<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bin.remaining() == 0 {
+            return None;
+        }
+        let offset = self.bin.offset();
+        let section = self.bin.read_byte()
+            .and_then(|id| Ok(RawSection { id, offset, content: self.bin.read_sized()? }))
+            .map_err(|error| error.at(self.bin.offset()));
+        if section.is_err() {
+            // stop after the first error
+            self.bin.offset = self.bin.end;
+        }
+        Some(section)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary::{WasmBinary, SliceBinary, Result, Error, module::Lazy, offsets::WithOffsets};
+    use crate::structure::module;
+
+    #[test]
+    fn can_read_leb_128() {
+        let bytes = [0x2A, 0xE5, 0x8E, 0x26, 0x80, 0x80, 0x80, 0x80, 0x10];
+        let mut bin = SliceBinary::new(&bytes);
+        assert_eq!(bin.read_u32(), Ok(42));
+        assert_eq!(bin.read_u32(), Ok(624485));
+        assert_eq!(bin.read_u32(), Err(Error::OutOfRangeUnsignedInteger));
+
+        // the same values as the generic parser, including padded and truncated encodings
+        for bytes in [&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F][..], &[0x80, 0x80, 0x00], &[0xFF, 0xFF, 0xFF, 0xFF, 0x8F, 0x00], &[0xE5, 0x8E]] {
+            assert_eq!(SliceBinary::new(bytes).read_u32(), bytes.iter().copied().parse::<u32>());
+        }
+    }
+
+    #[test]
+    fn can_borrow_bytes() {
+        let bytes = [0x02, 0x61, 0x62, 0x02, 0xFF, 0xFE, 0x01];
+        let mut bin = SliceBinary::new(&bytes);
+        assert_eq!(bin.read_name(), Ok("ab"));
+        assert_eq!(bin.read_vec(), Ok(&[0xFF, 0xFE][..]));
+        assert_eq!(bin.read_bytes(2), Err(Error::UnexpectedEndOfFile));
+        assert_eq!(bin.offset(), 6);
+    }
+
+    #[test]
+    fn can_read_sized_element() {
+        let bytes = [0x02, 0x2A, 0x2B, 0x2C];
+        let mut bin = SliceBinary::new(&bytes);
+        let mut sized = bin.read_sized().unwrap();
+        assert_eq!(bin.offset(), 3);
+        assert_eq!(sized.offset(), 1);
+        assert_eq!(sized.by_ref().collect::<Vec<_>>(), [0x2A, 0x2B]);
+        assert_eq!(sized.read_byte(), Err(Error::UnexpectedEndOfFile));
+        assert_eq!(bin.read_byte(), Ok(0x2C));
+    }
+
+    #[test]
+    fn can_parse_module_from_slice() {
+        let bytes = include_bytes!("../../../../test.wasm");
+        let result: module::Module = SliceBinary::new(bytes).parse().unwrap();
+        let expected: module::Module = bytes.iter().copied().parse().unwrap();
+        assert_eq!(result, expected);
+
+        let result: module::Module = SliceBinary::new(bytes).parse_slice().unwrap();
+        assert_eq!(result, expected);
+        let Lazy(result) = SliceBinary::new(bytes).parse_slice::<Lazy<module::Module>>().unwrap();
+        assert_eq!(result, expected);
+        let result: WithOffsets<module::Module> = SliceBinary::new(bytes).parse_slice().unwrap();
+        let WithOffsets(module, offsets): WithOffsets<module::Module> = bytes.iter().copied().parse().unwrap();
+        assert_eq!((result.0, result.1), (module, offsets));
+
+        let sections = SliceBinary::new(bytes).sections().unwrap()
+            .collect::<Result<Vec<_>>>().unwrap();
+        let end = sections.last().map(|section| section.content.offset() + section.content.remaining());
+        assert_eq!(end, Some(bytes.len()));
+    }
+}
\ No newline at end of file
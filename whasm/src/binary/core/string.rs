@@ -1,4 +1,4 @@
-//! This module defines the parsing of strings.
+//! This module defines the parsing and encoding of strings.
 //! 
 //! Strings are encoded as `Vec<Byte>` using UTF-8 encoding.
 //! 
@@ -14,10 +14,19 @@
 //! ```
 
 use crate::binary::{WasmBinaryParse, WasmBinary, Result, Byte, WasmBinaryParseProxy, UnwrappingVec};
+use crate::binary::{WasmBinarySink, WasmBinaryEncode};
 
 impl WasmBinaryParse for String {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let raw: UnwrappingVec<Byte> = bin.parse()?;
         Ok(String::from_utf8(raw.unwrap())?)
     }
-}
\ No newline at end of file
+}
+
+impl WasmBinaryEncode for String {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        (self.len() as u32).encode(bin)?;
+        bin.extend(self.bytes());
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
-//! This module defines a trait used during parsing of WebAssembly binary files.
+//! This module defines the traits used during parsing and encoding of WebAssembly binary files.
 
 use crate::binary::Result;
+use crate::validation::Features;
 
 /// This is a trait for types that can be used as input for parsing WebAssembly binary files.
 /// The trait is automatically applies to any type implementing `Iterator<Item=u8>`.
@@ -24,6 +25,11 @@ where Self: std::marker::Sized {
     fn parse<T: WasmBinaryParse>(&mut self) -> Result<T> {
         <T as WasmBinaryParse>::parse(self)
     }
+
+    /// Parses an element, rejecting the proposals that are not enabled in `features`.
+    fn parse_with<T: WasmBinaryParseWith>(&mut self, features: Features) -> Result<T> {
+        <T as WasmBinaryParseWith>::parse_with(self, features)
+    }
 }
 
 impl<T> WasmBinary for T
@@ -68,6 +74,33 @@ pub trait WasmBinaryParse where Self: std::marker::Sized {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self>;
 }
 
+/// This is a trait for parsing elements that make use of WebAssembly proposals, restricted to the
+/// proposals enabled in a `whasm::validation::Features`.
+/// 
+/// Parsing an element that uses a disabled proposal fails with `Error::FeatureDisabled`, which
+/// names the proposal.
+/// 
+/// # Example
+/// 
+/// ```
+/// # use whasm::binary::{WasmBinary, Error};
+/// # use whasm::structure::module;
+/// # use whasm::validation::Features;
+/// let bytes = [
+///     0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00,
+///     0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7B,
+/// ];
+/// let result: module::Module = bytes.iter().copied().parse_with(Features::WASM2).unwrap();
+/// assert_eq!(result.types.len(), 1);
+/// 
+/// let result = bytes.iter().copied().parse_with::<module::Module>(Features::MVP);
+/// let error = Error::FeatureDisabled { feature: "simd" }.at(8);
+/// assert_eq!(result.err(), Some(error));
+/// ```
+pub trait WasmBinaryParseWith where Self: std::marker::Sized {
+    fn parse_with<Binary: WasmBinary>(bin: &mut Binary, features: Features) -> Result<Self>;
+}
+
 /// This is a trait for proxy types: types that wrap other types to change their parsing rules.
 /// 
 /// Proxy types are intended to wrap another type to change the parsing rules of the wrapped type.
@@ -117,4 +150,53 @@ pub trait WasmBinaryParse where Self: std::marker::Sized {
 pub trait WasmBinaryParseProxy : WasmBinaryParse {
     type Inner;
     fn unwrap(self) -> Self::Inner;
+}
+
+/// This is a trait for types that can be used as output for encoding WebAssembly binary files.
+/// The trait is automatically applies to any type implementing `Extend<u8>`.
+/// 
+/// # Example
+/// 
+/// The `whasm::binary::Byte` implements the `WasmBinaryEncode`. It can be encoded into a
+/// `Vec<u8>` using its `encode` method.
+/// 
+/// ```
+/// # use whasm::binary::{WasmBinaryEncode, Byte};
+/// let mut bin = vec![];
+/// Byte(42).encode(&mut bin).unwrap();
+/// assert_eq!(bin, [0x2A])
+/// ```
+pub trait WasmBinarySink: Extend<u8> {
+}
+
+impl<T> WasmBinarySink for T
+where T: Extend<u8> {
+}
+
+/// This is a trait for encoding elements into a WebAssembly binary file.
+/// 
+/// This trait defines the `encode` method, which takes a `WasmBinarySink` and writes the binary
+/// representation of the element into it.
+/// It is the counterpart of `WasmBinaryParse`: encoding an element and parsing it back must
+/// result in the same element.
+/// 
+/// # Example
+/// 
+/// ```
+/// # use whasm::binary::{WasmBinary, WasmBinaryEncode};
+/// let mut bin = vec![];
+/// 624485u32.encode(&mut bin).unwrap();
+/// assert_eq!(bin, [0xE5, 0x8E, 0x26]);
+/// 
+/// let result: u32 = bin.iter().copied().parse().unwrap();
+/// assert_eq!(result, 624485);
+/// ```
+pub trait WasmBinaryEncode {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()>;
+}
+
+impl<T: WasmBinaryEncode + ?std::marker::Sized> WasmBinaryEncode for &T {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        (*self).encode(bin)
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-//! This module defines the parsing of unsigned integer numbers.
+//! This module defines the parsing and encoding of unsigned integer numbers.
 //! 
 //! Integer numbers are encoded with the LEB-128 format.
 //! The unsigned integer types are `i8`, `i16`, `i32`, `i64` and `isize`.
@@ -31,8 +31,17 @@
 //! let result: Result<u8> = iter.parse();
 //! assert_eq!(result, Err(Error::OutOfRangeUnsignedInteger));
 //! ```
+//! 
+//! Encoding always uses the shortest LEB-128 representation of the number.
+//! 
+//! ```
+//! # use whasm::binary::WasmBinaryEncode;
+//! let mut bin = vec![];
+//! 142u8.encode(&mut bin).unwrap();
+//! assert_eq!(bin, [0x8E, 0x01]);
+//! ```
 
-use crate::binary::{WasmBinaryParse, WasmBinary, Result, Byte, Error};
+use crate::binary::{WasmBinaryParse, WasmBinary, WasmBinaryEncode, WasmBinarySink, Result, Byte, Error};
 use num_traits::*;
 
 impl WasmBinaryParse for u8 {
@@ -41,24 +50,48 @@ impl WasmBinaryParse for u8 {
     }
 }
 
+impl WasmBinaryEncode for u8 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_unsigned_leb_128(*self, bin)
+    }
+}
+
 impl WasmBinaryParse for u16 {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         parse_unsigned_leb_128(bin)
     }
 }
 
+impl WasmBinaryEncode for u16 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_unsigned_leb_128(*self, bin)
+    }
+}
+
 impl WasmBinaryParse for u32 {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         parse_unsigned_leb_128(bin)
     }
 }
 
+impl WasmBinaryEncode for u32 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_unsigned_leb_128(*self, bin)
+    }
+}
+
 impl WasmBinaryParse for u64 {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         parse_unsigned_leb_128(bin)
     }
 }
 
+impl WasmBinaryEncode for u64 {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_unsigned_leb_128(*self, bin)
+    }
+}
+
 fn parse_unsigned_leb_128<T, Binary: WasmBinary>(bin: &mut Binary) -> Result<T>
 where T: PrimInt + Unsigned + FromPrimitive {
     let size = 8 * std::mem::size_of::<T>();
@@ -92,9 +125,25 @@ where T: PrimInt + Unsigned + FromPrimitive {
     Ok(result)
 }
 
+fn encode_unsigned_leb_128<T, Binary: WasmBinarySink>(val: T, bin: &mut Binary) -> Result<()>
+where T: PrimInt + Unsigned + FromPrimitive + ToPrimitive {
+    let mask = T::from_u8(0x7F).unwrap();
+    let mut val = val;
+    loop {
+        let byte = (val & mask).to_u8().unwrap();
+        val = val >> 7;
+        if val.is_zero() {
+            Byte(byte).encode(bin)?;
+            break;
+        }
+        Byte(byte | 0x80).encode(bin)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
-    use crate::binary::{WasmBinary, Result, Error};
+    use crate::binary::{WasmBinary, WasmBinaryEncode, Result, Error};
 
     // spec positive examples
     #[test]
@@ -133,4 +182,20 @@ mod test {
         let result: Result<u8> = iter.parse();
         assert_eq!(result, Err(Error::OutOfRangeUnsignedInteger));
     }
+
+    // encoding
+    #[test]
+    fn can_encode_spec_u32() {
+        let mut bin = vec![];
+        624485u32.encode(&mut bin).unwrap();
+        assert_eq!(bin, [0xE5, 0x8E, 0x26]);
+    }
+
+    #[test]
+    fn can_encode_and_parse_max_u64() {
+        let mut bin = vec![];
+        u64::MAX.encode(&mut bin).unwrap();
+        let result: u64 = bin.iter().copied().parse().unwrap();
+        assert_eq!(result, u64::MAX);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-//! This module defines the parsing of vectors.
+//! This module defines the parsing and encoding of vectors.
 //! 
 //! The following type of vectors are implemented in this module:
 //! 
@@ -49,8 +49,18 @@
 //! let result: Vec<u8> = iter.parse::<UnwrappingVec<Byte>>().unwrap().unwrap();
 //! assert_eq!(result, vec![0x2A, 0x8A, 0x42]);
 //! ```
+//! 
+//! When encoding a `CompactVec<_>`, consecutive repetitions of the same value are grouped.
+//! 
+//! ```
+//! # use whasm::binary::{WasmBinaryEncode, core::vec::CompactVec};
+//! let mut bin = vec![];
+//! CompactVec(vec![0x02u8, 0x04, 0x04, 0x04]).encode(&mut bin).unwrap();
+//! assert_eq!(bin, [0x02, 0x01, 0x02, 0x03, 0x04]);
+//! ```
 
 use crate::binary::{WasmBinary, WasmBinaryParse, WasmBinaryParseProxy, Result};
+use crate::binary::{WasmBinarySink, WasmBinaryEncode};
 
 impl<T: WasmBinaryParse> WasmBinaryParse for Vec<T> {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
@@ -63,7 +73,17 @@ impl<T: WasmBinaryParse> WasmBinaryParse for Vec<T> {
     }
 }
 
-pub struct CompactVec<T> ( Vec<T> );
+impl<T: WasmBinaryEncode> WasmBinaryEncode for Vec<T> {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        (self.len() as u32).encode(bin)?;
+        for elem in self.iter() {
+            elem.encode(bin)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct CompactVec<T> ( pub Vec<T> );
 impl<T: WasmBinaryParse + Copy> WasmBinaryParse for CompactVec<T> {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let mut result = vec![];
@@ -82,6 +102,23 @@ impl<T: WasmBinaryParse + Copy> WasmBinaryParseProxy for CompactVec<T> {
     type Inner = Vec<T>;
     fn unwrap(self) -> Self::Inner { self.0 }
 }
+impl<T: WasmBinaryEncode + PartialEq> WasmBinaryEncode for CompactVec<T> {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        let mut runs: Vec<(u32, &T)> = vec![];
+        for elem in self.0.iter() {
+            match runs.last_mut() {
+                Some((n, val)) if *val == elem => *n += 1,
+                _ => runs.push((1, elem)),
+            }
+        }
+        (runs.len() as u32).encode(bin)?;
+        for (n, val) in runs.iter() {
+            n.encode(bin)?;
+            val.encode(bin)?;
+        }
+        Ok(())
+    }
+}
 
 pub struct UnwrappingVec<T: WasmBinaryParseProxy> ( Vec<T::Inner> );
 impl<T: WasmBinaryParseProxy> WasmBinaryParse for UnwrappingVec<T> {
@@ -101,7 +138,7 @@ impl<T: WasmBinaryParseProxy> WasmBinaryParseProxy for UnwrappingVec<T> {
 
 #[cfg(test)]
 mod test {
-    use crate::binary::{WasmBinary, WasmBinaryParseProxy, Byte};
+    use crate::binary::{WasmBinary, WasmBinaryEncode, WasmBinaryParseProxy, Byte};
     use crate::binary::core::vec::{CompactVec, UnwrappingVec};
 
     #[test]
@@ -124,4 +161,18 @@ mod test {
         let result: Vec<u8> = iter.parse::<UnwrappingVec<Byte>>().unwrap().unwrap();
         assert_eq!(result, vec![42, 42, 42]);
     }
+
+    #[test]
+    fn can_serialize_vec() {
+        let mut bin = vec![];
+        vec![0x01u8, 0x02, 0x03, 0x04].encode(&mut bin).unwrap();
+        assert_eq!(bin, [0x04, 0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn can_serialize_compact_vec() {
+        let mut bin = vec![];
+        CompactVec(vec![42u8, 42, 142, 142, 142]).encode(&mut bin).unwrap();
+        assert_eq!(bin, [0x02, 0x02, 0x2A, 0x03, 0x8E, 0x01]);
+    }
 }
\ No newline at end of file
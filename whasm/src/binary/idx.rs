@@ -1,6 +1,6 @@
-//! This module defines the parsing of the different indexes found WebAssembly.
+//! This module defines the parsing and encoding of the different indexes found WebAssembly.
 
-use crate::binary::{WasmBinaryParse, WasmBinary, Result};
+use crate::binary::{WasmBinaryParse, WasmBinary, WasmBinaryEncode, WasmBinarySink, Result};
 use crate::structure::idx;
 
 impl WasmBinaryParse for idx::Type {
@@ -9,38 +9,128 @@ impl WasmBinaryParse for idx::Type {
     }
 }
 
+impl WasmBinaryEncode for idx::Type {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for idx::Func {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self(bin.parse()?))
     }
 }
 
+impl WasmBinaryEncode for idx::Func {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for idx::Table {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self(bin.parse()?))
     }
 }
 
+impl WasmBinaryEncode for idx::Table {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for idx::Mem {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self(bin.parse()?))
     }
 }
 
+impl WasmBinaryEncode for idx::Mem {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for idx::Global {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self(bin.parse()?))
     }
 }
 
+impl WasmBinaryEncode for idx::Global {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for idx::Elem {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self(bin.parse()?))
+    }
+}
+
+impl WasmBinaryEncode for idx::Elem {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for idx::Data {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self(bin.parse()?))
+    }
+}
+
+impl WasmBinaryEncode for idx::Data {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for idx::Tag {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self(bin.parse()?))
+    }
+}
+
+impl WasmBinaryEncode for idx::Tag {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for idx::Field {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self(bin.parse()?))
+    }
+}
+
+impl WasmBinaryEncode for idx::Field {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for idx::Local {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self(bin.parse()?))
     }
 }
 
+impl WasmBinaryEncode for idx::Local {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for idx::Label {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self(bin.parse()?))
     }
+}
+
+impl WasmBinaryEncode for idx::Label {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
 }
\ No newline at end of file
@@ -1,7 +1,9 @@
-//! This module defines the parsing of instructions and expressions found in WebAssembly.
+//! This module defines the parsing and encoding of instructions and expressions found in WebAssembly.
 
-use crate::binary::{WasmBinaryParse, WasmBinary, Result, Byte, Error};
-use crate::structure::{ty, instr, instr::Instr};
+use crate::binary::{WasmBinaryParse, WasmBinary, WasmBinaryEncode, WasmBinarySink, Result, Byte, Error};
+use crate::binary::Positioned;
+use std::ops::Range;
+use crate::structure::{ty, idx, instr, instr::Instr};
 
 impl WasmBinaryParse for instr::Expr {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
@@ -22,206 +24,1380 @@ impl WasmBinaryParse for instr::ConstExpr {
 
 impl WasmBinaryParse for instr::Instr {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
-        let Byte(byte) = bin.parse()?;
-        match byte {
+        let Byte(opcode) = bin.parse()?;
+        parse_opcode(opcode, bin)
+    }
+}
+
+/// Parses the immediates of the instruction with the given opcode.
+pub(crate) fn parse_opcode<Binary: WasmBinary>(opcode: u8, bin: &mut Binary) -> Result<Instr> {
+    match opcode {
+        // Control flow
+        0x00 => Ok(Instr::Unreachable),
+        0x01 => Ok(Instr::Nop),
+        0x02 => Ok(Instr::Block(bin.parse()?, bin.parse()?)),
+        0x03 => Ok(Instr::Loop(bin.parse()?, bin.parse()?)),
+        0x04 => Ok(bin.parse::<IfInstr>()?.into()),
+        0x05 => Ok(Instr::Else),
+        0x06 => parse_try(bin.parse()?, bin, |bin| bin.parse()),
+        0x07 => Ok(Instr::Catch(bin.parse()?)),
+        0x08 => Ok(Instr::Throw(bin.parse()?)),
+        0x09 => Ok(Instr::Rethrow(bin.parse()?)),
+        0x0A => Ok(Instr::ThrowRef),
+            // ... reserved ...
+        0x0B => Ok(Instr::End),
+        0x0C => Ok(Instr::Br(bin.parse()?)),
+        0x0D => Ok(Instr::BrIf(bin.parse()?)),
+        0x0E => Ok(Instr::BrTable(bin.parse()?, bin.parse()?)),
+        0x0F => Ok(Instr::Return),
+        0x10 => Ok(Instr::Call(bin.parse()?)),
+        0x11 => Ok(Instr::CallIndirect(bin.parse()?, bin.parse()?)),
+        0x12 => Ok(Instr::ReturnCall(bin.parse()?)),
+        0x13 => Ok(Instr::ReturnCallIndirect(bin.parse()?, bin.parse()?)),
+        0x14 => Ok(Instr::CallRef(bin.parse()?)),
+        0x15 => Ok(Instr::ReturnCallRef(bin.parse()?)),
+            // ... reserved ...
+        0x18 => Ok(Instr::Delegate(bin.parse()?)),
+        0x19 => Ok(Instr::CatchAll),
+            // ... reserved ...
+        // Parametric
+        0x1A => Ok(Instr::Drop),
+        0x1B => Ok(Instr::Select),
+        0x1C => Ok(Instr::SelectTyped(bin.parse()?)),
+            // ... reserved ...
+        // Exceptions
+        0x1F => Ok(Instr::TryTable(bin.parse()?, bin.parse()?, bin.parse()?)),
+            // ... reserved ...
+        // Variable
+        0x20 => Ok(Instr::LocalGet(bin.parse()?)),
+        0x21 => Ok(Instr::LocalSet(bin.parse()?)),
+        0x22 => Ok(Instr::LocalTee(bin.parse()?)),
+        0x23 => Ok(Instr::GlobalGet(bin.parse()?)),
+        0x24 => Ok(Instr::GlobalSet(bin.parse()?)),
+        // Table
+        0x25 => Ok(Instr::TableGet(bin.parse()?)),
+        0x26 => Ok(Instr::TableSet(bin.parse()?)),
+            // ... reserved ...
+        // Memory load
+        0x28 => Ok(Instr::I32Load(bin.parse()?)),
+        0x29 => Ok(Instr::I64Load(bin.parse()?)),
+        0x2A => Ok(Instr::F32Load(bin.parse()?)),
+        0x2B => Ok(Instr::F64Load(bin.parse()?)),
+        0x2C => Ok(Instr::I32Load8S(bin.parse()?)),
+        0x2D => Ok(Instr::I32Load8U(bin.parse()?)),
+        0x2E => Ok(Instr::I32Load16S(bin.parse()?)),
+        0x2F => Ok(Instr::I32Load16U(bin.parse()?)),
+        0x30 => Ok(Instr::I64Load8S(bin.parse()?)),
+        0x31 => Ok(Instr::I64Load8U(bin.parse()?)),
+        0x32 => Ok(Instr::I64Load16S(bin.parse()?)),
+        0x33 => Ok(Instr::I64Load16U(bin.parse()?)),
+        0x34 => Ok(Instr::I64Load32S(bin.parse()?)),
+        0x35 => Ok(Instr::I64Load32U(bin.parse()?)),
+        // Memory store
+        0x36 => Ok(Instr::I32Store(bin.parse()?)),
+        0x37 => Ok(Instr::I64Store(bin.parse()?)),
+        0x38 => Ok(Instr::F32Store(bin.parse()?)),
+        0x39 => Ok(Instr::F64Store(bin.parse()?)),
+        0x3A => Ok(Instr::I32Store8(bin.parse()?)),
+        0x3B => Ok(Instr::I32Store16(bin.parse()?)),
+        0x3C => Ok(Instr::I64Store8(bin.parse()?)),
+        0x3D => Ok(Instr::I64Store16(bin.parse()?)),
+        0x3E => Ok(Instr::I64Store32(bin.parse()?)),
+        // Memory management
+        0x3F => Ok(Instr::MemSize(bin.parse()?)),
+        0x40 => Ok(Instr::MemGrow(bin.parse()?)),
+        // Numeric
+        0x41 => Ok(Instr::ConstI32(bin.parse()?)),
+        0x42 => Ok(Instr::ConstI64(bin.parse()?)),
+        0x43 => Ok(Instr::ConstF32(bin.parse()?)),
+        0x44 => Ok(Instr::ConstF64(bin.parse()?)),
+        // Numeric without immediate -- i32
+        0x45 => Ok(Instr::I32Eqz),
+        0x46 => Ok(Instr::I32Eq),
+        0x47 => Ok(Instr::I32Ne),
+        0x48 => Ok(Instr::I32LtS),
+        0x49 => Ok(Instr::I32LtU),
+        0x4A => Ok(Instr::I32GtS),
+        0x4B => Ok(Instr::I32GtU),
+        0x4C => Ok(Instr::I32LeS),
+        0x4D => Ok(Instr::I32LeU),
+        0x4E => Ok(Instr::I32GeS),
+        0x4F => Ok(Instr::I32GeU),
+        // Numeric without immediate -- i64
+        0x50 => Ok(Instr::I64Eqz),
+        0x51 => Ok(Instr::I64Eq),
+        0x52 => Ok(Instr::I64Ne),
+        0x53 => Ok(Instr::I64LtS),
+        0x54 => Ok(Instr::I64LtU),
+        0x55 => Ok(Instr::I64GtS),
+        0x56 => Ok(Instr::I64GtU),
+        0x57 => Ok(Instr::I64LeS),
+        0x58 => Ok(Instr::I64LeU),
+        0x59 => Ok(Instr::I64GeS),
+        0x5A => Ok(Instr::I64GeU),
+        // Numeric without immediate -- f32
+        0x5B => Ok(Instr::F32Eq),
+        0x5C => Ok(Instr::F32Ne),
+        0x5D => Ok(Instr::F32Lt),
+        0x5E => Ok(Instr::F32Gt),
+        0x5F => Ok(Instr::F32Le),
+        0x60 => Ok(Instr::F32Ge),
+        // Numeric without immediate -- f64
+        0x61 => Ok(Instr::F64Eq),
+        0x62 => Ok(Instr::F64Ne),
+        0x63 => Ok(Instr::F64Lt),
+        0x64 => Ok(Instr::F64Gt),
+        0x65 => Ok(Instr::F64Le),
+        0x66 => Ok(Instr::F64Ge),
+        // Numeric without immediate -- i32 cont.
+        0x67 => Ok(Instr::I32Clz),
+        0x68 => Ok(Instr::I32Ctz),
+        0x69 => Ok(Instr::I32Popcnt),
+        0x6A => Ok(Instr::I32Add),
+        0x6B => Ok(Instr::I32Sub),
+        0x6C => Ok(Instr::I32Mul),
+        0x6D => Ok(Instr::I32DivS),
+        0x6E => Ok(Instr::I32DivU),
+        0x6F => Ok(Instr::I32RemS),
+        0x70 => Ok(Instr::I32RemU),
+        0x71 => Ok(Instr::I32And),
+        0x72 => Ok(Instr::I32Or),
+        0x73 => Ok(Instr::I32Xor),
+        0x74 => Ok(Instr::I32Shl),
+        0x75 => Ok(Instr::I32ShrS),
+        0x76 => Ok(Instr::I32ShrU),
+        0x77 => Ok(Instr::I32Rotl),
+        0x78 => Ok(Instr::I32Rotr),
+        // Numeric without immediate -- i64 cont.
+        0x79 => Ok(Instr::I64Clz),
+        0x7A => Ok(Instr::I64Ctz),
+        0x7B => Ok(Instr::I64Popcnt),
+        0x7C => Ok(Instr::I64Add),
+        0x7D => Ok(Instr::I64Sub),
+        0x7E => Ok(Instr::I64Mul),
+        0x7F => Ok(Instr::I64DivS),
+        0x80 => Ok(Instr::I64DivU),
+        0x81 => Ok(Instr::I64RemS),
+        0x82 => Ok(Instr::I64RemU),
+        0x83 => Ok(Instr::I64And),
+        0x84 => Ok(Instr::I64Or),
+        0x85 => Ok(Instr::I64Xor),
+        0x86 => Ok(Instr::I64Shl),
+        0x87 => Ok(Instr::I64ShrS),
+        0x88 => Ok(Instr::I64ShrU),
+        0x89 => Ok(Instr::I64Rotl),
+        0x8A => Ok(Instr::I64Rotr),
+        // Numeric without immediate -- f32 cont.
+        0x8B => Ok(Instr::F32Abs),
+        0x8C => Ok(Instr::F32Neg),
+        0x8D => Ok(Instr::F32Ceil),
+        0x8E => Ok(Instr::F32Floor),
+        0x8F => Ok(Instr::F32Trunc),
+        0x90 => Ok(Instr::F32Nearest),
+        0x91 => Ok(Instr::F32Sqrt),
+        0x92 => Ok(Instr::F32Add),
+        0x93 => Ok(Instr::F32Sub),
+        0x94 => Ok(Instr::F32Mul),
+        0x95 => Ok(Instr::F32Div),
+        0x96 => Ok(Instr::F32Min),
+        0x97 => Ok(Instr::F32Max),
+        0x98 => Ok(Instr::F32Copysign),
+        // Numeric without immediate -- f64 cont.
+        0x99 => Ok(Instr::F64Abs),
+        0x9A => Ok(Instr::F64Neg),
+        0x9B => Ok(Instr::F64Ceil),
+        0x9C => Ok(Instr::F64Floor),
+        0x9D => Ok(Instr::F64Trunc),
+        0x9E => Ok(Instr::F64Nearest),
+        0x9F => Ok(Instr::F64Sqrt),
+        0xA0 => Ok(Instr::F64Add),
+        0xA1 => Ok(Instr::F64Sub),
+        0xA2 => Ok(Instr::F64Mul),
+        0xA3 => Ok(Instr::F64Div),
+        0xA4 => Ok(Instr::F64Min),
+        0xA5 => Ok(Instr::F64Max),
+        0xA6 => Ok(Instr::F64Copysign),
+        // Numeric without immediate -- conversion
+        0xA7 => Ok(Instr::I32WrapI64),
+        0xA8 => Ok(Instr::I32TruncF32S),
+        0xA9 => Ok(Instr::I32TruncF32U),
+        0xAA => Ok(Instr::I32TruncF64S),
+        0xAB => Ok(Instr::I32TruncF64U),
+        0xAC => Ok(Instr::I64ExtendI32S),
+        0xAD => Ok(Instr::I64ExtendI32U),
+        0xAE => Ok(Instr::I64TruncF32S),
+        0xAF => Ok(Instr::I64TruncF32U),
+        0xB0 => Ok(Instr::I64TruncF64S),
+        0xB1 => Ok(Instr::I64TruncF64U),
+        0xB2 => Ok(Instr::F32ConvertI32S),
+        0xB3 => Ok(Instr::F32ConvertI32U),
+        0xB4 => Ok(Instr::F32ConvertI64S),
+        0xB5 => Ok(Instr::F32ConvertI64U),
+        0xB6 => Ok(Instr::F32DemoteF64),
+        0xB7 => Ok(Instr::F64ConvertI32S),
+        0xB8 => Ok(Instr::F64ConvertI32U),
+        0xB9 => Ok(Instr::F64ConvertI64S),
+        0xBA => Ok(Instr::F64ConvertI64U),
+        0xBB => Ok(Instr::F64PromoteF32),
+        0xBC => Ok(Instr::I32ReinterpretF32),
+        0xBD => Ok(Instr::I64ReinterpretF64),
+        0xBE => Ok(Instr::F32ReinterpretI32),
+        0xBF => Ok(Instr::F64ReinterpretI64),
+        // Sign extension
+        0xC0 => Ok(Instr::I32Extend8S),
+        0xC1 => Ok(Instr::I32Extend16S),
+        0xC2 => Ok(Instr::I64Extend8S),
+        0xC3 => Ok(Instr::I64Extend16S),
+        0xC4 => Ok(Instr::I64Extend32S),
+            // ... reserved ...
+        // Reference
+        0xD0 => Ok(Instr::RefNull(bin.parse()?)),
+        0xD1 => Ok(Instr::RefIsNull),
+        0xD2 => Ok(Instr::RefFunc(bin.parse()?)),
+        0xD3 => Ok(Instr::RefEq),
+        0xD4 => Ok(Instr::RefAsNonNull),
+        0xD5 => Ok(Instr::BrOnNull(bin.parse()?)),
+        0xD6 => Ok(Instr::BrOnNonNull(bin.parse()?)),
+            // ... reserved ...
+        // Prefixed
+        0xFB..=0xFE => {
+            let subopcode = bin.parse()?;
+            parse_prefixed_opcode(opcode, subopcode, bin)
+        },
+        id => Err(Error::InvalidVariantId{id, ty: "instr::Instr"})
+    }
+}
+
+/// Parses the immediates of the instruction with the given prefix byte and LEB-128 subopcode.
+fn parse_prefixed_opcode<Binary: WasmBinary>(prefix: u8, subopcode: u32, bin: &mut Binary) -> Result<Instr> {
+    match (prefix, subopcode) {
+        // Aggregate
+        (0xFB, 0x00) => Ok(Instr::StructNew(bin.parse()?)),
+        (0xFB, 0x01) => Ok(Instr::StructNewDefault(bin.parse()?)),
+        (0xFB, 0x02) => Ok(Instr::StructGet(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x03) => Ok(Instr::StructGetS(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x04) => Ok(Instr::StructGetU(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x05) => Ok(Instr::StructSet(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x06) => Ok(Instr::ArrayNew(bin.parse()?)),
+        (0xFB, 0x07) => Ok(Instr::ArrayNewDefault(bin.parse()?)),
+        (0xFB, 0x08) => Ok(Instr::ArrayNewFixed(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x09) => Ok(Instr::ArrayNewData(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x0A) => Ok(Instr::ArrayNewElem(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x0B) => Ok(Instr::ArrayGet(bin.parse()?)),
+        (0xFB, 0x0C) => Ok(Instr::ArrayGetS(bin.parse()?)),
+        (0xFB, 0x0D) => Ok(Instr::ArrayGetU(bin.parse()?)),
+        (0xFB, 0x0E) => Ok(Instr::ArraySet(bin.parse()?)),
+        (0xFB, 0x0F) => Ok(Instr::ArrayLen),
+        (0xFB, 0x10) => Ok(Instr::ArrayFill(bin.parse()?)),
+        (0xFB, 0x11) => Ok(Instr::ArrayCopy(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x12) => Ok(Instr::ArrayInitData(bin.parse()?, bin.parse()?)),
+        (0xFB, 0x13) => Ok(Instr::ArrayInitElem(bin.parse()?, bin.parse()?)),
+        // Casts
+        (0xFB, 0x14) => Ok(Instr::RefTest(ty::Ref { nullable: false, heap: bin.parse()? })),
+        (0xFB, 0x15) => Ok(Instr::RefTest(ty::Ref { nullable: true, heap: bin.parse()? })),
+        (0xFB, 0x16) => Ok(Instr::RefCast(ty::Ref { nullable: false, heap: bin.parse()? })),
+        (0xFB, 0x17) => Ok(Instr::RefCast(ty::Ref { nullable: true, heap: bin.parse()? })),
+        (0xFB, 0x18) => {
+            let (label, from, to) = parse_br_on_cast(bin)?;
+            Ok(Instr::BrOnCast(label, from, to))
+        },
+        (0xFB, 0x19) => {
+            let (label, from, to) = parse_br_on_cast(bin)?;
+            Ok(Instr::BrOnCastFail(label, from, to))
+        },
+        (0xFB, 0x1A) => Ok(Instr::AnyConvertExtern),
+        (0xFB, 0x1B) => Ok(Instr::ExternConvertAny),
+        // Scalar references
+        (0xFB, 0x1C) => Ok(Instr::RefI31),
+        (0xFB, 0x1D) => Ok(Instr::I31GetS),
+        (0xFB, 0x1E) => Ok(Instr::I31GetU),
+        // Saturating truncation
+        (0xFC, 0x00) => Ok(Instr::I32TruncSatF32S),
+        (0xFC, 0x01) => Ok(Instr::I32TruncSatF32U),
+        (0xFC, 0x02) => Ok(Instr::I32TruncSatF64S),
+        (0xFC, 0x03) => Ok(Instr::I32TruncSatF64U),
+        (0xFC, 0x04) => Ok(Instr::I64TruncSatF32S),
+        (0xFC, 0x05) => Ok(Instr::I64TruncSatF32U),
+        (0xFC, 0x06) => Ok(Instr::I64TruncSatF64S),
+        (0xFC, 0x07) => Ok(Instr::I64TruncSatF64U),
+        // Bulk memory
+        (0xFC, 0x08) => Ok(Instr::MemInit(bin.parse()?, bin.parse()?)),
+        (0xFC, 0x09) => Ok(Instr::DataDrop(bin.parse()?)),
+        (0xFC, 0x0A) => Ok(Instr::MemCopy(bin.parse()?, bin.parse()?)),
+        (0xFC, 0x0B) => Ok(Instr::MemFill(bin.parse()?)),
+        (0xFC, 0x0C) => Ok(Instr::TableInit(bin.parse()?, bin.parse()?)),
+        (0xFC, 0x0D) => Ok(Instr::ElemDrop(bin.parse()?)),
+        (0xFC, 0x0E) => Ok(Instr::TableCopy(bin.parse()?, bin.parse()?)),
+        // Table management
+        (0xFC, 0x0F) => Ok(Instr::TableGrow(bin.parse()?)),
+        (0xFC, 0x10) => Ok(Instr::TableSize(bin.parse()?)),
+        (0xFC, 0x11) => Ok(Instr::TableFill(bin.parse()?)),
+        // Vector
+        (0xFD, 0x00) => Ok(Instr::V128Load(bin.parse()?)),
+        (0xFD, 0x01) => Ok(Instr::V128Load8x8S(bin.parse()?)),
+        (0xFD, 0x02) => Ok(Instr::V128Load8x8U(bin.parse()?)),
+        (0xFD, 0x03) => Ok(Instr::V128Load16x4S(bin.parse()?)),
+        (0xFD, 0x04) => Ok(Instr::V128Load16x4U(bin.parse()?)),
+        (0xFD, 0x05) => Ok(Instr::V128Load32x2S(bin.parse()?)),
+        (0xFD, 0x06) => Ok(Instr::V128Load32x2U(bin.parse()?)),
+        (0xFD, 0x07) => Ok(Instr::V128Load8Splat(bin.parse()?)),
+        (0xFD, 0x08) => Ok(Instr::V128Load16Splat(bin.parse()?)),
+        (0xFD, 0x09) => Ok(Instr::V128Load32Splat(bin.parse()?)),
+        (0xFD, 0x0A) => Ok(Instr::V128Load64Splat(bin.parse()?)),
+        (0xFD, 0x0B) => Ok(Instr::V128Store(bin.parse()?)),
+        (0xFD, 0x0C) => Ok(Instr::ConstV128(u128::from_le_bytes(parse_bytes(bin)?))),
+        (0xFD, 0x0D) => Ok(Instr::I8x16Shuffle(parse_bytes(bin)?)),
+        (0xFD, 0x0E) => Ok(Instr::I8x16Swizzle),
+        (0xFD, 0x0F) => Ok(Instr::I8x16Splat),
+        (0xFD, 0x10) => Ok(Instr::I16x8Splat),
+        (0xFD, 0x11) => Ok(Instr::I32x4Splat),
+        (0xFD, 0x12) => Ok(Instr::I64x2Splat),
+        (0xFD, 0x13) => Ok(Instr::F32x4Splat),
+        (0xFD, 0x14) => Ok(Instr::F64x2Splat),
+        (0xFD, 0x15) => Ok(Instr::I8x16ExtractLaneS(parse_lane(bin)?)),
+        (0xFD, 0x16) => Ok(Instr::I8x16ExtractLaneU(parse_lane(bin)?)),
+        (0xFD, 0x17) => Ok(Instr::I8x16ReplaceLane(parse_lane(bin)?)),
+        (0xFD, 0x18) => Ok(Instr::I16x8ExtractLaneS(parse_lane(bin)?)),
+        (0xFD, 0x19) => Ok(Instr::I16x8ExtractLaneU(parse_lane(bin)?)),
+        (0xFD, 0x1A) => Ok(Instr::I16x8ReplaceLane(parse_lane(bin)?)),
+        (0xFD, 0x1B) => Ok(Instr::I32x4ExtractLane(parse_lane(bin)?)),
+        (0xFD, 0x1C) => Ok(Instr::I32x4ReplaceLane(parse_lane(bin)?)),
+        (0xFD, 0x1D) => Ok(Instr::I64x2ExtractLane(parse_lane(bin)?)),
+        (0xFD, 0x1E) => Ok(Instr::I64x2ReplaceLane(parse_lane(bin)?)),
+        (0xFD, 0x1F) => Ok(Instr::F32x4ExtractLane(parse_lane(bin)?)),
+        (0xFD, 0x20) => Ok(Instr::F32x4ReplaceLane(parse_lane(bin)?)),
+        (0xFD, 0x21) => Ok(Instr::F64x2ExtractLane(parse_lane(bin)?)),
+        (0xFD, 0x22) => Ok(Instr::F64x2ReplaceLane(parse_lane(bin)?)),
+        (0xFD, 0x23) => Ok(Instr::I8x16Eq),
+        (0xFD, 0x24) => Ok(Instr::I8x16Ne),
+        (0xFD, 0x25) => Ok(Instr::I8x16LtS),
+        (0xFD, 0x26) => Ok(Instr::I8x16LtU),
+        (0xFD, 0x27) => Ok(Instr::I8x16GtS),
+        (0xFD, 0x28) => Ok(Instr::I8x16GtU),
+        (0xFD, 0x29) => Ok(Instr::I8x16LeS),
+        (0xFD, 0x2A) => Ok(Instr::I8x16LeU),
+        (0xFD, 0x2B) => Ok(Instr::I8x16GeS),
+        (0xFD, 0x2C) => Ok(Instr::I8x16GeU),
+        (0xFD, 0x2D) => Ok(Instr::I16x8Eq),
+        (0xFD, 0x2E) => Ok(Instr::I16x8Ne),
+        (0xFD, 0x2F) => Ok(Instr::I16x8LtS),
+        (0xFD, 0x30) => Ok(Instr::I16x8LtU),
+        (0xFD, 0x31) => Ok(Instr::I16x8GtS),
+        (0xFD, 0x32) => Ok(Instr::I16x8GtU),
+        (0xFD, 0x33) => Ok(Instr::I16x8LeS),
+        (0xFD, 0x34) => Ok(Instr::I16x8LeU),
+        (0xFD, 0x35) => Ok(Instr::I16x8GeS),
+        (0xFD, 0x36) => Ok(Instr::I16x8GeU),
+        (0xFD, 0x37) => Ok(Instr::I32x4Eq),
+        (0xFD, 0x38) => Ok(Instr::I32x4Ne),
+        (0xFD, 0x39) => Ok(Instr::I32x4LtS),
+        (0xFD, 0x3A) => Ok(Instr::I32x4LtU),
+        (0xFD, 0x3B) => Ok(Instr::I32x4GtS),
+        (0xFD, 0x3C) => Ok(Instr::I32x4GtU),
+        (0xFD, 0x3D) => Ok(Instr::I32x4LeS),
+        (0xFD, 0x3E) => Ok(Instr::I32x4LeU),
+        (0xFD, 0x3F) => Ok(Instr::I32x4GeS),
+        (0xFD, 0x40) => Ok(Instr::I32x4GeU),
+        (0xFD, 0x41) => Ok(Instr::F32x4Eq),
+        (0xFD, 0x42) => Ok(Instr::F32x4Ne),
+        (0xFD, 0x43) => Ok(Instr::F32x4Lt),
+        (0xFD, 0x44) => Ok(Instr::F32x4Gt),
+        (0xFD, 0x45) => Ok(Instr::F32x4Le),
+        (0xFD, 0x46) => Ok(Instr::F32x4Ge),
+        (0xFD, 0x47) => Ok(Instr::F64x2Eq),
+        (0xFD, 0x48) => Ok(Instr::F64x2Ne),
+        (0xFD, 0x49) => Ok(Instr::F64x2Lt),
+        (0xFD, 0x4A) => Ok(Instr::F64x2Gt),
+        (0xFD, 0x4B) => Ok(Instr::F64x2Le),
+        (0xFD, 0x4C) => Ok(Instr::F64x2Ge),
+        (0xFD, 0x4D) => Ok(Instr::V128Not),
+        (0xFD, 0x4E) => Ok(Instr::V128And),
+        (0xFD, 0x4F) => Ok(Instr::V128AndNot),
+        (0xFD, 0x50) => Ok(Instr::V128Or),
+        (0xFD, 0x51) => Ok(Instr::V128Xor),
+        (0xFD, 0x52) => Ok(Instr::V128Bitselect),
+        (0xFD, 0x53) => Ok(Instr::V128AnyTrue),
+        (0xFD, 0x54) => Ok(Instr::V128Load8Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x55) => Ok(Instr::V128Load16Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x56) => Ok(Instr::V128Load32Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x57) => Ok(Instr::V128Load64Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x58) => Ok(Instr::V128Store8Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x59) => Ok(Instr::V128Store16Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x5A) => Ok(Instr::V128Store32Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x5B) => Ok(Instr::V128Store64Lane(bin.parse()?, parse_lane(bin)?)),
+        (0xFD, 0x5C) => Ok(Instr::V128Load32Zero(bin.parse()?)),
+        (0xFD, 0x5D) => Ok(Instr::V128Load64Zero(bin.parse()?)),
+        (0xFD, 0x5E) => Ok(Instr::F32x4DemoteF64x2Zero),
+        (0xFD, 0x5F) => Ok(Instr::F64x2PromoteLowF32x4),
+        (0xFD, 0x60) => Ok(Instr::I8x16Abs),
+        (0xFD, 0x61) => Ok(Instr::I8x16Neg),
+        (0xFD, 0x62) => Ok(Instr::I8x16Popcnt),
+        (0xFD, 0x63) => Ok(Instr::I8x16AllTrue),
+        (0xFD, 0x64) => Ok(Instr::I8x16Bitmask),
+        (0xFD, 0x65) => Ok(Instr::I8x16NarrowI16x8S),
+        (0xFD, 0x66) => Ok(Instr::I8x16NarrowI16x8U),
+        (0xFD, 0x67) => Ok(Instr::F32x4Ceil),
+        (0xFD, 0x68) => Ok(Instr::F32x4Floor),
+        (0xFD, 0x69) => Ok(Instr::F32x4Trunc),
+        (0xFD, 0x6A) => Ok(Instr::F32x4Nearest),
+        (0xFD, 0x6B) => Ok(Instr::I8x16Shl),
+        (0xFD, 0x6C) => Ok(Instr::I8x16ShrS),
+        (0xFD, 0x6D) => Ok(Instr::I8x16ShrU),
+        (0xFD, 0x6E) => Ok(Instr::I8x16Add),
+        (0xFD, 0x6F) => Ok(Instr::I8x16AddSatS),
+        (0xFD, 0x70) => Ok(Instr::I8x16AddSatU),
+        (0xFD, 0x71) => Ok(Instr::I8x16Sub),
+        (0xFD, 0x72) => Ok(Instr::I8x16SubSatS),
+        (0xFD, 0x73) => Ok(Instr::I8x16SubSatU),
+        (0xFD, 0x74) => Ok(Instr::F64x2Ceil),
+        (0xFD, 0x75) => Ok(Instr::F64x2Floor),
+        (0xFD, 0x76) => Ok(Instr::I8x16MinS),
+        (0xFD, 0x77) => Ok(Instr::I8x16MinU),
+        (0xFD, 0x78) => Ok(Instr::I8x16MaxS),
+        (0xFD, 0x79) => Ok(Instr::I8x16MaxU),
+        (0xFD, 0x7A) => Ok(Instr::F64x2Trunc),
+        (0xFD, 0x7B) => Ok(Instr::I8x16AvgrU),
+        (0xFD, 0x7C) => Ok(Instr::I16x8ExtaddPairwiseI8x16S),
+        (0xFD, 0x7D) => Ok(Instr::I16x8ExtaddPairwiseI8x16U),
+        (0xFD, 0x7E) => Ok(Instr::I32x4ExtaddPairwiseI16x8S),
+        (0xFD, 0x7F) => Ok(Instr::I32x4ExtaddPairwiseI16x8U),
+        (0xFD, 0x80) => Ok(Instr::I16x8Abs),
+        (0xFD, 0x81) => Ok(Instr::I16x8Neg),
+        (0xFD, 0x82) => Ok(Instr::I16x8Q15mulrSatS),
+        (0xFD, 0x83) => Ok(Instr::I16x8AllTrue),
+        (0xFD, 0x84) => Ok(Instr::I16x8Bitmask),
+        (0xFD, 0x85) => Ok(Instr::I16x8NarrowI32x4S),
+        (0xFD, 0x86) => Ok(Instr::I16x8NarrowI32x4U),
+        (0xFD, 0x87) => Ok(Instr::I16x8ExtendLowI8x16S),
+        (0xFD, 0x88) => Ok(Instr::I16x8ExtendHighI8x16S),
+        (0xFD, 0x89) => Ok(Instr::I16x8ExtendLowI8x16U),
+        (0xFD, 0x8A) => Ok(Instr::I16x8ExtendHighI8x16U),
+        (0xFD, 0x8B) => Ok(Instr::I16x8Shl),
+        (0xFD, 0x8C) => Ok(Instr::I16x8ShrS),
+        (0xFD, 0x8D) => Ok(Instr::I16x8ShrU),
+        (0xFD, 0x8E) => Ok(Instr::I16x8Add),
+        (0xFD, 0x8F) => Ok(Instr::I16x8AddSatS),
+        (0xFD, 0x90) => Ok(Instr::I16x8AddSatU),
+        (0xFD, 0x91) => Ok(Instr::I16x8Sub),
+        (0xFD, 0x92) => Ok(Instr::I16x8SubSatS),
+        (0xFD, 0x93) => Ok(Instr::I16x8SubSatU),
+        (0xFD, 0x94) => Ok(Instr::F64x2Nearest),
+        (0xFD, 0x95) => Ok(Instr::I16x8Mul),
+        (0xFD, 0x96) => Ok(Instr::I16x8MinS),
+        (0xFD, 0x97) => Ok(Instr::I16x8MinU),
+        (0xFD, 0x98) => Ok(Instr::I16x8MaxS),
+        (0xFD, 0x99) => Ok(Instr::I16x8MaxU),
+            // ... reserved ...
+        (0xFD, 0x9B) => Ok(Instr::I16x8AvgrU),
+        (0xFD, 0x9C) => Ok(Instr::I16x8ExtmulLowI8x16S),
+        (0xFD, 0x9D) => Ok(Instr::I16x8ExtmulHighI8x16S),
+        (0xFD, 0x9E) => Ok(Instr::I16x8ExtmulLowI8x16U),
+        (0xFD, 0x9F) => Ok(Instr::I16x8ExtmulHighI8x16U),
+        (0xFD, 0xA0) => Ok(Instr::I32x4Abs),
+        (0xFD, 0xA1) => Ok(Instr::I32x4Neg),
+            // ... reserved ...
+        (0xFD, 0xA3) => Ok(Instr::I32x4AllTrue),
+        (0xFD, 0xA4) => Ok(Instr::I32x4Bitmask),
+            // ... reserved ...
+        (0xFD, 0xA7) => Ok(Instr::I32x4ExtendLowI16x8S),
+        (0xFD, 0xA8) => Ok(Instr::I32x4ExtendHighI16x8S),
+        (0xFD, 0xA9) => Ok(Instr::I32x4ExtendLowI16x8U),
+        (0xFD, 0xAA) => Ok(Instr::I32x4ExtendHighI16x8U),
+        (0xFD, 0xAB) => Ok(Instr::I32x4Shl),
+        (0xFD, 0xAC) => Ok(Instr::I32x4ShrS),
+        (0xFD, 0xAD) => Ok(Instr::I32x4ShrU),
+        (0xFD, 0xAE) => Ok(Instr::I32x4Add),
+            // ... reserved ...
+        (0xFD, 0xB1) => Ok(Instr::I32x4Sub),
+            // ... reserved ...
+        (0xFD, 0xB5) => Ok(Instr::I32x4Mul),
+        (0xFD, 0xB6) => Ok(Instr::I32x4MinS),
+        (0xFD, 0xB7) => Ok(Instr::I32x4MinU),
+        (0xFD, 0xB8) => Ok(Instr::I32x4MaxS),
+        (0xFD, 0xB9) => Ok(Instr::I32x4MaxU),
+        (0xFD, 0xBA) => Ok(Instr::I32x4DotI16x8S),
+            // ... reserved ...
+        (0xFD, 0xBC) => Ok(Instr::I32x4ExtmulLowI16x8S),
+        (0xFD, 0xBD) => Ok(Instr::I32x4ExtmulHighI16x8S),
+        (0xFD, 0xBE) => Ok(Instr::I32x4ExtmulLowI16x8U),
+        (0xFD, 0xBF) => Ok(Instr::I32x4ExtmulHighI16x8U),
+        (0xFD, 0xC0) => Ok(Instr::I64x2Abs),
+        (0xFD, 0xC1) => Ok(Instr::I64x2Neg),
+            // ... reserved ...
+        (0xFD, 0xC3) => Ok(Instr::I64x2AllTrue),
+        (0xFD, 0xC4) => Ok(Instr::I64x2Bitmask),
+            // ... reserved ...
+        (0xFD, 0xC7) => Ok(Instr::I64x2ExtendLowI32x4S),
+        (0xFD, 0xC8) => Ok(Instr::I64x2ExtendHighI32x4S),
+        (0xFD, 0xC9) => Ok(Instr::I64x2ExtendLowI32x4U),
+        (0xFD, 0xCA) => Ok(Instr::I64x2ExtendHighI32x4U),
+        (0xFD, 0xCB) => Ok(Instr::I64x2Shl),
+        (0xFD, 0xCC) => Ok(Instr::I64x2ShrS),
+        (0xFD, 0xCD) => Ok(Instr::I64x2ShrU),
+        (0xFD, 0xCE) => Ok(Instr::I64x2Add),
+            // ... reserved ...
+        (0xFD, 0xD1) => Ok(Instr::I64x2Sub),
+            // ... reserved ...
+        (0xFD, 0xD5) => Ok(Instr::I64x2Mul),
+        (0xFD, 0xD6) => Ok(Instr::I64x2Eq),
+        (0xFD, 0xD7) => Ok(Instr::I64x2Ne),
+        (0xFD, 0xD8) => Ok(Instr::I64x2LtS),
+        (0xFD, 0xD9) => Ok(Instr::I64x2GtS),
+        (0xFD, 0xDA) => Ok(Instr::I64x2LeS),
+        (0xFD, 0xDB) => Ok(Instr::I64x2GeS),
+        (0xFD, 0xDC) => Ok(Instr::I64x2ExtmulLowI32x4S),
+        (0xFD, 0xDD) => Ok(Instr::I64x2ExtmulHighI32x4S),
+        (0xFD, 0xDE) => Ok(Instr::I64x2ExtmulLowI32x4U),
+        (0xFD, 0xDF) => Ok(Instr::I64x2ExtmulHighI32x4U),
+        (0xFD, 0xE0) => Ok(Instr::F32x4Abs),
+        (0xFD, 0xE1) => Ok(Instr::F32x4Neg),
+            // ... reserved ...
+        (0xFD, 0xE3) => Ok(Instr::F32x4Sqrt),
+        (0xFD, 0xE4) => Ok(Instr::F32x4Add),
+        (0xFD, 0xE5) => Ok(Instr::F32x4Sub),
+        (0xFD, 0xE6) => Ok(Instr::F32x4Mul),
+        (0xFD, 0xE7) => Ok(Instr::F32x4Div),
+        (0xFD, 0xE8) => Ok(Instr::F32x4Min),
+        (0xFD, 0xE9) => Ok(Instr::F32x4Max),
+        (0xFD, 0xEA) => Ok(Instr::F32x4Pmin),
+        (0xFD, 0xEB) => Ok(Instr::F32x4Pmax),
+        (0xFD, 0xEC) => Ok(Instr::F64x2Abs),
+        (0xFD, 0xED) => Ok(Instr::F64x2Neg),
+            // ... reserved ...
+        (0xFD, 0xEF) => Ok(Instr::F64x2Sqrt),
+        (0xFD, 0xF0) => Ok(Instr::F64x2Add),
+        (0xFD, 0xF1) => Ok(Instr::F64x2Sub),
+        (0xFD, 0xF2) => Ok(Instr::F64x2Mul),
+        (0xFD, 0xF3) => Ok(Instr::F64x2Div),
+        (0xFD, 0xF4) => Ok(Instr::F64x2Min),
+        (0xFD, 0xF5) => Ok(Instr::F64x2Max),
+        (0xFD, 0xF6) => Ok(Instr::F64x2Pmin),
+        (0xFD, 0xF7) => Ok(Instr::F64x2Pmax),
+        (0xFD, 0xF8) => Ok(Instr::I32x4TruncSatF32x4S),
+        (0xFD, 0xF9) => Ok(Instr::I32x4TruncSatF32x4U),
+        (0xFD, 0xFA) => Ok(Instr::F32x4ConvertI32x4S),
+        (0xFD, 0xFB) => Ok(Instr::F32x4ConvertI32x4U),
+        (0xFD, 0xFC) => Ok(Instr::I32x4TruncSatF64x2SZero),
+        (0xFD, 0xFD) => Ok(Instr::I32x4TruncSatF64x2UZero),
+        (0xFD, 0xFE) => Ok(Instr::F64x2ConvertLowI32x4S),
+        (0xFD, 0xFF) => Ok(Instr::F64x2ConvertLowI32x4U),
+        // Relaxed vector
+        (0xFD, 0x100) => Ok(Instr::I8x16RelaxedSwizzle),
+        (0xFD, 0x101) => Ok(Instr::I32x4RelaxedTruncF32x4S),
+        (0xFD, 0x102) => Ok(Instr::I32x4RelaxedTruncF32x4U),
+        (0xFD, 0x103) => Ok(Instr::I32x4RelaxedTruncF64x2SZero),
+        (0xFD, 0x104) => Ok(Instr::I32x4RelaxedTruncF64x2UZero),
+        (0xFD, 0x105) => Ok(Instr::F32x4RelaxedMadd),
+        (0xFD, 0x106) => Ok(Instr::F32x4RelaxedNmadd),
+        (0xFD, 0x107) => Ok(Instr::F64x2RelaxedMadd),
+        (0xFD, 0x108) => Ok(Instr::F64x2RelaxedNmadd),
+        (0xFD, 0x109) => Ok(Instr::I8x16RelaxedLaneselect),
+        (0xFD, 0x10A) => Ok(Instr::I16x8RelaxedLaneselect),
+        (0xFD, 0x10B) => Ok(Instr::I32x4RelaxedLaneselect),
+        (0xFD, 0x10C) => Ok(Instr::I64x2RelaxedLaneselect),
+        (0xFD, 0x10D) => Ok(Instr::F32x4RelaxedMin),
+        (0xFD, 0x10E) => Ok(Instr::F32x4RelaxedMax),
+        (0xFD, 0x10F) => Ok(Instr::F64x2RelaxedMin),
+        (0xFD, 0x110) => Ok(Instr::F64x2RelaxedMax),
+        (0xFD, 0x111) => Ok(Instr::I16x8RelaxedQ15mulrS),
+        (0xFD, 0x112) => Ok(Instr::I16x8RelaxedDotI8x16I7x16S),
+        (0xFD, 0x113) => Ok(Instr::I32x4RelaxedDotI8x16I7x16AddS),
+        // Atomic memory
+        (0xFE, 0x00) => Ok(Instr::MemAtomicNotify(bin.parse()?)),
+        (0xFE, 0x01) => Ok(Instr::MemAtomicWait32(bin.parse()?)),
+        (0xFE, 0x02) => Ok(Instr::MemAtomicWait64(bin.parse()?)),
+        (0xFE, 0x03) => {
+            let Byte(byte) = bin.parse()?;
+            match byte {
+                0x00 => Ok(Instr::AtomicFence),
+                id => Err(Error::InvalidVariantId{id, ty: "instr::Instr"}),
+            }
+        },
+            // ... reserved ...
+        (0xFE, 0x10) => Ok(Instr::I32AtomicLoad(bin.parse()?)),
+        (0xFE, 0x11) => Ok(Instr::I64AtomicLoad(bin.parse()?)),
+        (0xFE, 0x12) => Ok(Instr::I32AtomicLoad8U(bin.parse()?)),
+        (0xFE, 0x13) => Ok(Instr::I32AtomicLoad16U(bin.parse()?)),
+        (0xFE, 0x14) => Ok(Instr::I64AtomicLoad8U(bin.parse()?)),
+        (0xFE, 0x15) => Ok(Instr::I64AtomicLoad16U(bin.parse()?)),
+        (0xFE, 0x16) => Ok(Instr::I64AtomicLoad32U(bin.parse()?)),
+        (0xFE, 0x17) => Ok(Instr::I32AtomicStore(bin.parse()?)),
+        (0xFE, 0x18) => Ok(Instr::I64AtomicStore(bin.parse()?)),
+        (0xFE, 0x19) => Ok(Instr::I32AtomicStore8(bin.parse()?)),
+        (0xFE, 0x1A) => Ok(Instr::I32AtomicStore16(bin.parse()?)),
+        (0xFE, 0x1B) => Ok(Instr::I64AtomicStore8(bin.parse()?)),
+        (0xFE, 0x1C) => Ok(Instr::I64AtomicStore16(bin.parse()?)),
+        (0xFE, 0x1D) => Ok(Instr::I64AtomicStore32(bin.parse()?)),
+        (0xFE, 0x1E) => Ok(Instr::I32AtomicRmwAdd(bin.parse()?)),
+        (0xFE, 0x1F) => Ok(Instr::I64AtomicRmwAdd(bin.parse()?)),
+        (0xFE, 0x20) => Ok(Instr::I32AtomicRmw8AddU(bin.parse()?)),
+        (0xFE, 0x21) => Ok(Instr::I32AtomicRmw16AddU(bin.parse()?)),
+        (0xFE, 0x22) => Ok(Instr::I64AtomicRmw8AddU(bin.parse()?)),
+        (0xFE, 0x23) => Ok(Instr::I64AtomicRmw16AddU(bin.parse()?)),
+        (0xFE, 0x24) => Ok(Instr::I64AtomicRmw32AddU(bin.parse()?)),
+        (0xFE, 0x25) => Ok(Instr::I32AtomicRmwSub(bin.parse()?)),
+        (0xFE, 0x26) => Ok(Instr::I64AtomicRmwSub(bin.parse()?)),
+        (0xFE, 0x27) => Ok(Instr::I32AtomicRmw8SubU(bin.parse()?)),
+        (0xFE, 0x28) => Ok(Instr::I32AtomicRmw16SubU(bin.parse()?)),
+        (0xFE, 0x29) => Ok(Instr::I64AtomicRmw8SubU(bin.parse()?)),
+        (0xFE, 0x2A) => Ok(Instr::I64AtomicRmw16SubU(bin.parse()?)),
+        (0xFE, 0x2B) => Ok(Instr::I64AtomicRmw32SubU(bin.parse()?)),
+        (0xFE, 0x2C) => Ok(Instr::I32AtomicRmwAnd(bin.parse()?)),
+        (0xFE, 0x2D) => Ok(Instr::I64AtomicRmwAnd(bin.parse()?)),
+        (0xFE, 0x2E) => Ok(Instr::I32AtomicRmw8AndU(bin.parse()?)),
+        (0xFE, 0x2F) => Ok(Instr::I32AtomicRmw16AndU(bin.parse()?)),
+        (0xFE, 0x30) => Ok(Instr::I64AtomicRmw8AndU(bin.parse()?)),
+        (0xFE, 0x31) => Ok(Instr::I64AtomicRmw16AndU(bin.parse()?)),
+        (0xFE, 0x32) => Ok(Instr::I64AtomicRmw32AndU(bin.parse()?)),
+        (0xFE, 0x33) => Ok(Instr::I32AtomicRmwOr(bin.parse()?)),
+        (0xFE, 0x34) => Ok(Instr::I64AtomicRmwOr(bin.parse()?)),
+        (0xFE, 0x35) => Ok(Instr::I32AtomicRmw8OrU(bin.parse()?)),
+        (0xFE, 0x36) => Ok(Instr::I32AtomicRmw16OrU(bin.parse()?)),
+        (0xFE, 0x37) => Ok(Instr::I64AtomicRmw8OrU(bin.parse()?)),
+        (0xFE, 0x38) => Ok(Instr::I64AtomicRmw16OrU(bin.parse()?)),
+        (0xFE, 0x39) => Ok(Instr::I64AtomicRmw32OrU(bin.parse()?)),
+        (0xFE, 0x3A) => Ok(Instr::I32AtomicRmwXor(bin.parse()?)),
+        (0xFE, 0x3B) => Ok(Instr::I64AtomicRmwXor(bin.parse()?)),
+        (0xFE, 0x3C) => Ok(Instr::I32AtomicRmw8XorU(bin.parse()?)),
+        (0xFE, 0x3D) => Ok(Instr::I32AtomicRmw16XorU(bin.parse()?)),
+        (0xFE, 0x3E) => Ok(Instr::I64AtomicRmw8XorU(bin.parse()?)),
+        (0xFE, 0x3F) => Ok(Instr::I64AtomicRmw16XorU(bin.parse()?)),
+        (0xFE, 0x40) => Ok(Instr::I64AtomicRmw32XorU(bin.parse()?)),
+        (0xFE, 0x41) => Ok(Instr::I32AtomicRmwXchg(bin.parse()?)),
+        (0xFE, 0x42) => Ok(Instr::I64AtomicRmwXchg(bin.parse()?)),
+        (0xFE, 0x43) => Ok(Instr::I32AtomicRmw8XchgU(bin.parse()?)),
+        (0xFE, 0x44) => Ok(Instr::I32AtomicRmw16XchgU(bin.parse()?)),
+        (0xFE, 0x45) => Ok(Instr::I64AtomicRmw8XchgU(bin.parse()?)),
+        (0xFE, 0x46) => Ok(Instr::I64AtomicRmw16XchgU(bin.parse()?)),
+        (0xFE, 0x47) => Ok(Instr::I64AtomicRmw32XchgU(bin.parse()?)),
+        (0xFE, 0x48) => Ok(Instr::I32AtomicRmwCmpxchg(bin.parse()?)),
+        (0xFE, 0x49) => Ok(Instr::I64AtomicRmwCmpxchg(bin.parse()?)),
+        (0xFE, 0x4A) => Ok(Instr::I32AtomicRmw8CmpxchgU(bin.parse()?)),
+        (0xFE, 0x4B) => Ok(Instr::I32AtomicRmw16CmpxchgU(bin.parse()?)),
+        (0xFE, 0x4C) => Ok(Instr::I64AtomicRmw8CmpxchgU(bin.parse()?)),
+        (0xFE, 0x4D) => Ok(Instr::I64AtomicRmw16CmpxchgU(bin.parse()?)),
+        (0xFE, 0x4E) => Ok(Instr::I64AtomicRmw32CmpxchgU(bin.parse()?)),
+            // ... reserved ...
+        (prefix, subopcode) => Err(Error::InvalidSubopcode{prefix, subopcode, ty: "instr::Instr"})
+    }
+}
+
+impl WasmBinaryEncode for instr::Expr {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_instrs(&self.0, bin)?;
+        Byte(0x0B).encode(bin)
+    }
+}
+
+impl WasmBinaryEncode for instr::ConstExpr {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        encode_instrs(&self.0, bin)?;
+        Byte(0x0B).encode(bin)
+    }
+}
+
+impl WasmBinaryEncode for instr::Instr {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
             // Control flow
-            0x00 => Ok(Instr::Unreachable),
-            0x01 => Ok(Instr::Nop),
-            0x02 => Ok(Instr::Block(bin.parse()?, bin.parse()?)),
-            0x03 => Ok(Instr::Loop(bin.parse()?, bin.parse()?)),
-            0x04 => Ok(bin.parse::<IfInstr>()?.into()),
-            0x05 => Ok(Instr::Else),
-                // ... reserved ...
-            0x0B => Ok(Instr::End),
-            0x0C => Ok(Instr::Br(bin.parse()?)),
-            0x0D => Ok(Instr::BrIf(bin.parse()?)),
-            0x0E => Ok(Instr::BrTable(bin.parse()?, bin.parse()?)),
-            0x0F => Ok(Instr::Return),
-            0x10 => Ok(Instr::Call(bin.parse()?)),
-            0x11 => Ok(Instr::CallIndirect(bin.parse()?, bin.parse()?)),
+            Instr::Unreachable => Byte(0x00).encode(bin),
+            Instr::Nop => Byte(0x01).encode(bin),
+            Instr::Block(ret, expr) => { Byte(0x02).encode(bin)?; ret.encode(bin)?; expr.encode(bin) },
+            Instr::Loop(ret, expr) => { Byte(0x03).encode(bin)?; ret.encode(bin)?; expr.encode(bin) },
+            Instr::If(ret, expr1, expr2) => {
+                Byte(0x04).encode(bin)?;
+                ret.encode(bin)?;
+                encode_instrs(&expr1.0, bin)?;
+                if !expr2.0.is_empty() {
+                    Byte(0x05).encode(bin)?;
+                    encode_instrs(&expr2.0, bin)?;
+                }
+                Byte(0x0B).encode(bin)
+            },
+            Instr::Else => Byte(0x05).encode(bin),
+            Instr::Try(ret, body, catches, catch_all) => {
+                Byte(0x06).encode(bin)?;
+                ret.encode(bin)?;
+                encode_instrs(&body.0, bin)?;
+                for (tag, expr) in catches.iter() {
+                    Byte(0x07).encode(bin)?;
+                    tag.encode(bin)?;
+                    encode_instrs(&expr.0, bin)?;
+                }
+                if let Some(expr) = catch_all {
+                    Byte(0x19).encode(bin)?;
+                    encode_instrs(&expr.0, bin)?;
+                }
+                Byte(0x0B).encode(bin)
+            },
+            Instr::TryDelegate(ret, body, label) => {
+                Byte(0x06).encode(bin)?;
+                ret.encode(bin)?;
+                encode_instrs(&body.0, bin)?;
+                Byte(0x18).encode(bin)?;
+                label.encode(bin)
+            },
+            Instr::Catch(tag) => { Byte(0x07).encode(bin)?; tag.encode(bin) },
+            Instr::Throw(tag) => { Byte(0x08).encode(bin)?; tag.encode(bin) },
+            Instr::Rethrow(label) => { Byte(0x09).encode(bin)?; label.encode(bin) },
+            Instr::ThrowRef => Byte(0x0A).encode(bin),
+            Instr::End => Byte(0x0B).encode(bin),
+            Instr::Br(label) => { Byte(0x0C).encode(bin)?; label.encode(bin) },
+            Instr::BrIf(label) => { Byte(0x0D).encode(bin)?; label.encode(bin) },
+            Instr::BrTable(idxs, label) => { Byte(0x0E).encode(bin)?; idxs.encode(bin)?; label.encode(bin) },
+            Instr::Return => Byte(0x0F).encode(bin),
+            Instr::Call(func) => { Byte(0x10).encode(bin)?; func.encode(bin) },
+            Instr::CallIndirect(ty, table) => { Byte(0x11).encode(bin)?; ty.encode(bin)?; table.encode(bin) },
+            Instr::ReturnCall(func) => { Byte(0x12).encode(bin)?; func.encode(bin) },
+            Instr::ReturnCallIndirect(ty, table) => { Byte(0x13).encode(bin)?; ty.encode(bin)?; table.encode(bin) },
+            Instr::CallRef(ty) => { Byte(0x14).encode(bin)?; ty.encode(bin) },
+            Instr::ReturnCallRef(ty) => { Byte(0x15).encode(bin)?; ty.encode(bin) },
                 // ... reserved ...
+            Instr::Delegate(label) => { Byte(0x18).encode(bin)?; label.encode(bin) },
+            Instr::CatchAll => Byte(0x19).encode(bin),
             // Parametric
-            0x1A => Ok(Instr::Drop),
-            0x1B => Ok(Instr::Select),
+            Instr::Drop => Byte(0x1A).encode(bin),
+            Instr::Select => Byte(0x1B).encode(bin),
+            Instr::SelectTyped(vals) => { Byte(0x1C).encode(bin)?; vals.encode(bin) },
                 // ... reserved ...
+            // Exceptions
+            Instr::TryTable(ret, catches, expr) => { Byte(0x1F).encode(bin)?; ret.encode(bin)?; catches.encode(bin)?; expr.encode(bin) },
             // Variable
-            0x20 => Ok(Instr::LocalGet(bin.parse()?)),
-            0x21 => Ok(Instr::LocalSet(bin.parse()?)),
-            0x22 => Ok(Instr::LocalTee(bin.parse()?)),
-            0x23 => Ok(Instr::GlobalGet(bin.parse()?)),
-            0x24 => Ok(Instr::GlobalSet(bin.parse()?)),
+            Instr::LocalGet(idx) => { Byte(0x20).encode(bin)?; idx.encode(bin) },
+            Instr::LocalSet(idx) => { Byte(0x21).encode(bin)?; idx.encode(bin) },
+            Instr::LocalTee(idx) => { Byte(0x22).encode(bin)?; idx.encode(bin) },
+            Instr::GlobalGet(idx) => { Byte(0x23).encode(bin)?; idx.encode(bin) },
+            Instr::GlobalSet(idx) => { Byte(0x24).encode(bin)?; idx.encode(bin) },
+            // Table
+            Instr::TableGet(idx) => { Byte(0x25).encode(bin)?; idx.encode(bin) },
+            Instr::TableSet(idx) => { Byte(0x26).encode(bin)?; idx.encode(bin) },
                 // ... reserved ...
             // Memory load
-            0x28 => Ok(Instr::I32Load(bin.parse()?, bin.parse()?)),
-            0x29 => Ok(Instr::I64Load(bin.parse()?, bin.parse()?)),
-            0x2A => Ok(Instr::F32Load(bin.parse()?, bin.parse()?)),
-            0x2B => Ok(Instr::F64Load(bin.parse()?, bin.parse()?)),
-            0x2C => Ok(Instr::I32Load8S(bin.parse()?, bin.parse()?)),
-            0x2D => Ok(Instr::I32Load8U(bin.parse()?, bin.parse()?)),
-            0x2E => Ok(Instr::I32Load16S(bin.parse()?, bin.parse()?)),
-            0x2F => Ok(Instr::I32Load16U(bin.parse()?, bin.parse()?)),
-            0x30 => Ok(Instr::I64Load8S(bin.parse()?, bin.parse()?)),
-            0x31 => Ok(Instr::I64Load8U(bin.parse()?, bin.parse()?)),
-            0x32 => Ok(Instr::I64Load16S(bin.parse()?, bin.parse()?)),
-            0x33 => Ok(Instr::I64Load16U(bin.parse()?, bin.parse()?)),
-            0x34 => Ok(Instr::I64Load32S(bin.parse()?, bin.parse()?)),
-            0x35 => Ok(Instr::I64Load32U(bin.parse()?, bin.parse()?)),
+            Instr::I32Load(memarg) => { Byte(0x28).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Load(memarg) => { Byte(0x29).encode(bin)?; memarg.encode(bin) },
+            Instr::F32Load(memarg) => { Byte(0x2A).encode(bin)?; memarg.encode(bin) },
+            Instr::F64Load(memarg) => { Byte(0x2B).encode(bin)?; memarg.encode(bin) },
+            Instr::I32Load8S(memarg) => { Byte(0x2C).encode(bin)?; memarg.encode(bin) },
+            Instr::I32Load8U(memarg) => { Byte(0x2D).encode(bin)?; memarg.encode(bin) },
+            Instr::I32Load16S(memarg) => { Byte(0x2E).encode(bin)?; memarg.encode(bin) },
+            Instr::I32Load16U(memarg) => { Byte(0x2F).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Load8S(memarg) => { Byte(0x30).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Load8U(memarg) => { Byte(0x31).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Load16S(memarg) => { Byte(0x32).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Load16U(memarg) => { Byte(0x33).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Load32S(memarg) => { Byte(0x34).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Load32U(memarg) => { Byte(0x35).encode(bin)?; memarg.encode(bin) },
             // Memory store
-            0x36 => Ok(Instr::I32Store(bin.parse()?, bin.parse()?)),
-            0x37 => Ok(Instr::I64Store(bin.parse()?, bin.parse()?)),
-            0x38 => Ok(Instr::F32Store(bin.parse()?, bin.parse()?)),
-            0x39 => Ok(Instr::F64Store(bin.parse()?, bin.parse()?)),
-            0x3A => Ok(Instr::I32Store8(bin.parse()?, bin.parse()?)),
-            0x3B => Ok(Instr::I32Store16(bin.parse()?, bin.parse()?)),
-            0x3C => Ok(Instr::I64Store8(bin.parse()?, bin.parse()?)),
-            0x3D => Ok(Instr::I64Store16(bin.parse()?, bin.parse()?)),
-            0x3E => Ok(Instr::I64Store32(bin.parse()?, bin.parse()?)),
+            Instr::I32Store(memarg) => { Byte(0x36).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Store(memarg) => { Byte(0x37).encode(bin)?; memarg.encode(bin) },
+            Instr::F32Store(memarg) => { Byte(0x38).encode(bin)?; memarg.encode(bin) },
+            Instr::F64Store(memarg) => { Byte(0x39).encode(bin)?; memarg.encode(bin) },
+            Instr::I32Store8(memarg) => { Byte(0x3A).encode(bin)?; memarg.encode(bin) },
+            Instr::I32Store16(memarg) => { Byte(0x3B).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Store8(memarg) => { Byte(0x3C).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Store16(memarg) => { Byte(0x3D).encode(bin)?; memarg.encode(bin) },
+            Instr::I64Store32(memarg) => { Byte(0x3E).encode(bin)?; memarg.encode(bin) },
             // Memory management
-            0x3F => Ok(Instr::MemSize(bin.parse()?)),
-            0x40 => Ok(Instr::MemGrow(bin.parse()?)),
+            Instr::MemSize(idx) => { Byte(0x3F).encode(bin)?; idx.encode(bin) },
+            Instr::MemGrow(idx) => { Byte(0x40).encode(bin)?; idx.encode(bin) },
             // Numeric
-            0x41 => Ok(Instr::ConstI32(bin.parse()?)),
-            0x42 => Ok(Instr::ConstI64(bin.parse()?)),
-            0x43 => Ok(Instr::ConstF32(bin.parse()?)),
-            0x44 => Ok(Instr::ConstF64(bin.parse()?)),
+            Instr::ConstI32(val) => { Byte(0x41).encode(bin)?; val.encode(bin) },
+            Instr::ConstI64(val) => { Byte(0x42).encode(bin)?; val.encode(bin) },
+            Instr::ConstF32(val) => { Byte(0x43).encode(bin)?; val.encode(bin) },
+            Instr::ConstF64(val) => { Byte(0x44).encode(bin)?; val.encode(bin) },
             // Numeric without immediate -- i32
-            0x45 => Ok(Instr::I32Eqz),
-            0x46 => Ok(Instr::I32Eq),
-            0x47 => Ok(Instr::I32Ne),
-            0x48 => Ok(Instr::I32LtS),
-            0x49 => Ok(Instr::I32LtU),
-            0x4A => Ok(Instr::I32GtS),
-            0x4B => Ok(Instr::I32GtU),
-            0x4C => Ok(Instr::I32LeS),
-            0x4D => Ok(Instr::I32LeU),
-            0x4E => Ok(Instr::I32GeS),
-            0x4F => Ok(Instr::I32GeU),
+            Instr::I32Eqz => Byte(0x45).encode(bin),
+            Instr::I32Eq => Byte(0x46).encode(bin),
+            Instr::I32Ne => Byte(0x47).encode(bin),
+            Instr::I32LtS => Byte(0x48).encode(bin),
+            Instr::I32LtU => Byte(0x49).encode(bin),
+            Instr::I32GtS => Byte(0x4A).encode(bin),
+            Instr::I32GtU => Byte(0x4B).encode(bin),
+            Instr::I32LeS => Byte(0x4C).encode(bin),
+            Instr::I32LeU => Byte(0x4D).encode(bin),
+            Instr::I32GeS => Byte(0x4E).encode(bin),
+            Instr::I32GeU => Byte(0x4F).encode(bin),
             // Numeric without immediate -- i64
-            0x50 => Ok(Instr::I64Eqz),
-            0x51 => Ok(Instr::I64Eq),
-            0x52 => Ok(Instr::I64Ne),
-            0x53 => Ok(Instr::I64LtS),
-            0x54 => Ok(Instr::I64LtU),
-            0x55 => Ok(Instr::I64GtS),
-            0x56 => Ok(Instr::I64GtU),
-            0x57 => Ok(Instr::I64LeS),
-            0x58 => Ok(Instr::I64LeU),
-            0x59 => Ok(Instr::I64GeS),
-            0x5A => Ok(Instr::I64GeU),
+            Instr::I64Eqz => Byte(0x50).encode(bin),
+            Instr::I64Eq => Byte(0x51).encode(bin),
+            Instr::I64Ne => Byte(0x52).encode(bin),
+            Instr::I64LtS => Byte(0x53).encode(bin),
+            Instr::I64LtU => Byte(0x54).encode(bin),
+            Instr::I64GtS => Byte(0x55).encode(bin),
+            Instr::I64GtU => Byte(0x56).encode(bin),
+            Instr::I64LeS => Byte(0x57).encode(bin),
+            Instr::I64LeU => Byte(0x58).encode(bin),
+            Instr::I64GeS => Byte(0x59).encode(bin),
+            Instr::I64GeU => Byte(0x5A).encode(bin),
             // Numeric without immediate -- f32
-            0x5B => Ok(Instr::F32Eq),
-            0x5C => Ok(Instr::F32Ne),
-            0x5D => Ok(Instr::F32Lt),
-            0x5E => Ok(Instr::F32Gt),
-            0x5F => Ok(Instr::F32Le),
-            0x60 => Ok(Instr::F32Ge),
+            Instr::F32Eq => Byte(0x5B).encode(bin),
+            Instr::F32Ne => Byte(0x5C).encode(bin),
+            Instr::F32Lt => Byte(0x5D).encode(bin),
+            Instr::F32Gt => Byte(0x5E).encode(bin),
+            Instr::F32Le => Byte(0x5F).encode(bin),
+            Instr::F32Ge => Byte(0x60).encode(bin),
             // Numeric without immediate -- f64
-            0x61 => Ok(Instr::F64Eq),
-            0x62 => Ok(Instr::F64Ne),
-            0x63 => Ok(Instr::F64Lt),
-            0x64 => Ok(Instr::F64Gt),
-            0x65 => Ok(Instr::F64Le),
-            0x66 => Ok(Instr::F64Ge),
+            Instr::F64Eq => Byte(0x61).encode(bin),
+            Instr::F64Ne => Byte(0x62).encode(bin),
+            Instr::F64Lt => Byte(0x63).encode(bin),
+            Instr::F64Gt => Byte(0x64).encode(bin),
+            Instr::F64Le => Byte(0x65).encode(bin),
+            Instr::F64Ge => Byte(0x66).encode(bin),
             // Numeric without immediate -- i32 cont.
-            0x67 => Ok(Instr::I32Clz),
-            0x68 => Ok(Instr::I32Ctz),
-            0x69 => Ok(Instr::I32Popcnt),
-            0x6A => Ok(Instr::I32Add),
-            0x6B => Ok(Instr::I32Sub),
-            0x6C => Ok(Instr::I32Mul),
-            0x6D => Ok(Instr::I32DivS),
-            0x6E => Ok(Instr::I32DivU),
-            0x6F => Ok(Instr::I32RemS),
-            0x70 => Ok(Instr::I32RemU),
-            0x71 => Ok(Instr::I32And),
-            0x72 => Ok(Instr::I32Or),
-            0x73 => Ok(Instr::I32Xor),
-            0x74 => Ok(Instr::I32Shl),
-            0x75 => Ok(Instr::I32ShrS),
-            0x76 => Ok(Instr::I32ShrU),
-            0x77 => Ok(Instr::I32Rotl),
-            0x78 => Ok(Instr::I32Rotr),
+            Instr::I32Clz => Byte(0x67).encode(bin),
+            Instr::I32Ctz => Byte(0x68).encode(bin),
+            Instr::I32Popcnt => Byte(0x69).encode(bin),
+            Instr::I32Add => Byte(0x6A).encode(bin),
+            Instr::I32Sub => Byte(0x6B).encode(bin),
+            Instr::I32Mul => Byte(0x6C).encode(bin),
+            Instr::I32DivS => Byte(0x6D).encode(bin),
+            Instr::I32DivU => Byte(0x6E).encode(bin),
+            Instr::I32RemS => Byte(0x6F).encode(bin),
+            Instr::I32RemU => Byte(0x70).encode(bin),
+            Instr::I32And => Byte(0x71).encode(bin),
+            Instr::I32Or => Byte(0x72).encode(bin),
+            Instr::I32Xor => Byte(0x73).encode(bin),
+            Instr::I32Shl => Byte(0x74).encode(bin),
+            Instr::I32ShrS => Byte(0x75).encode(bin),
+            Instr::I32ShrU => Byte(0x76).encode(bin),
+            Instr::I32Rotl => Byte(0x77).encode(bin),
+            Instr::I32Rotr => Byte(0x78).encode(bin),
             // Numeric without immediate -- i64 cont.
-            0x79 => Ok(Instr::I64Clz),
-            0x7A => Ok(Instr::I64Ctz),
-            0x7B => Ok(Instr::I64Popcnt),
-            0x7C => Ok(Instr::I64Add),
-            0x7D => Ok(Instr::I64Sub),
-            0x7E => Ok(Instr::I64Mul),
-            0x7F => Ok(Instr::I64DivS),
-            0x80 => Ok(Instr::I64DivU),
-            0x81 => Ok(Instr::I64RemS),
-            0x82 => Ok(Instr::I64RemU),
-            0x83 => Ok(Instr::I64And),
-            0x84 => Ok(Instr::I64Or),
-            0x85 => Ok(Instr::I64Xor),
-            0x86 => Ok(Instr::I64Shl),
-            0x87 => Ok(Instr::I64ShrS),
-            0x88 => Ok(Instr::I64ShrU),
-            0x89 => Ok(Instr::I64Rotl),
-            0x8A => Ok(Instr::I64Rotr),
+            Instr::I64Clz => Byte(0x79).encode(bin),
+            Instr::I64Ctz => Byte(0x7A).encode(bin),
+            Instr::I64Popcnt => Byte(0x7B).encode(bin),
+            Instr::I64Add => Byte(0x7C).encode(bin),
+            Instr::I64Sub => Byte(0x7D).encode(bin),
+            Instr::I64Mul => Byte(0x7E).encode(bin),
+            Instr::I64DivS => Byte(0x7F).encode(bin),
+            Instr::I64DivU => Byte(0x80).encode(bin),
+            Instr::I64RemS => Byte(0x81).encode(bin),
+            Instr::I64RemU => Byte(0x82).encode(bin),
+            Instr::I64And => Byte(0x83).encode(bin),
+            Instr::I64Or => Byte(0x84).encode(bin),
+            Instr::I64Xor => Byte(0x85).encode(bin),
+            Instr::I64Shl => Byte(0x86).encode(bin),
+            Instr::I64ShrS => Byte(0x87).encode(bin),
+            Instr::I64ShrU => Byte(0x88).encode(bin),
+            Instr::I64Rotl => Byte(0x89).encode(bin),
+            Instr::I64Rotr => Byte(0x8A).encode(bin),
             // Numeric without immediate -- f32 cont.
-            0x8B => Ok(Instr::F32Abs),
-            0x8C => Ok(Instr::F32Neg),
-            0x8D => Ok(Instr::F32Ceil),
-            0x8E => Ok(Instr::F32Floor),
-            0x8F => Ok(Instr::F32Trunc),
-            0x90 => Ok(Instr::F32Nearest),
-            0x91 => Ok(Instr::F32Sqrt),
-            0x92 => Ok(Instr::F32Add),
-            0x93 => Ok(Instr::F32Sub),
-            0x94 => Ok(Instr::F32Mul),
-            0x95 => Ok(Instr::F32Div),
-            0x96 => Ok(Instr::F32Min),
-            0x97 => Ok(Instr::F32Max),
-            0x98 => Ok(Instr::F32Copysign),
+            Instr::F32Abs => Byte(0x8B).encode(bin),
+            Instr::F32Neg => Byte(0x8C).encode(bin),
+            Instr::F32Ceil => Byte(0x8D).encode(bin),
+            Instr::F32Floor => Byte(0x8E).encode(bin),
+            Instr::F32Trunc => Byte(0x8F).encode(bin),
+            Instr::F32Nearest => Byte(0x90).encode(bin),
+            Instr::F32Sqrt => Byte(0x91).encode(bin),
+            Instr::F32Add => Byte(0x92).encode(bin),
+            Instr::F32Sub => Byte(0x93).encode(bin),
+            Instr::F32Mul => Byte(0x94).encode(bin),
+            Instr::F32Div => Byte(0x95).encode(bin),
+            Instr::F32Min => Byte(0x96).encode(bin),
+            Instr::F32Max => Byte(0x97).encode(bin),
+            Instr::F32Copysign => Byte(0x98).encode(bin),
             // Numeric without immediate -- f64 cont.
-            0x99 => Ok(Instr::F64Abs),
-            0x9A => Ok(Instr::F64Neg),
-            0x9B => Ok(Instr::F64Ceil),
-            0x9C => Ok(Instr::F64Floor),
-            0x9D => Ok(Instr::F64Trunc),
-            0x9E => Ok(Instr::F64Nearest),
-            0x9F => Ok(Instr::F64Sqrt),
-            0xA0 => Ok(Instr::F64Add),
-            0xA1 => Ok(Instr::F64Sub),
-            0xA2 => Ok(Instr::F64Mul),
-            0xA3 => Ok(Instr::F64Div),
-            0xA4 => Ok(Instr::F64Min),
-            0xA5 => Ok(Instr::F64Max),
-            0xA6 => Ok(Instr::F64Copysign),
+            Instr::F64Abs => Byte(0x99).encode(bin),
+            Instr::F64Neg => Byte(0x9A).encode(bin),
+            Instr::F64Ceil => Byte(0x9B).encode(bin),
+            Instr::F64Floor => Byte(0x9C).encode(bin),
+            Instr::F64Trunc => Byte(0x9D).encode(bin),
+            Instr::F64Nearest => Byte(0x9E).encode(bin),
+            Instr::F64Sqrt => Byte(0x9F).encode(bin),
+            Instr::F64Add => Byte(0xA0).encode(bin),
+            Instr::F64Sub => Byte(0xA1).encode(bin),
+            Instr::F64Mul => Byte(0xA2).encode(bin),
+            Instr::F64Div => Byte(0xA3).encode(bin),
+            Instr::F64Min => Byte(0xA4).encode(bin),
+            Instr::F64Max => Byte(0xA5).encode(bin),
+            Instr::F64Copysign => Byte(0xA6).encode(bin),
             // Numeric without immediate -- conversion
-            0xA7 => Ok(Instr::I32WrapI64),
-            0xA8 => Ok(Instr::I32TruncF32S),
-            0xA9 => Ok(Instr::I32TruncF32U),
-            0xAA => Ok(Instr::I32TruncF64S),
-            0xAB => Ok(Instr::I32TruncF64U),
-            0xAC => Ok(Instr::I64ExtendI32S),
-            0xAD => Ok(Instr::I64ExtendI32U),
-            0xAE => Ok(Instr::I64TruncF32S),
-            0xAF => Ok(Instr::I64TruncF32U),
-            0xB0 => Ok(Instr::I64TruncF64S),
-            0xB1 => Ok(Instr::I64TruncF64U),
-            0xB2 => Ok(Instr::F32ConvertI32S),
-            0xB3 => Ok(Instr::F32ConvertI32U),
-            0xB4 => Ok(Instr::F32ConvertI64S),
-            0xB5 => Ok(Instr::F32ConvertI64U),
-            0xB6 => Ok(Instr::F32DemoteF64),
-            0xB7 => Ok(Instr::F64ConvertI32S),
-            0xB8 => Ok(Instr::F64ConvertI32U),
-            0xB9 => Ok(Instr::F64ConvertI64S),
-            0xBA => Ok(Instr::F64ConvertI64U),
-            0xBB => Ok(Instr::F64PromoteF32),
-            0xBC => Ok(Instr::I32ReinterpretF32),
-            0xBD => Ok(Instr::I64ReinterpretF64),
-            0xBE => Ok(Instr::F32ReinterpretI32),
-            0xBF => Ok(Instr::F64ReinterpretI64),
+            Instr::I32WrapI64 => Byte(0xA7).encode(bin),
+            Instr::I32TruncF32S => Byte(0xA8).encode(bin),
+            Instr::I32TruncF32U => Byte(0xA9).encode(bin),
+            Instr::I32TruncF64S => Byte(0xAA).encode(bin),
+            Instr::I32TruncF64U => Byte(0xAB).encode(bin),
+            Instr::I64ExtendI32S => Byte(0xAC).encode(bin),
+            Instr::I64ExtendI32U => Byte(0xAD).encode(bin),
+            Instr::I64TruncF32S => Byte(0xAE).encode(bin),
+            Instr::I64TruncF32U => Byte(0xAF).encode(bin),
+            Instr::I64TruncF64S => Byte(0xB0).encode(bin),
+            Instr::I64TruncF64U => Byte(0xB1).encode(bin),
+            Instr::F32ConvertI32S => Byte(0xB2).encode(bin),
+            Instr::F32ConvertI32U => Byte(0xB3).encode(bin),
+            Instr::F32ConvertI64S => Byte(0xB4).encode(bin),
+            Instr::F32ConvertI64U => Byte(0xB5).encode(bin),
+            Instr::F32DemoteF64 => Byte(0xB6).encode(bin),
+            Instr::F64ConvertI32S => Byte(0xB7).encode(bin),
+            Instr::F64ConvertI32U => Byte(0xB8).encode(bin),
+            Instr::F64ConvertI64S => Byte(0xB9).encode(bin),
+            Instr::F64ConvertI64U => Byte(0xBA).encode(bin),
+            Instr::F64PromoteF32 => Byte(0xBB).encode(bin),
+            Instr::I32ReinterpretF32 => Byte(0xBC).encode(bin),
+            Instr::I64ReinterpretF64 => Byte(0xBD).encode(bin),
+            Instr::F32ReinterpretI32 => Byte(0xBE).encode(bin),
+            Instr::F64ReinterpretI64 => Byte(0xBF).encode(bin),
+            Instr::I32Extend8S => Byte(0xC0).encode(bin),
+            Instr::I32Extend16S => Byte(0xC1).encode(bin),
+            Instr::I64Extend8S => Byte(0xC2).encode(bin),
+            Instr::I64Extend16S => Byte(0xC3).encode(bin),
+            Instr::I64Extend32S => Byte(0xC4).encode(bin),
+                // ... reserved ...
+            // Reference
+            Instr::RefNull(heap) => { Byte(0xD0).encode(bin)?; heap.encode(bin) },
+            Instr::RefIsNull => Byte(0xD1).encode(bin),
+            Instr::RefFunc(func) => { Byte(0xD2).encode(bin)?; func.encode(bin) },
+            Instr::RefEq => Byte(0xD3).encode(bin),
+            Instr::RefAsNonNull => Byte(0xD4).encode(bin),
+            Instr::BrOnNull(label) => { Byte(0xD5).encode(bin)?; label.encode(bin) },
+            Instr::BrOnNonNull(label) => { Byte(0xD6).encode(bin)?; label.encode(bin) },
+                // ... reserved ...
+            // Aggregate
+            Instr::StructNew(ty) => { encode_prefix(0xFB, 0x00, bin)?; ty.encode(bin) },
+            Instr::StructNewDefault(ty) => { encode_prefix(0xFB, 0x01, bin)?; ty.encode(bin) },
+            Instr::StructGet(ty, field) => { encode_prefix(0xFB, 0x02, bin)?; ty.encode(bin)?; field.encode(bin) },
+            Instr::StructGetS(ty, field) => { encode_prefix(0xFB, 0x03, bin)?; ty.encode(bin)?; field.encode(bin) },
+            Instr::StructGetU(ty, field) => { encode_prefix(0xFB, 0x04, bin)?; ty.encode(bin)?; field.encode(bin) },
+            Instr::StructSet(ty, field) => { encode_prefix(0xFB, 0x05, bin)?; ty.encode(bin)?; field.encode(bin) },
+            Instr::ArrayNew(ty) => { encode_prefix(0xFB, 0x06, bin)?; ty.encode(bin) },
+            Instr::ArrayNewDefault(ty) => { encode_prefix(0xFB, 0x07, bin)?; ty.encode(bin) },
+            Instr::ArrayNewFixed(ty, len) => { encode_prefix(0xFB, 0x08, bin)?; ty.encode(bin)?; len.encode(bin) },
+            Instr::ArrayNewData(ty, data) => { encode_prefix(0xFB, 0x09, bin)?; ty.encode(bin)?; data.encode(bin) },
+            Instr::ArrayNewElem(ty, elem) => { encode_prefix(0xFB, 0x0A, bin)?; ty.encode(bin)?; elem.encode(bin) },
+            Instr::ArrayGet(ty) => { encode_prefix(0xFB, 0x0B, bin)?; ty.encode(bin) },
+            Instr::ArrayGetS(ty) => { encode_prefix(0xFB, 0x0C, bin)?; ty.encode(bin) },
+            Instr::ArrayGetU(ty) => { encode_prefix(0xFB, 0x0D, bin)?; ty.encode(bin) },
+            Instr::ArraySet(ty) => { encode_prefix(0xFB, 0x0E, bin)?; ty.encode(bin) },
+            Instr::ArrayLen => encode_prefix(0xFB, 0x0F, bin),
+            Instr::ArrayFill(ty) => { encode_prefix(0xFB, 0x10, bin)?; ty.encode(bin) },
+            Instr::ArrayCopy(dst, src) => { encode_prefix(0xFB, 0x11, bin)?; dst.encode(bin)?; src.encode(bin) },
+            Instr::ArrayInitData(ty, data) => { encode_prefix(0xFB, 0x12, bin)?; ty.encode(bin)?; data.encode(bin) },
+            Instr::ArrayInitElem(ty, elem) => { encode_prefix(0xFB, 0x13, bin)?; ty.encode(bin)?; elem.encode(bin) },
+            // Casts
+            Instr::RefTest(to) => { encode_prefix(0xFB, 0x14 + to.nullable as u32, bin)?; to.heap.encode(bin) },
+            Instr::RefCast(to) => { encode_prefix(0xFB, 0x16 + to.nullable as u32, bin)?; to.heap.encode(bin) },
+            Instr::BrOnCast(label, from, to) => { encode_prefix(0xFB, 0x18, bin)?; encode_br_on_cast(label, from, to, bin) },
+            Instr::BrOnCastFail(label, from, to) => { encode_prefix(0xFB, 0x19, bin)?; encode_br_on_cast(label, from, to, bin) },
+            Instr::AnyConvertExtern => encode_prefix(0xFB, 0x1A, bin),
+            Instr::ExternConvertAny => encode_prefix(0xFB, 0x1B, bin),
+            // Scalar references
+            Instr::RefI31 => encode_prefix(0xFB, 0x1C, bin),
+            Instr::I31GetS => encode_prefix(0xFB, 0x1D, bin),
+            Instr::I31GetU => encode_prefix(0xFB, 0x1E, bin),
+            // Saturating truncation
+            Instr::I32TruncSatF32S => encode_prefix(0xFC, 0x00, bin),
+            Instr::I32TruncSatF32U => encode_prefix(0xFC, 0x01, bin),
+            Instr::I32TruncSatF64S => encode_prefix(0xFC, 0x02, bin),
+            Instr::I32TruncSatF64U => encode_prefix(0xFC, 0x03, bin),
+            Instr::I64TruncSatF32S => encode_prefix(0xFC, 0x04, bin),
+            Instr::I64TruncSatF32U => encode_prefix(0xFC, 0x05, bin),
+            Instr::I64TruncSatF64S => encode_prefix(0xFC, 0x06, bin),
+            Instr::I64TruncSatF64U => encode_prefix(0xFC, 0x07, bin),
+            // Bulk memory
+            Instr::MemInit(data, mem) => { encode_prefix(0xFC, 0x08, bin)?; data.encode(bin)?; mem.encode(bin) },
+            Instr::DataDrop(data) => { encode_prefix(0xFC, 0x09, bin)?; data.encode(bin) },
+            Instr::MemCopy(dst, src) => { encode_prefix(0xFC, 0x0A, bin)?; dst.encode(bin)?; src.encode(bin) },
+            Instr::MemFill(mem) => { encode_prefix(0xFC, 0x0B, bin)?; mem.encode(bin) },
+            Instr::TableInit(elem, table) => { encode_prefix(0xFC, 0x0C, bin)?; elem.encode(bin)?; table.encode(bin) },
+            Instr::ElemDrop(elem) => { encode_prefix(0xFC, 0x0D, bin)?; elem.encode(bin) },
+            Instr::TableCopy(dst, src) => { encode_prefix(0xFC, 0x0E, bin)?; dst.encode(bin)?; src.encode(bin) },
+            // Table management
+            Instr::TableGrow(table) => { encode_prefix(0xFC, 0x0F, bin)?; table.encode(bin) },
+            Instr::TableSize(table) => { encode_prefix(0xFC, 0x10, bin)?; table.encode(bin) },
+            Instr::TableFill(table) => { encode_prefix(0xFC, 0x11, bin)?; table.encode(bin) },
+            // Vector
+            Instr::V128Load(memarg) => { encode_prefix(0xFD, 0x00, bin)?; memarg.encode(bin) },
+            Instr::V128Load8x8S(memarg) => { encode_prefix(0xFD, 0x01, bin)?; memarg.encode(bin) },
+            Instr::V128Load8x8U(memarg) => { encode_prefix(0xFD, 0x02, bin)?; memarg.encode(bin) },
+            Instr::V128Load16x4S(memarg) => { encode_prefix(0xFD, 0x03, bin)?; memarg.encode(bin) },
+            Instr::V128Load16x4U(memarg) => { encode_prefix(0xFD, 0x04, bin)?; memarg.encode(bin) },
+            Instr::V128Load32x2S(memarg) => { encode_prefix(0xFD, 0x05, bin)?; memarg.encode(bin) },
+            Instr::V128Load32x2U(memarg) => { encode_prefix(0xFD, 0x06, bin)?; memarg.encode(bin) },
+            Instr::V128Load8Splat(memarg) => { encode_prefix(0xFD, 0x07, bin)?; memarg.encode(bin) },
+            Instr::V128Load16Splat(memarg) => { encode_prefix(0xFD, 0x08, bin)?; memarg.encode(bin) },
+            Instr::V128Load32Splat(memarg) => { encode_prefix(0xFD, 0x09, bin)?; memarg.encode(bin) },
+            Instr::V128Load64Splat(memarg) => { encode_prefix(0xFD, 0x0A, bin)?; memarg.encode(bin) },
+            Instr::V128Store(memarg) => { encode_prefix(0xFD, 0x0B, bin)?; memarg.encode(bin) },
+            Instr::ConstV128(val) => { encode_prefix(0xFD, 0x0C, bin)?; bin.extend(val.to_le_bytes().iter().copied()); Ok(()) },
+            Instr::I8x16Shuffle(lanes) => { encode_prefix(0xFD, 0x0D, bin)?; bin.extend(lanes.iter().copied()); Ok(()) },
+            Instr::I8x16Swizzle => encode_prefix(0xFD, 0x0E, bin),
+            Instr::I8x16Splat => encode_prefix(0xFD, 0x0F, bin),
+            Instr::I16x8Splat => encode_prefix(0xFD, 0x10, bin),
+            Instr::I32x4Splat => encode_prefix(0xFD, 0x11, bin),
+            Instr::I64x2Splat => encode_prefix(0xFD, 0x12, bin),
+            Instr::F32x4Splat => encode_prefix(0xFD, 0x13, bin),
+            Instr::F64x2Splat => encode_prefix(0xFD, 0x14, bin),
+            Instr::I8x16ExtractLaneS(lane) => { encode_prefix(0xFD, 0x15, bin)?; Byte(*lane).encode(bin) },
+            Instr::I8x16ExtractLaneU(lane) => { encode_prefix(0xFD, 0x16, bin)?; Byte(*lane).encode(bin) },
+            Instr::I8x16ReplaceLane(lane) => { encode_prefix(0xFD, 0x17, bin)?; Byte(*lane).encode(bin) },
+            Instr::I16x8ExtractLaneS(lane) => { encode_prefix(0xFD, 0x18, bin)?; Byte(*lane).encode(bin) },
+            Instr::I16x8ExtractLaneU(lane) => { encode_prefix(0xFD, 0x19, bin)?; Byte(*lane).encode(bin) },
+            Instr::I16x8ReplaceLane(lane) => { encode_prefix(0xFD, 0x1A, bin)?; Byte(*lane).encode(bin) },
+            Instr::I32x4ExtractLane(lane) => { encode_prefix(0xFD, 0x1B, bin)?; Byte(*lane).encode(bin) },
+            Instr::I32x4ReplaceLane(lane) => { encode_prefix(0xFD, 0x1C, bin)?; Byte(*lane).encode(bin) },
+            Instr::I64x2ExtractLane(lane) => { encode_prefix(0xFD, 0x1D, bin)?; Byte(*lane).encode(bin) },
+            Instr::I64x2ReplaceLane(lane) => { encode_prefix(0xFD, 0x1E, bin)?; Byte(*lane).encode(bin) },
+            Instr::F32x4ExtractLane(lane) => { encode_prefix(0xFD, 0x1F, bin)?; Byte(*lane).encode(bin) },
+            Instr::F32x4ReplaceLane(lane) => { encode_prefix(0xFD, 0x20, bin)?; Byte(*lane).encode(bin) },
+            Instr::F64x2ExtractLane(lane) => { encode_prefix(0xFD, 0x21, bin)?; Byte(*lane).encode(bin) },
+            Instr::F64x2ReplaceLane(lane) => { encode_prefix(0xFD, 0x22, bin)?; Byte(*lane).encode(bin) },
+            Instr::I8x16Eq => encode_prefix(0xFD, 0x23, bin),
+            Instr::I8x16Ne => encode_prefix(0xFD, 0x24, bin),
+            Instr::I8x16LtS => encode_prefix(0xFD, 0x25, bin),
+            Instr::I8x16LtU => encode_prefix(0xFD, 0x26, bin),
+            Instr::I8x16GtS => encode_prefix(0xFD, 0x27, bin),
+            Instr::I8x16GtU => encode_prefix(0xFD, 0x28, bin),
+            Instr::I8x16LeS => encode_prefix(0xFD, 0x29, bin),
+            Instr::I8x16LeU => encode_prefix(0xFD, 0x2A, bin),
+            Instr::I8x16GeS => encode_prefix(0xFD, 0x2B, bin),
+            Instr::I8x16GeU => encode_prefix(0xFD, 0x2C, bin),
+            Instr::I16x8Eq => encode_prefix(0xFD, 0x2D, bin),
+            Instr::I16x8Ne => encode_prefix(0xFD, 0x2E, bin),
+            Instr::I16x8LtS => encode_prefix(0xFD, 0x2F, bin),
+            Instr::I16x8LtU => encode_prefix(0xFD, 0x30, bin),
+            Instr::I16x8GtS => encode_prefix(0xFD, 0x31, bin),
+            Instr::I16x8GtU => encode_prefix(0xFD, 0x32, bin),
+            Instr::I16x8LeS => encode_prefix(0xFD, 0x33, bin),
+            Instr::I16x8LeU => encode_prefix(0xFD, 0x34, bin),
+            Instr::I16x8GeS => encode_prefix(0xFD, 0x35, bin),
+            Instr::I16x8GeU => encode_prefix(0xFD, 0x36, bin),
+            Instr::I32x4Eq => encode_prefix(0xFD, 0x37, bin),
+            Instr::I32x4Ne => encode_prefix(0xFD, 0x38, bin),
+            Instr::I32x4LtS => encode_prefix(0xFD, 0x39, bin),
+            Instr::I32x4LtU => encode_prefix(0xFD, 0x3A, bin),
+            Instr::I32x4GtS => encode_prefix(0xFD, 0x3B, bin),
+            Instr::I32x4GtU => encode_prefix(0xFD, 0x3C, bin),
+            Instr::I32x4LeS => encode_prefix(0xFD, 0x3D, bin),
+            Instr::I32x4LeU => encode_prefix(0xFD, 0x3E, bin),
+            Instr::I32x4GeS => encode_prefix(0xFD, 0x3F, bin),
+            Instr::I32x4GeU => encode_prefix(0xFD, 0x40, bin),
+            Instr::F32x4Eq => encode_prefix(0xFD, 0x41, bin),
+            Instr::F32x4Ne => encode_prefix(0xFD, 0x42, bin),
+            Instr::F32x4Lt => encode_prefix(0xFD, 0x43, bin),
+            Instr::F32x4Gt => encode_prefix(0xFD, 0x44, bin),
+            Instr::F32x4Le => encode_prefix(0xFD, 0x45, bin),
+            Instr::F32x4Ge => encode_prefix(0xFD, 0x46, bin),
+            Instr::F64x2Eq => encode_prefix(0xFD, 0x47, bin),
+            Instr::F64x2Ne => encode_prefix(0xFD, 0x48, bin),
+            Instr::F64x2Lt => encode_prefix(0xFD, 0x49, bin),
+            Instr::F64x2Gt => encode_prefix(0xFD, 0x4A, bin),
+            Instr::F64x2Le => encode_prefix(0xFD, 0x4B, bin),
+            Instr::F64x2Ge => encode_prefix(0xFD, 0x4C, bin),
+            Instr::V128Not => encode_prefix(0xFD, 0x4D, bin),
+            Instr::V128And => encode_prefix(0xFD, 0x4E, bin),
+            Instr::V128AndNot => encode_prefix(0xFD, 0x4F, bin),
+            Instr::V128Or => encode_prefix(0xFD, 0x50, bin),
+            Instr::V128Xor => encode_prefix(0xFD, 0x51, bin),
+            Instr::V128Bitselect => encode_prefix(0xFD, 0x52, bin),
+            Instr::V128AnyTrue => encode_prefix(0xFD, 0x53, bin),
+            Instr::V128Load8Lane(memarg, lane) => { encode_prefix(0xFD, 0x54, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Load16Lane(memarg, lane) => { encode_prefix(0xFD, 0x55, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Load32Lane(memarg, lane) => { encode_prefix(0xFD, 0x56, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Load64Lane(memarg, lane) => { encode_prefix(0xFD, 0x57, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Store8Lane(memarg, lane) => { encode_prefix(0xFD, 0x58, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Store16Lane(memarg, lane) => { encode_prefix(0xFD, 0x59, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Store32Lane(memarg, lane) => { encode_prefix(0xFD, 0x5A, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Store64Lane(memarg, lane) => { encode_prefix(0xFD, 0x5B, bin)?; memarg.encode(bin)?; Byte(*lane).encode(bin) },
+            Instr::V128Load32Zero(memarg) => { encode_prefix(0xFD, 0x5C, bin)?; memarg.encode(bin) },
+            Instr::V128Load64Zero(memarg) => { encode_prefix(0xFD, 0x5D, bin)?; memarg.encode(bin) },
+            Instr::F32x4DemoteF64x2Zero => encode_prefix(0xFD, 0x5E, bin),
+            Instr::F64x2PromoteLowF32x4 => encode_prefix(0xFD, 0x5F, bin),
+            Instr::I8x16Abs => encode_prefix(0xFD, 0x60, bin),
+            Instr::I8x16Neg => encode_prefix(0xFD, 0x61, bin),
+            Instr::I8x16Popcnt => encode_prefix(0xFD, 0x62, bin),
+            Instr::I8x16AllTrue => encode_prefix(0xFD, 0x63, bin),
+            Instr::I8x16Bitmask => encode_prefix(0xFD, 0x64, bin),
+            Instr::I8x16NarrowI16x8S => encode_prefix(0xFD, 0x65, bin),
+            Instr::I8x16NarrowI16x8U => encode_prefix(0xFD, 0x66, bin),
+            Instr::F32x4Ceil => encode_prefix(0xFD, 0x67, bin),
+            Instr::F32x4Floor => encode_prefix(0xFD, 0x68, bin),
+            Instr::F32x4Trunc => encode_prefix(0xFD, 0x69, bin),
+            Instr::F32x4Nearest => encode_prefix(0xFD, 0x6A, bin),
+            Instr::I8x16Shl => encode_prefix(0xFD, 0x6B, bin),
+            Instr::I8x16ShrS => encode_prefix(0xFD, 0x6C, bin),
+            Instr::I8x16ShrU => encode_prefix(0xFD, 0x6D, bin),
+            Instr::I8x16Add => encode_prefix(0xFD, 0x6E, bin),
+            Instr::I8x16AddSatS => encode_prefix(0xFD, 0x6F, bin),
+            Instr::I8x16AddSatU => encode_prefix(0xFD, 0x70, bin),
+            Instr::I8x16Sub => encode_prefix(0xFD, 0x71, bin),
+            Instr::I8x16SubSatS => encode_prefix(0xFD, 0x72, bin),
+            Instr::I8x16SubSatU => encode_prefix(0xFD, 0x73, bin),
+            Instr::F64x2Ceil => encode_prefix(0xFD, 0x74, bin),
+            Instr::F64x2Floor => encode_prefix(0xFD, 0x75, bin),
+            Instr::I8x16MinS => encode_prefix(0xFD, 0x76, bin),
+            Instr::I8x16MinU => encode_prefix(0xFD, 0x77, bin),
+            Instr::I8x16MaxS => encode_prefix(0xFD, 0x78, bin),
+            Instr::I8x16MaxU => encode_prefix(0xFD, 0x79, bin),
+            Instr::F64x2Trunc => encode_prefix(0xFD, 0x7A, bin),
+            Instr::I8x16AvgrU => encode_prefix(0xFD, 0x7B, bin),
+            Instr::I16x8ExtaddPairwiseI8x16S => encode_prefix(0xFD, 0x7C, bin),
+            Instr::I16x8ExtaddPairwiseI8x16U => encode_prefix(0xFD, 0x7D, bin),
+            Instr::I32x4ExtaddPairwiseI16x8S => encode_prefix(0xFD, 0x7E, bin),
+            Instr::I32x4ExtaddPairwiseI16x8U => encode_prefix(0xFD, 0x7F, bin),
+            Instr::I16x8Abs => encode_prefix(0xFD, 0x80, bin),
+            Instr::I16x8Neg => encode_prefix(0xFD, 0x81, bin),
+            Instr::I16x8Q15mulrSatS => encode_prefix(0xFD, 0x82, bin),
+            Instr::I16x8AllTrue => encode_prefix(0xFD, 0x83, bin),
+            Instr::I16x8Bitmask => encode_prefix(0xFD, 0x84, bin),
+            Instr::I16x8NarrowI32x4S => encode_prefix(0xFD, 0x85, bin),
+            Instr::I16x8NarrowI32x4U => encode_prefix(0xFD, 0x86, bin),
+            Instr::I16x8ExtendLowI8x16S => encode_prefix(0xFD, 0x87, bin),
+            Instr::I16x8ExtendHighI8x16S => encode_prefix(0xFD, 0x88, bin),
+            Instr::I16x8ExtendLowI8x16U => encode_prefix(0xFD, 0x89, bin),
+            Instr::I16x8ExtendHighI8x16U => encode_prefix(0xFD, 0x8A, bin),
+            Instr::I16x8Shl => encode_prefix(0xFD, 0x8B, bin),
+            Instr::I16x8ShrS => encode_prefix(0xFD, 0x8C, bin),
+            Instr::I16x8ShrU => encode_prefix(0xFD, 0x8D, bin),
+            Instr::I16x8Add => encode_prefix(0xFD, 0x8E, bin),
+            Instr::I16x8AddSatS => encode_prefix(0xFD, 0x8F, bin),
+            Instr::I16x8AddSatU => encode_prefix(0xFD, 0x90, bin),
+            Instr::I16x8Sub => encode_prefix(0xFD, 0x91, bin),
+            Instr::I16x8SubSatS => encode_prefix(0xFD, 0x92, bin),
+            Instr::I16x8SubSatU => encode_prefix(0xFD, 0x93, bin),
+            Instr::F64x2Nearest => encode_prefix(0xFD, 0x94, bin),
+            Instr::I16x8Mul => encode_prefix(0xFD, 0x95, bin),
+            Instr::I16x8MinS => encode_prefix(0xFD, 0x96, bin),
+            Instr::I16x8MinU => encode_prefix(0xFD, 0x97, bin),
+            Instr::I16x8MaxS => encode_prefix(0xFD, 0x98, bin),
+            Instr::I16x8MaxU => encode_prefix(0xFD, 0x99, bin),
+                // ... reserved ...
+            Instr::I16x8AvgrU => encode_prefix(0xFD, 0x9B, bin),
+            Instr::I16x8ExtmulLowI8x16S => encode_prefix(0xFD, 0x9C, bin),
+            Instr::I16x8ExtmulHighI8x16S => encode_prefix(0xFD, 0x9D, bin),
+            Instr::I16x8ExtmulLowI8x16U => encode_prefix(0xFD, 0x9E, bin),
+            Instr::I16x8ExtmulHighI8x16U => encode_prefix(0xFD, 0x9F, bin),
+            Instr::I32x4Abs => encode_prefix(0xFD, 0xA0, bin),
+            Instr::I32x4Neg => encode_prefix(0xFD, 0xA1, bin),
+                // ... reserved ...
+            Instr::I32x4AllTrue => encode_prefix(0xFD, 0xA3, bin),
+            Instr::I32x4Bitmask => encode_prefix(0xFD, 0xA4, bin),
+                // ... reserved ...
+            Instr::I32x4ExtendLowI16x8S => encode_prefix(0xFD, 0xA7, bin),
+            Instr::I32x4ExtendHighI16x8S => encode_prefix(0xFD, 0xA8, bin),
+            Instr::I32x4ExtendLowI16x8U => encode_prefix(0xFD, 0xA9, bin),
+            Instr::I32x4ExtendHighI16x8U => encode_prefix(0xFD, 0xAA, bin),
+            Instr::I32x4Shl => encode_prefix(0xFD, 0xAB, bin),
+            Instr::I32x4ShrS => encode_prefix(0xFD, 0xAC, bin),
+            Instr::I32x4ShrU => encode_prefix(0xFD, 0xAD, bin),
+            Instr::I32x4Add => encode_prefix(0xFD, 0xAE, bin),
+                // ... reserved ...
+            Instr::I32x4Sub => encode_prefix(0xFD, 0xB1, bin),
+                // ... reserved ...
+            Instr::I32x4Mul => encode_prefix(0xFD, 0xB5, bin),
+            Instr::I32x4MinS => encode_prefix(0xFD, 0xB6, bin),
+            Instr::I32x4MinU => encode_prefix(0xFD, 0xB7, bin),
+            Instr::I32x4MaxS => encode_prefix(0xFD, 0xB8, bin),
+            Instr::I32x4MaxU => encode_prefix(0xFD, 0xB9, bin),
+            Instr::I32x4DotI16x8S => encode_prefix(0xFD, 0xBA, bin),
+                // ... reserved ...
+            Instr::I32x4ExtmulLowI16x8S => encode_prefix(0xFD, 0xBC, bin),
+            Instr::I32x4ExtmulHighI16x8S => encode_prefix(0xFD, 0xBD, bin),
+            Instr::I32x4ExtmulLowI16x8U => encode_prefix(0xFD, 0xBE, bin),
+            Instr::I32x4ExtmulHighI16x8U => encode_prefix(0xFD, 0xBF, bin),
+            Instr::I64x2Abs => encode_prefix(0xFD, 0xC0, bin),
+            Instr::I64x2Neg => encode_prefix(0xFD, 0xC1, bin),
+                // ... reserved ...
+            Instr::I64x2AllTrue => encode_prefix(0xFD, 0xC3, bin),
+            Instr::I64x2Bitmask => encode_prefix(0xFD, 0xC4, bin),
+                // ... reserved ...
+            Instr::I64x2ExtendLowI32x4S => encode_prefix(0xFD, 0xC7, bin),
+            Instr::I64x2ExtendHighI32x4S => encode_prefix(0xFD, 0xC8, bin),
+            Instr::I64x2ExtendLowI32x4U => encode_prefix(0xFD, 0xC9, bin),
+            Instr::I64x2ExtendHighI32x4U => encode_prefix(0xFD, 0xCA, bin),
+            Instr::I64x2Shl => encode_prefix(0xFD, 0xCB, bin),
+            Instr::I64x2ShrS => encode_prefix(0xFD, 0xCC, bin),
+            Instr::I64x2ShrU => encode_prefix(0xFD, 0xCD, bin),
+            Instr::I64x2Add => encode_prefix(0xFD, 0xCE, bin),
+                // ... reserved ...
+            Instr::I64x2Sub => encode_prefix(0xFD, 0xD1, bin),
+                // ... reserved ...
+            Instr::I64x2Mul => encode_prefix(0xFD, 0xD5, bin),
+            Instr::I64x2Eq => encode_prefix(0xFD, 0xD6, bin),
+            Instr::I64x2Ne => encode_prefix(0xFD, 0xD7, bin),
+            Instr::I64x2LtS => encode_prefix(0xFD, 0xD8, bin),
+            Instr::I64x2GtS => encode_prefix(0xFD, 0xD9, bin),
+            Instr::I64x2LeS => encode_prefix(0xFD, 0xDA, bin),
+            Instr::I64x2GeS => encode_prefix(0xFD, 0xDB, bin),
+            Instr::I64x2ExtmulLowI32x4S => encode_prefix(0xFD, 0xDC, bin),
+            Instr::I64x2ExtmulHighI32x4S => encode_prefix(0xFD, 0xDD, bin),
+            Instr::I64x2ExtmulLowI32x4U => encode_prefix(0xFD, 0xDE, bin),
+            Instr::I64x2ExtmulHighI32x4U => encode_prefix(0xFD, 0xDF, bin),
+            Instr::F32x4Abs => encode_prefix(0xFD, 0xE0, bin),
+            Instr::F32x4Neg => encode_prefix(0xFD, 0xE1, bin),
+                // ... reserved ...
+            Instr::F32x4Sqrt => encode_prefix(0xFD, 0xE3, bin),
+            Instr::F32x4Add => encode_prefix(0xFD, 0xE4, bin),
+            Instr::F32x4Sub => encode_prefix(0xFD, 0xE5, bin),
+            Instr::F32x4Mul => encode_prefix(0xFD, 0xE6, bin),
+            Instr::F32x4Div => encode_prefix(0xFD, 0xE7, bin),
+            Instr::F32x4Min => encode_prefix(0xFD, 0xE8, bin),
+            Instr::F32x4Max => encode_prefix(0xFD, 0xE9, bin),
+            Instr::F32x4Pmin => encode_prefix(0xFD, 0xEA, bin),
+            Instr::F32x4Pmax => encode_prefix(0xFD, 0xEB, bin),
+            Instr::F64x2Abs => encode_prefix(0xFD, 0xEC, bin),
+            Instr::F64x2Neg => encode_prefix(0xFD, 0xED, bin),
+                // ... reserved ...
+            Instr::F64x2Sqrt => encode_prefix(0xFD, 0xEF, bin),
+            Instr::F64x2Add => encode_prefix(0xFD, 0xF0, bin),
+            Instr::F64x2Sub => encode_prefix(0xFD, 0xF1, bin),
+            Instr::F64x2Mul => encode_prefix(0xFD, 0xF2, bin),
+            Instr::F64x2Div => encode_prefix(0xFD, 0xF3, bin),
+            Instr::F64x2Min => encode_prefix(0xFD, 0xF4, bin),
+            Instr::F64x2Max => encode_prefix(0xFD, 0xF5, bin),
+            Instr::F64x2Pmin => encode_prefix(0xFD, 0xF6, bin),
+            Instr::F64x2Pmax => encode_prefix(0xFD, 0xF7, bin),
+            Instr::I32x4TruncSatF32x4S => encode_prefix(0xFD, 0xF8, bin),
+            Instr::I32x4TruncSatF32x4U => encode_prefix(0xFD, 0xF9, bin),
+            Instr::F32x4ConvertI32x4S => encode_prefix(0xFD, 0xFA, bin),
+            Instr::F32x4ConvertI32x4U => encode_prefix(0xFD, 0xFB, bin),
+            Instr::I32x4TruncSatF64x2SZero => encode_prefix(0xFD, 0xFC, bin),
+            Instr::I32x4TruncSatF64x2UZero => encode_prefix(0xFD, 0xFD, bin),
+            Instr::F64x2ConvertLowI32x4S => encode_prefix(0xFD, 0xFE, bin),
+            Instr::F64x2ConvertLowI32x4U => encode_prefix(0xFD, 0xFF, bin),
+            // Relaxed vector
+            Instr::I8x16RelaxedSwizzle => encode_prefix(0xFD, 0x100, bin),
+            Instr::I32x4RelaxedTruncF32x4S => encode_prefix(0xFD, 0x101, bin),
+            Instr::I32x4RelaxedTruncF32x4U => encode_prefix(0xFD, 0x102, bin),
+            Instr::I32x4RelaxedTruncF64x2SZero => encode_prefix(0xFD, 0x103, bin),
+            Instr::I32x4RelaxedTruncF64x2UZero => encode_prefix(0xFD, 0x104, bin),
+            Instr::F32x4RelaxedMadd => encode_prefix(0xFD, 0x105, bin),
+            Instr::F32x4RelaxedNmadd => encode_prefix(0xFD, 0x106, bin),
+            Instr::F64x2RelaxedMadd => encode_prefix(0xFD, 0x107, bin),
+            Instr::F64x2RelaxedNmadd => encode_prefix(0xFD, 0x108, bin),
+            Instr::I8x16RelaxedLaneselect => encode_prefix(0xFD, 0x109, bin),
+            Instr::I16x8RelaxedLaneselect => encode_prefix(0xFD, 0x10A, bin),
+            Instr::I32x4RelaxedLaneselect => encode_prefix(0xFD, 0x10B, bin),
+            Instr::I64x2RelaxedLaneselect => encode_prefix(0xFD, 0x10C, bin),
+            Instr::F32x4RelaxedMin => encode_prefix(0xFD, 0x10D, bin),
+            Instr::F32x4RelaxedMax => encode_prefix(0xFD, 0x10E, bin),
+            Instr::F64x2RelaxedMin => encode_prefix(0xFD, 0x10F, bin),
+            Instr::F64x2RelaxedMax => encode_prefix(0xFD, 0x110, bin),
+            Instr::I16x8RelaxedQ15mulrS => encode_prefix(0xFD, 0x111, bin),
+            Instr::I16x8RelaxedDotI8x16I7x16S => encode_prefix(0xFD, 0x112, bin),
+            Instr::I32x4RelaxedDotI8x16I7x16AddS => encode_prefix(0xFD, 0x113, bin),
+            // Atomic memory
+            Instr::MemAtomicNotify(memarg) => { encode_prefix(0xFE, 0x00, bin)?; memarg.encode(bin) },
+            Instr::MemAtomicWait32(memarg) => { encode_prefix(0xFE, 0x01, bin)?; memarg.encode(bin) },
+            Instr::MemAtomicWait64(memarg) => { encode_prefix(0xFE, 0x02, bin)?; memarg.encode(bin) },
+            Instr::AtomicFence => { encode_prefix(0xFE, 0x03, bin)?; Byte(0x00).encode(bin) },
+                // ... reserved ...
+            Instr::I32AtomicLoad(memarg) => { encode_prefix(0xFE, 0x10, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicLoad(memarg) => { encode_prefix(0xFE, 0x11, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicLoad8U(memarg) => { encode_prefix(0xFE, 0x12, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicLoad16U(memarg) => { encode_prefix(0xFE, 0x13, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicLoad8U(memarg) => { encode_prefix(0xFE, 0x14, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicLoad16U(memarg) => { encode_prefix(0xFE, 0x15, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicLoad32U(memarg) => { encode_prefix(0xFE, 0x16, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicStore(memarg) => { encode_prefix(0xFE, 0x17, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicStore(memarg) => { encode_prefix(0xFE, 0x18, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicStore8(memarg) => { encode_prefix(0xFE, 0x19, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicStore16(memarg) => { encode_prefix(0xFE, 0x1A, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicStore8(memarg) => { encode_prefix(0xFE, 0x1B, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicStore16(memarg) => { encode_prefix(0xFE, 0x1C, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicStore32(memarg) => { encode_prefix(0xFE, 0x1D, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmwAdd(memarg) => { encode_prefix(0xFE, 0x1E, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmwAdd(memarg) => { encode_prefix(0xFE, 0x1F, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw8AddU(memarg) => { encode_prefix(0xFE, 0x20, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw16AddU(memarg) => { encode_prefix(0xFE, 0x21, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw8AddU(memarg) => { encode_prefix(0xFE, 0x22, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw16AddU(memarg) => { encode_prefix(0xFE, 0x23, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw32AddU(memarg) => { encode_prefix(0xFE, 0x24, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmwSub(memarg) => { encode_prefix(0xFE, 0x25, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmwSub(memarg) => { encode_prefix(0xFE, 0x26, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw8SubU(memarg) => { encode_prefix(0xFE, 0x27, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw16SubU(memarg) => { encode_prefix(0xFE, 0x28, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw8SubU(memarg) => { encode_prefix(0xFE, 0x29, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw16SubU(memarg) => { encode_prefix(0xFE, 0x2A, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw32SubU(memarg) => { encode_prefix(0xFE, 0x2B, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmwAnd(memarg) => { encode_prefix(0xFE, 0x2C, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmwAnd(memarg) => { encode_prefix(0xFE, 0x2D, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw8AndU(memarg) => { encode_prefix(0xFE, 0x2E, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw16AndU(memarg) => { encode_prefix(0xFE, 0x2F, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw8AndU(memarg) => { encode_prefix(0xFE, 0x30, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw16AndU(memarg) => { encode_prefix(0xFE, 0x31, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw32AndU(memarg) => { encode_prefix(0xFE, 0x32, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmwOr(memarg) => { encode_prefix(0xFE, 0x33, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmwOr(memarg) => { encode_prefix(0xFE, 0x34, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw8OrU(memarg) => { encode_prefix(0xFE, 0x35, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw16OrU(memarg) => { encode_prefix(0xFE, 0x36, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw8OrU(memarg) => { encode_prefix(0xFE, 0x37, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw16OrU(memarg) => { encode_prefix(0xFE, 0x38, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw32OrU(memarg) => { encode_prefix(0xFE, 0x39, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmwXor(memarg) => { encode_prefix(0xFE, 0x3A, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmwXor(memarg) => { encode_prefix(0xFE, 0x3B, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw8XorU(memarg) => { encode_prefix(0xFE, 0x3C, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw16XorU(memarg) => { encode_prefix(0xFE, 0x3D, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw8XorU(memarg) => { encode_prefix(0xFE, 0x3E, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw16XorU(memarg) => { encode_prefix(0xFE, 0x3F, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw32XorU(memarg) => { encode_prefix(0xFE, 0x40, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmwXchg(memarg) => { encode_prefix(0xFE, 0x41, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmwXchg(memarg) => { encode_prefix(0xFE, 0x42, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw8XchgU(memarg) => { encode_prefix(0xFE, 0x43, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw16XchgU(memarg) => { encode_prefix(0xFE, 0x44, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw8XchgU(memarg) => { encode_prefix(0xFE, 0x45, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw16XchgU(memarg) => { encode_prefix(0xFE, 0x46, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw32XchgU(memarg) => { encode_prefix(0xFE, 0x47, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmwCmpxchg(memarg) => { encode_prefix(0xFE, 0x48, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmwCmpxchg(memarg) => { encode_prefix(0xFE, 0x49, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw8CmpxchgU(memarg) => { encode_prefix(0xFE, 0x4A, bin)?; memarg.encode(bin) },
+            Instr::I32AtomicRmw16CmpxchgU(memarg) => { encode_prefix(0xFE, 0x4B, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw8CmpxchgU(memarg) => { encode_prefix(0xFE, 0x4C, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw16CmpxchgU(memarg) => { encode_prefix(0xFE, 0x4D, bin)?; memarg.encode(bin) },
+            Instr::I64AtomicRmw32CmpxchgU(memarg) => { encode_prefix(0xFE, 0x4E, bin)?; memarg.encode(bin) },
                 // ... reserved ...
-            id => Err(Error::InvalidVariantId{id, ty: "instr::Instr"})
         }
     }
 }
 
+/// Parses a lane index immediate, which is encoded as a single byte.
+fn parse_lane<Binary: WasmBinary>(bin: &mut Binary) -> Result<u8> {
+    let Byte(lane) = bin.parse()?;
+    Ok(lane)
+}
+
+/// Parses the 16 raw bytes of a vector immediate.
+fn parse_bytes<Binary: WasmBinary>(bin: &mut Binary) -> Result<[u8; 16]> {
+    let mut bytes = [0; 16];
+    for byte in bytes.iter_mut() {
+        let Byte(value) = bin.parse()?;
+        *byte = value;
+    }
+    Ok(bytes)
+}
+
+/// Parses the immediates of `br_on_cast` and `br_on_cast_fail`, whose first byte flags the
+/// nullability of the source and target reference types.
+fn parse_br_on_cast<Binary: WasmBinary>(bin: &mut Binary) -> Result<(idx::Label, ty::Ref, ty::Ref)> {
+    let Byte(flags) = bin.parse()?;
+    if flags > 0x03 {
+        return Err(Error::InvalidVariantId{ id: flags, ty: "instr::Instr" });
+    }
+    let label = bin.parse()?;
+    let from = ty::Ref { nullable: flags & 0x01 != 0, heap: bin.parse()? };
+    let to = ty::Ref { nullable: flags & 0x02 != 0, heap: bin.parse()? };
+    Ok((label, from, to))
+}
+
+fn encode_br_on_cast<Binary: WasmBinarySink>(label: &idx::Label, from: &ty::Ref, to: &ty::Ref, bin: &mut Binary) -> Result<()> {
+    Byte(from.nullable as u8 | (to.nullable as u8) << 1).encode(bin)?;
+    label.encode(bin)?;
+    from.heap.encode(bin)?;
+    to.heap.encode(bin)
+}
+
+/// Encodes the prefix byte and LEB-128 subopcode of a prefixed instruction.
+fn encode_prefix<Binary: WasmBinarySink>(prefix: u8, subopcode: u32, bin: &mut Binary) -> Result<()> {
+    Byte(prefix).encode(bin)?;
+    subopcode.encode(bin)
+}
+
+fn encode_instrs<Binary: WasmBinarySink>(instrs: &[Instr], bin: &mut Binary) -> Result<()> {
+    for instr in instrs.iter() {
+        instr.encode(bin)?;
+    }
+    Ok(())
+}
+
 struct SubExpr ( instr::Expr, instr::Instr );
 
 impl WasmBinaryParse for SubExpr {
@@ -233,6 +1409,7 @@ impl WasmBinaryParse for SubExpr {
             match next {
                 Instr::End => break,
                 Instr::Else => break,
+                Instr::Catch(_) | Instr::CatchAll | Instr::Delegate(_) => break,
                 _ => expr.0.push(next),
             }
         }
@@ -271,4 +1448,360 @@ impl WasmBinaryParse for IfInstr {
 
 impl From<IfInstr> for Instr {
     fn from(if_instr: IfInstr) -> Instr { Instr::If(if_instr.0, if_instr.1, if_instr.2) }
+}
+
+/// Parses the body and handlers of a legacy `try` block, using `parse_sub_expr` to parse each
+/// sequence of instructions up to the next `catch`, `catch_all`, `delegate` or `end`.
+fn parse_try<Binary, F>(ret_val: ty::RetVal, bin: &mut Binary, mut parse_sub_expr: F) -> Result<Instr>
+where F: FnMut(&mut Binary) -> Result<SubExpr> {
+    let SubExpr(body, mut next) = parse_sub_expr(bin)?;
+    let mut catches = vec![];
+    let mut catch_all = None;
+    loop {
+        match next {
+            Instr::Catch(tag) if catch_all.is_none() => {
+                let SubExpr(expr, n) = parse_sub_expr(bin)?;
+                catches.push((tag, expr));
+                next = n;
+            },
+            Instr::CatchAll if catch_all.is_none() => {
+                let SubExpr(expr, n) = parse_sub_expr(bin)?;
+                catch_all = Some(expr);
+                next = n;
+            },
+            // only a `try` block without handlers can delegate
+            Instr::Delegate(label) if catches.is_empty() && catch_all.is_none() => {
+                return Ok(Instr::TryDelegate(ret_val, body, label));
+            },
+            Instr::End => return Ok(Instr::Try(ret_val, body, catches, catch_all)),
+            Instr::Else => return Err(Error::UnexpectedOpcode{ instr: "Else" }),
+            _ => return Err(Error::UnexpectedOpcode{ instr: "Unknown" }),
+        }
+    }
+}
+
+impl WasmBinaryParse for instr::MemArg {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let align: u32 = bin.parse()?;
+        // bit 6 of the alignment flags the presence of an explicit memory index
+        if align & 0x40 != 0 {
+            let mem = bin.parse()?;
+            Ok(Self { align: align & !0x40, offset: bin.parse()?, mem })
+        } else {
+            Ok(Self { align, offset: bin.parse()?, mem: idx::Mem(0) })
+        }
+    }
+}
+
+impl WasmBinaryEncode for instr::MemArg {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        if self.mem.0 != 0 {
+            (self.align | 0x40).encode(bin)?;
+            self.mem.encode(bin)?;
+        } else {
+            self.align.encode(bin)?;
+        }
+        self.offset.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for instr::Catch {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x00 => Ok(Self::Catch(bin.parse()?, bin.parse()?)),
+            0x01 => Ok(Self::CatchRef(bin.parse()?, bin.parse()?)),
+            0x02 => Ok(Self::CatchAll(bin.parse()?)),
+            0x03 => Ok(Self::CatchAllRef(bin.parse()?)),
+            id => Err(Error::InvalidVariantId{id, ty: "instr::Catch"})
+        }
+    }
+}
+
+impl WasmBinaryEncode for instr::Catch {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::Catch(tag, label) => { Byte(0x00).encode(bin)?; tag.encode(bin)?; label.encode(bin) },
+            Self::CatchRef(tag, label) => { Byte(0x01).encode(bin)?; tag.encode(bin)?; label.encode(bin) },
+            Self::CatchAll(label) => { Byte(0x02).encode(bin)?; label.encode(bin) },
+            Self::CatchAllRef(label) => { Byte(0x03).encode(bin)?; label.encode(bin) },
+        }
+    }
+}
+
+
+/// Parses the instructions of an expression up to its final `end`, recording the byte range of
+/// each instruction (including `else` and `end`) in `ranges`, in the order they appear.
+pub(crate) fn parse_expr_with_offsets<Binary: WasmBinary>(bin: &mut Positioned<Binary>, ranges: &mut Vec<Range<usize>>) -> Result<instr::Expr> {
+    match parse_sub_expr_with_offsets(bin, ranges)? {
+        SubExpr(expr, Instr::End) => Ok(expr),
+        SubExpr(_, Instr::Else) => Err(Error::UnexpectedOpcode{ instr: "Else" }),
+        SubExpr(_, _) => Err(Error::UnexpectedOpcode{ instr: "Unknown" })
+    }
+}
+
+fn parse_sub_expr_with_offsets<Binary: WasmBinary>(bin: &mut Positioned<Binary>, ranges: &mut Vec<Range<usize>>) -> Result<SubExpr> {
+    let mut expr = instr::Expr(vec![]);
+    loop {
+        let start = bin.offset();
+        let index = ranges.len();
+        ranges.push(start..start);
+
+        let Byte(opcode) = bin.parse()?;
+        let next = match opcode {
+            0x02 => Instr::Block(bin.parse()?, parse_expr_with_offsets(bin, ranges)?),
+            0x03 => Instr::Loop(bin.parse()?, parse_expr_with_offsets(bin, ranges)?),
+            0x04 => {
+                let ret_val = bin.parse()?;
+                let SubExpr(branch_1, next) = parse_sub_expr_with_offsets(bin, ranges)?;
+                let branch_2 = if next == Instr::Else {
+                    let else_index = ranges.len() - 1;
+                    let SubExpr(branch_2, next) = parse_sub_expr_with_offsets(bin, ranges)?;
+                    if next != Instr::End {
+                        return Err(Error::UnexpectedOpcode{ instr: "Unknown" });
+                    }
+                    if branch_2.0.is_empty() {
+                        // an empty else branch is indistinguishable from a missing one
+                        ranges.remove(else_index);
+                    }
+                    branch_2
+                } else {
+                    instr::Expr(vec![])
+                };
+                Instr::If(ret_val, branch_1, branch_2)
+            },
+            0x06 => parse_try(bin.parse()?, bin, |bin| parse_sub_expr_with_offsets(bin, ranges))?,
+            0x1F => Instr::TryTable(bin.parse()?, bin.parse()?, parse_expr_with_offsets(bin, ranges)?),
+            opcode => parse_opcode(opcode, bin)?,
+        };
+        ranges[index].end = bin.offset();
+
+        match next {
+            Instr::End | Instr::Else => return Ok(SubExpr(expr, next)),
+            Instr::Catch(_) | Instr::CatchAll | Instr::Delegate(_) => return Ok(SubExpr(expr, next)),
+            _ => expr.0.push(next),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::binary::{WasmBinary, WasmBinaryEncode, Result, Error};
+    use crate::structure::{idx, instr::{Instr, MemArg}};
+
+    fn memarg(align: u32, offset: u64) -> MemArg {
+        MemArg { align, offset, mem: idx::Mem(0) }
+    }
+
+    fn assert_roundtrip(bytes: &[u8], instr: Instr) {
+        let result: Instr = bytes.iter().copied().parse().unwrap();
+        assert_eq!(result, instr);
+        let mut bin = vec![];
+        instr.encode(&mut bin).unwrap();
+        assert_eq!(bin, bytes);
+    }
+
+    #[test]
+    fn can_parse_sign_extension_instructions() {
+        assert_roundtrip(&[0xC0], Instr::I32Extend8S);
+        assert_roundtrip(&[0xC1], Instr::I32Extend16S);
+        assert_roundtrip(&[0xC2], Instr::I64Extend8S);
+        assert_roundtrip(&[0xC3], Instr::I64Extend16S);
+        assert_roundtrip(&[0xC4], Instr::I64Extend32S);
+    }
+
+    #[test]
+    fn can_parse_saturating_truncation_instructions() {
+        assert_roundtrip(&[0xFC, 0x00], Instr::I32TruncSatF32S);
+        assert_roundtrip(&[0xFC, 0x01], Instr::I32TruncSatF32U);
+        assert_roundtrip(&[0xFC, 0x02], Instr::I32TruncSatF64S);
+        assert_roundtrip(&[0xFC, 0x03], Instr::I32TruncSatF64U);
+        assert_roundtrip(&[0xFC, 0x04], Instr::I64TruncSatF32S);
+        assert_roundtrip(&[0xFC, 0x05], Instr::I64TruncSatF32U);
+        assert_roundtrip(&[0xFC, 0x06], Instr::I64TruncSatF64S);
+        assert_roundtrip(&[0xFC, 0x07], Instr::I64TruncSatF64U);
+    }
+
+    #[test]
+    fn can_parse_bulk_memory_instructions() {
+        use crate::structure::idx;
+        assert_roundtrip(&[0xFC, 0x08, 0x01, 0x00], Instr::MemInit(idx::Data(1), idx::Mem(0)));
+        assert_roundtrip(&[0xFC, 0x09, 0x02], Instr::DataDrop(idx::Data(2)));
+        assert_roundtrip(&[0xFC, 0x0A, 0x00, 0x00], Instr::MemCopy(idx::Mem(0), idx::Mem(0)));
+        assert_roundtrip(&[0xFC, 0x0B, 0x00], Instr::MemFill(idx::Mem(0)));
+        assert_roundtrip(&[0xFC, 0x0C, 0x03, 0x01], Instr::TableInit(idx::Elem(3), idx::Table(1)));
+        assert_roundtrip(&[0xFC, 0x0D, 0x04], Instr::ElemDrop(idx::Elem(4)));
+        assert_roundtrip(&[0xFC, 0x0E, 0x01, 0x02], Instr::TableCopy(idx::Table(1), idx::Table(2)));
+    }
+
+    #[test]
+    fn can_parse_reference_instructions() {
+        use crate::structure::{idx, ty};
+        assert_roundtrip(&[0x1C, 0x01, 0x6F], Instr::SelectTyped(vec![ty::Val::EXTERN_REF]));
+        assert_roundtrip(&[0x25, 0x01], Instr::TableGet(idx::Table(1)));
+        assert_roundtrip(&[0x26, 0x02], Instr::TableSet(idx::Table(2)));
+        assert_roundtrip(&[0xD0, 0x70], Instr::RefNull(ty::Heap::Func));
+        assert_roundtrip(&[0xD1], Instr::RefIsNull);
+        assert_roundtrip(&[0xD2, 0x03], Instr::RefFunc(idx::Func(3)));
+        assert_roundtrip(&[0xFC, 0x0F, 0x01], Instr::TableGrow(idx::Table(1)));
+        assert_roundtrip(&[0xFC, 0x10, 0x01], Instr::TableSize(idx::Table(1)));
+        assert_roundtrip(&[0xFC, 0x11, 0x01], Instr::TableFill(idx::Table(1)));
+    }
+
+    #[test]
+    fn can_parse_vector_instructions() {
+        assert_roundtrip(&[0xFD, 0x00, 0x04, 0x10], Instr::V128Load(memarg(4, 16)));
+        assert_roundtrip(
+            &[0xFD, 0x0C, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80],
+            Instr::ConstV128(1 | 1 << 127),
+        );
+        assert_roundtrip(
+            &[0xFD, 0x0D, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17],
+            Instr::I8x16Shuffle([0, 1, 2, 3, 4, 5, 6, 7, 16, 17, 18, 19, 20, 21, 22, 23]),
+        );
+        assert_roundtrip(&[0xFD, 0x15, 0x0F], Instr::I8x16ExtractLaneS(15));
+        assert_roundtrip(&[0xFD, 0x54, 0x00, 0x00, 0x03], Instr::V128Load8Lane(memarg(0, 0), 3));
+        assert_roundtrip(&[0xFD, 0x4F], Instr::V128AndNot);
+        assert_roundtrip(&[0xFD, 0x80, 0x01], Instr::I16x8Abs);
+        assert_roundtrip(&[0xFD, 0xFF, 0x01], Instr::F64x2ConvertLowI32x4U);
+
+        let result: Result<Instr> = [0xFD, 0x9A, 0x01].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidSubopcode { prefix: 0xFD, subopcode: 0x9A, ty: "instr::Instr" }));
+    }
+
+    #[test]
+    fn can_parse_atomic_instructions() {
+        assert_roundtrip(&[0xFE, 0x00, 0x02, 0x00], Instr::MemAtomicNotify(memarg(2, 0)));
+        assert_roundtrip(&[0xFE, 0x03, 0x00], Instr::AtomicFence);
+        assert_roundtrip(&[0xFE, 0x11, 0x03, 0x08], Instr::I64AtomicLoad(memarg(3, 8)));
+        assert_roundtrip(&[0xFE, 0x1A, 0x01, 0x00], Instr::I32AtomicStore16(memarg(1, 0)));
+        assert_roundtrip(&[0xFE, 0x24, 0x02, 0x00], Instr::I64AtomicRmw32AddU(memarg(2, 0)));
+        assert_roundtrip(&[0xFE, 0x48, 0x02, 0x00], Instr::I32AtomicRmwCmpxchg(memarg(2, 0)));
+
+        let result: Result<Instr> = [0xFE, 0x03, 0x01].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidVariantId { id: 0x01, ty: "instr::Instr" }));
+    }
+
+    #[test]
+    fn can_parse_tail_call_instructions() {
+        use crate::structure::idx;
+        assert_roundtrip(&[0x12, 0x02], Instr::ReturnCall(idx::Func(2)));
+        assert_roundtrip(&[0x13, 0x01, 0x00], Instr::ReturnCallIndirect(idx::Type(1), idx::Table(0)));
+    }
+
+    #[test]
+    fn subopcode_is_a_leb128_integer() {
+        let result: Instr = [0xFC, 0x87, 0x00].iter().copied().parse().unwrap();
+        assert_eq!(result, Instr::I64TruncSatF64U);
+    }
+
+    #[test]
+    fn fails_to_parse_unknown_subopcode() {
+        let result: Result<Instr> = [0xFC, 0xFF, 0x01].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidSubopcode { prefix: 0xFC, subopcode: 0xFF, ty: "instr::Instr" }));
+    }
+
+    #[test]
+    fn can_parse_block_types() {
+        use crate::structure::{ty, idx, instr::Expr};
+        assert_roundtrip(&[0x02, 0x40, 0x0B], Instr::Block(ty::RetVal::Empty, Expr(vec![])));
+        assert_roundtrip(&[0x02, 0x7E, 0x0B], Instr::Block(ty::RetVal::Val(ty::Val::I64), Expr(vec![])));
+        assert_roundtrip(&[0x02, 0x03, 0x0B], Instr::Block(ty::RetVal::Type(idx::Type(3)), Expr(vec![])));
+        assert_roundtrip(&[0x02, 0xC0, 0x00, 0x0B], Instr::Block(ty::RetVal::Type(idx::Type(64)), Expr(vec![])));
+
+        let result: Result<Instr> = [0x02, 0xBF, 0x7F, 0x0B].iter().copied().parse();
+        assert_eq!(result, Err(Error::OutOfRangeSignedInteger));
+    }
+
+    #[test]
+    fn can_parse_exception_instructions() {
+        use crate::structure::{ty, idx, instr::{Expr, Catch}};
+        assert_roundtrip(&[0x08, 0x01], Instr::Throw(idx::Tag(1)));
+        assert_roundtrip(&[0x0A], Instr::ThrowRef);
+        assert_roundtrip(
+            &[0x1F, 0x40, 0x02, 0x00, 0x01, 0x00, 0x03, 0x01, 0x08, 0x01, 0x0B],
+            Instr::TryTable(ty::RetVal::Empty, vec![
+                Catch::Catch(idx::Tag(1), idx::Label(0)),
+                Catch::CatchAllRef(idx::Label(1)),
+            ], Expr(vec![Instr::Throw(idx::Tag(1))])),
+        );
+
+        let result: Result<Instr> = [0x1F, 0x40, 0x01, 0x04, 0x00, 0x0B].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidVariantId { id: 0x04, ty: "instr::Catch" }));
+    }
+
+    #[test]
+    fn can_parse_legacy_exception_instructions() {
+        use crate::structure::{ty, idx, instr::Expr};
+        assert_roundtrip(
+            &[0x06, 0x40, 0x01, 0x07, 0x00, 0x1A, 0x07, 0x01, 0x19, 0x09, 0x00, 0x0B],
+            Instr::Try(ty::RetVal::Empty, Expr(vec![Instr::Nop]), vec![
+                (idx::Tag(0), Expr(vec![Instr::Drop])),
+                (idx::Tag(1), Expr(vec![])),
+            ], Some(Expr(vec![Instr::Rethrow(idx::Label(0))]))),
+        );
+        assert_roundtrip(&[0x06, 0x40, 0x01, 0x18, 0x02], Instr::TryDelegate(ty::RetVal::Empty, Expr(vec![Instr::Nop]), idx::Label(2)));
+
+        // no handler can follow `catch_all`
+        let result: Result<Instr> = [0x06, 0x40, 0x19, 0x07, 0x00, 0x0B].iter().copied().parse();
+        assert_eq!(result, Err(Error::UnexpectedOpcode { instr: "Unknown" }));
+    }
+
+    #[test]
+    fn can_parse_relaxed_vector_instructions() {
+        assert_roundtrip(&[0xFD, 0x80, 0x02], Instr::I8x16RelaxedSwizzle);
+        assert_roundtrip(&[0xFD, 0x85, 0x02], Instr::F32x4RelaxedMadd);
+        assert_roundtrip(&[0xFD, 0x8C, 0x02], Instr::I64x2RelaxedLaneselect);
+        assert_roundtrip(&[0xFD, 0x93, 0x02], Instr::I32x4RelaxedDotI8x16I7x16AddS);
+
+        let result: Result<Instr> = [0xFD, 0x94, 0x02].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidSubopcode { prefix: 0xFD, subopcode: 0x114, ty: "instr::Instr" }));
+    }
+
+    #[test]
+    fn can_parse_64_bit_offsets() {
+        assert_roundtrip(&[0x29, 0x03, 0x80, 0x80, 0x80, 0x80, 0x10], Instr::I64Load(memarg(3, 1 << 32)));
+        assert_roundtrip(&[0xFD, 0x54, 0x00, 0x80, 0x80, 0x80, 0x80, 0x10, 0x0F], Instr::V128Load8Lane(memarg(0, 1 << 32), 15));
+    }
+
+    #[test]
+    fn can_parse_memory_indices() {
+        assert_roundtrip(&[0x28, 0x42, 0x01, 0x08], Instr::I32Load(MemArg { align: 2, offset: 8, mem: idx::Mem(1) }));
+        assert_roundtrip(&[0xFE, 0x10, 0x42, 0x03, 0x00], Instr::I32AtomicLoad(MemArg { align: 2, offset: 0, mem: idx::Mem(3) }));
+        // an explicit index of the first memory is accepted, but is not encoded back
+        let result: Instr = [0x28, 0x42, 0x00, 0x08].iter().copied().parse().unwrap();
+        assert_eq!(result, Instr::I32Load(memarg(2, 8)));
+    }
+
+    #[test]
+    fn can_parse_gc_instructions() {
+        use crate::structure::{ty, instr::Expr};
+        let ty0 = |nullable| ty::Ref { nullable, heap: ty::Heap::Type(idx::Type(0)) };
+        assert_roundtrip(&[0x02, 0x63, 0x00, 0x0B], Instr::Block(ty::RetVal::Val(ty0(true).into()), Expr(vec![])));
+        assert_roundtrip(&[0x02, 0x64, 0x6C, 0x0B], Instr::Block(ty::RetVal::Val(ty::Ref { nullable: false, heap: ty::Heap::I31 }.into()), Expr(vec![])));
+        assert_roundtrip(&[0xD0, 0x00], Instr::RefNull(ty::Heap::Type(idx::Type(0))));
+        assert_roundtrip(&[0xD0, 0x71], Instr::RefNull(ty::Heap::None));
+        assert_roundtrip(&[0x14, 0x02], Instr::CallRef(idx::Type(2)));
+        assert_roundtrip(&[0x15, 0x02], Instr::ReturnCallRef(idx::Type(2)));
+        assert_roundtrip(&[0xD3], Instr::RefEq);
+        assert_roundtrip(&[0xD4], Instr::RefAsNonNull);
+        assert_roundtrip(&[0xD5, 0x01], Instr::BrOnNull(idx::Label(1)));
+        assert_roundtrip(&[0xD6, 0x01], Instr::BrOnNonNull(idx::Label(1)));
+        assert_roundtrip(&[0xFB, 0x00, 0x01], Instr::StructNew(idx::Type(1)));
+        assert_roundtrip(&[0xFB, 0x03, 0x01, 0x02], Instr::StructGetS(idx::Type(1), idx::Field(2)));
+        assert_roundtrip(&[0xFB, 0x05, 0x01, 0x02], Instr::StructSet(idx::Type(1), idx::Field(2)));
+        assert_roundtrip(&[0xFB, 0x08, 0x01, 0x03], Instr::ArrayNewFixed(idx::Type(1), 3));
+        assert_roundtrip(&[0xFB, 0x0F], Instr::ArrayLen);
+        assert_roundtrip(&[0xFB, 0x11, 0x01, 0x02], Instr::ArrayCopy(idx::Type(1), idx::Type(2)));
+        assert_roundtrip(&[0xFB, 0x14, 0x00], Instr::RefTest(ty0(false)));
+        assert_roundtrip(&[0xFB, 0x15, 0x6B], Instr::RefTest(ty::Ref { nullable: true, heap: ty::Heap::Struct }));
+        assert_roundtrip(&[0xFB, 0x17, 0x00], Instr::RefCast(ty0(true)));
+        assert_roundtrip(&[0xFB, 0x18, 0x01, 0x00, 0x6E, 0x00], Instr::BrOnCast(idx::Label(0), ty::Ref { nullable: true, heap: ty::Heap::Any }, ty0(false)));
+        assert_roundtrip(&[0xFB, 0x19, 0x03, 0x00, 0x6E, 0x00], Instr::BrOnCastFail(idx::Label(0), ty::Ref { nullable: true, heap: ty::Heap::Any }, ty0(true)));
+        assert_roundtrip(&[0xFB, 0x1C], Instr::RefI31);
+        assert_roundtrip(&[0xFB, 0x1E], Instr::I31GetU);
+
+        let result: Result<Instr> = [0xFB, 0x18, 0x04, 0x00, 0x6E, 0x00].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidVariantId { id: 0x04, ty: "instr::Instr" }));
+    }
 }
\ No newline at end of file
@@ -1,15 +1,22 @@
-//! This module defines the parsing of all the elements required to parse a WebAssembly module.
+//! This module defines the parsing and encoding of all the elements required to read and write a
+//! WebAssembly module.
 
 pub mod core;
 
 pub mod idx;
 pub mod instr;
 pub mod module;
+pub mod name;
+pub mod offsets;
+pub mod stream;
 pub mod ty;
 
 pub use self::core::byte::Byte;
 pub use self::core::vec::{CompactVec, UnwrappingVec};
 pub use self::core::sized::{Sized, Consume};
+pub use self::core::position::Positioned;
+pub use self::core::slice::{SliceBinary, WasmSliceParse};
 pub use self::core::error::Error;
 pub use self::core::result::Result;
-pub use self::core::traits::{WasmBinary, WasmBinaryParse, WasmBinaryParseProxy};
\ No newline at end of file
+pub use self::core::traits::{WasmBinary, WasmBinaryParse, WasmBinaryParseWith, WasmBinaryParseProxy};
+pub use self::core::traits::{WasmBinarySink, WasmBinaryEncode};
\ No newline at end of file
@@ -1,73 +1,430 @@
-//! This module defines the parsing of a WebAssembly module.
+//! This module defines the parsing and encoding of a WebAssembly module.
 
-use crate::binary::{WasmBinary, WasmBinaryParse, WasmBinaryParseProxy, Byte, Result, Error};
-use crate::binary::{Sized, Consume, UnwrappingVec, CompactVec};
-use crate::structure::{module, idx};
+use crate::binary::{WasmBinary, WasmBinaryParse, WasmBinaryParseWith, WasmBinaryParseProxy, Byte, Result, Error};
+use crate::binary::{WasmBinarySink, WasmBinaryEncode};
+use crate::binary::{Sized, UnwrappingVec, CompactVec, Positioned, SliceBinary, WasmSliceParse};
+use crate::binary::core::slice::RawSection;
+use crate::binary::{offsets, offsets::{Offsets, WithOffsets}, instr::parse_expr_with_offsets};
+use crate::structure::{module, idx, instr, ty};
+use crate::validation::{self, Features};
 
-impl WasmBinaryParse for (String, Consume) {
+/// A proxy type to parse elements lazily.
+/// 
+/// Parsing a `Lazy<module::Module>` keeps the function bodies as raw bytes, which are decoded
+/// on demand by `module::Body::expr`.
+/// 
+/// # Example
+/// 
+/// ```
+/// # use whasm::binary::{WasmBinary, WasmBinaryParseProxy, module::Lazy};
+/// # use whasm::structure::{module, instr};
+/// let mut iter = [
+///     0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00,
+///     0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+///     0x03, 0x02, 0x01, 0x00,
+///     0x0A, 0x05, 0x01, 0x03, 0x00, 0x01, 0x0B,
+/// ].iter().copied();
+/// let module: module::Module = iter.parse::<Lazy<_>>().unwrap().unwrap();
+/// 
+/// if let module::Body::Raw { bytes, .. } = &module.funcs[0].body {
+///     assert_eq!(bytes, &[0x01, 0x0B]);
+/// }
+/// assert_eq!(module.funcs[0].body.expr().unwrap(), &instr::Expr(vec![instr::Instr::Nop]));
+/// ```
+pub struct Lazy<T> ( pub T );
+
+impl<T> WasmBinaryParseProxy for Lazy<T>
+where Self: WasmBinaryParse {
+    type Inner = T;
+    fn unwrap(self) -> Self::Inner { self.0 }
+}
+
+/// A proxy type to parse the function bodies of a module in parallel, enabled with the
+/// `parallel` feature.
+/// 
+/// The module is parsed lazily, and then the function bodies are decoded on the rayon thread
+/// pool. If several function bodies are malformed, the error of the first one is reported.
+#[cfg(feature = "parallel")]
+pub struct Parallel<T> ( pub T );
+
+#[cfg(feature = "parallel")]
+impl<T> WasmBinaryParseProxy for Parallel<T>
+where Self: WasmBinaryParse {
+    type Inner = T;
+    fn unwrap(self) -> Self::Inner { self.0 }
+}
+
+enum Mode<'a> {
+    Eager,
+    Lazy,
+    WithOffsets(&'a mut Offsets),
+}
+
+impl WasmBinaryParse for module::Module {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
-        Ok((bin.parse()?, bin.parse()?))
+        bin.parse_with(Features::ALL)
     }
 }
 
-impl WasmBinaryParse for module::Module {
+impl WasmBinaryParseWith for module::Module {
+    fn parse_with<Binary: WasmBinary>(bin: &mut Binary, features: Features) -> Result<Self> {
+        let mut bin = Positioned::new(bin);
+        parse_module(&mut bin, Mode::Eager, features).map_err(|error| locate(error, bin.offset()))
+    }
+}
+
+impl WasmBinaryParse for Lazy<module::Module> {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        bin.parse_with(Features::ALL)
+    }
+}
+
+impl WasmBinaryParseWith for Lazy<module::Module> {
+    fn parse_with<Binary: WasmBinary>(bin: &mut Binary, features: Features) -> Result<Self> {
+        let mut bin = Positioned::new(bin);
+        let module = parse_module(&mut bin, Mode::Lazy, features).map_err(|error| locate(error, bin.offset()))?;
+        Ok(Lazy(module))
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl WasmBinaryParse for Parallel<module::Module> {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
-        let magic: [Byte; 4] = bin.parse()?;
-        if magic != ['\0', 'a', 's', 'm'] {
-            return Err(Error::InvalidPreambleMagic)
+        use rayon::prelude::*;
+        let Lazy(module): Lazy<module::Module> = bin.parse()?;
+        let error = module.funcs.par_iter()
+            .map(|func| func.body.expr().map(|_| ()))
+            .find_first(|result| result.is_err());
+        error.unwrap_or(Ok(()))?;
+        Ok(Parallel(module))
+    }
+}
+
+impl WasmBinaryParse for WithOffsets<module::Module> {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        bin.parse_with(Features::ALL)
+    }
+}
+
+impl WasmBinaryParseWith for WithOffsets<module::Module> {
+    fn parse_with<Binary: WasmBinary>(bin: &mut Binary, features: Features) -> Result<Self> {
+        let mut bin = Positioned::new(bin);
+        let mut offsets = Offsets::default();
+        let module = parse_module(&mut bin, Mode::WithOffsets(&mut offsets), features).map_err(|error| locate(error, bin.offset()))?;
+        Ok(WithOffsets(module, offsets))
+    }
+}
+
+fn locate(error: Error, offset: usize) -> Error {
+    // point at the offending byte, or at the end of the input if there are no bytes left
+    let offset = match error {
+        Error::UnexpectedEndOfFile => offset,
+        _ => offset.saturating_sub(1),
+    };
+    error.at(offset)
+}
+
+/// Checks the section that was just parsed into `module` against `features`.
+fn check_features(features: Features, id: u8, module: &module::Module) -> Result<()> {
+    // every proposal is enabled, skip walking the section again
+    if features == Features::ALL {
+        return Ok(());
+    }
+    features.check_section(id, module).map_err(|error| match error {
+        validation::Error::FeatureDisabled { feature } => Error::FeatureDisabled { feature },
+        error => unreachable!("unexpected error while checking features: {}", error),
+    })
+}
+
+impl WasmSliceParse for module::Module {
+    fn parse_slice(bin: &mut SliceBinary, features: Features) -> Result<Self> {
+        parse_module_slice(bin, Mode::Eager, features)
+    }
+}
+
+impl WasmSliceParse for Lazy<module::Module> {
+    fn parse_slice(bin: &mut SliceBinary, features: Features) -> Result<Self> {
+        Ok(Lazy(parse_module_slice(bin, Mode::Lazy, features)?))
+    }
+}
+
+impl WasmSliceParse for WithOffsets<module::Module> {
+    fn parse_slice(bin: &mut SliceBinary, features: Features) -> Result<Self> {
+        let mut offsets = Offsets::default();
+        let module = parse_module_slice(bin, Mode::WithOffsets(&mut offsets), features)?;
+        Ok(WithOffsets(module, offsets))
+    }
+}
+
+/// Parses a module like `parse_module`, but frames the sections and function bodies with
+/// `SliceBinary::read_sized` and borrows names and payloads from the slice.
+fn parse_module_slice(bin: &mut SliceBinary, mut mode: Mode, features: Features) -> Result<module::Module> {
+    let mut func_types = vec![];
+    let mut result = module::Module::default();
+
+    let mut last_id = 0;
+
+    for section in bin.clone().sections()? {
+        let RawSection { id, offset, mut content } = section?;
+
+        if id != 0x00 {
+            let order = section_order(id).ok_or(Error::InvalidSectionId{ id: Byte(id) }.at(offset))?;
+            if order <= section_order(last_id).unwrap_or(0) {
+                // follow strict section order
+                return Err(Error::UnexpectedSectionId{ id: Byte(id) }.at(offset))
+            } else {
+                last_id = id;
+            }
         }
-        let version: [Byte; 4] = bin.parse()?;
-        if version != [1, 0, 0, 0] {
-            return Err(Error::InvalidPreambleVersion)
+
+        let end = content.offset() + content.remaining();
+        parse_section_slice(id, last_id, &mut content, &mut result, &mut func_types, &mut mode)
+            .map_err(|error| locate(error, content.offset()))?;
+        if content.remaining() != 0 {
+            return Err(Error::SectionSizeMissmatch { id: Byte(id) }.at(content.offset()));
         }
+        check_features(features, id, &result).map_err(|error| error.at(offset))?;
 
-        let mut func_types = vec![];
-        let mut result = module::Module::default();
+        if let Mode::WithOffsets(ref mut offsets) = mode {
+            offsets.sections.push(offsets::Section { id, range: offset..end });
+        }
+    }
+    bin.skip(bin.remaining())?;
 
-        let mut bin = bin.peekable();
-        let mut last_id = 0;
+    // the bodies of the functions declared in the function section are missing
+    if !func_types.is_empty() && result.funcs.is_empty() {
+        return Err(Error::UnexpectedEndOfFile.at(bin.offset()));
+    }
 
-        while bin.peek().is_some() {
-            let Byte(id) = bin.parse()?;
+    Ok(result)
+}
 
-            if id != 0x00 {
-                if id <= last_id {
-                    // follow strict section order
-                    return Err(Error::UnexpectedSectionId{ id: Byte(id) })
-                } else {
-                    last_id = id;
+fn parse_section_slice(id: u8, last_id: u8, bin: &mut SliceBinary, result: &mut module::Module, func_types: &mut Vec<idx::Type>, mode: &mut Mode) -> Result<()> {
+    match id {
+        0x00 => {
+            let name = bin.read_name()?.into();
+            let data = bin.read_bytes(bin.remaining())?.to_vec();
+            result.customs.push(module::Custom { name, data, after: last_id });
+        },
+        0x01 => result.types = bin.parse()?,
+        0x02 => result.imports = parse_vec_slice(bin, |bin| Ok(module::Import {
+            module: bin.read_name()?.into(),
+            name: bin.read_name()?.into(),
+            desc: bin.parse()?,
+        }))?,
+        0x03 => *func_types = bin.parse()?,
+        0x04 => result.tables = bin.parse()?,
+        0x05 => result.mems = bin.parse()?,
+        0x06 => result.globals = bin.parse()?,
+        0x07 => result.exports = parse_vec_slice(bin, |bin| Ok(module::Export {
+            name: bin.read_name()?.into(),
+            desc: bin.parse()?,
+        }))?,
+        0x08 => result.start = Some(bin.parse()?),
+        0x09 => result.elem = bin.parse()?,
+        0x0A => {
+            result.funcs = parse_vec_slice(bin, |bin| {
+                let mut body = bin.read_sized()?;
+                let func = match mode {
+                    Mode::Eager => body.parse()?,
+                    Mode::Lazy => {
+                        let locals = body.parse::<CompactVec<_>>()?.unwrap();
+                        let bytes = body.read_bytes(body.remaining())?.to_vec();
+                        module::Func { ty: idx::Type(0), locals, body: module::Body::Raw { bytes, expr: Default::default() } }
+                    },
+                    Mode::WithOffsets(ref mut offsets) => {
+                        let start = body.offset();
+                        let mut positioned = Positioned::starting_at(&mut body, start);
+                        let locals = positioned.parse::<CompactVec<_>>()?.unwrap();
+                        let mut instrs = vec![];
+                        let expr = parse_expr_with_offsets(&mut positioned, &mut instrs)?;
+                        offsets.funcs.push(offsets::Func { range: start..positioned.offset(), instrs });
+                        module::Func { ty: idx::Type(0), locals, body: module::Body::Expr(expr) }
+                    },
+                };
+                if body.remaining() != 0 {
+                    return Err(Error::FunctionSizeMissmatch);
                 }
+                Ok(func)
+            })?;
+            if result.funcs.len() != func_types.len() {
+                return Err(Error::UnexpectedEndOfFile);
             }
+            for (func, ty) in result.funcs.iter_mut().zip(func_types.iter()) {
+                func.ty = *ty;
+            }
+        },
+        0x0B => result.data = parse_vec_slice(bin, |bin| {
+            let flags = bin.read_u32()?;
+            let mode = parse_data_mode(flags, bin)?;
+            Ok(module::Data { mode, init: bin.read_vec()?.to_vec() })
+        })?,
+        0x0C => result.data_count = Some(bin.parse()?),
+        0x0D => result.tags = bin.parse()?,
+        _ => return Err(Error::InvalidSectionId{ id: Byte(id) })
+    }
+    Ok(())
+}
 
-            match id {
-                0x00 => {
-                    let (_name, _) : (String, Consume) = bin.parse::<Sized<_>>()?.unwrap();
-                },
-                0x01 => result.types = bin.parse::<Sized<_>>()?.unwrap(),
-                0x02 => result.imports = bin.parse::<Sized<_>>()?.unwrap(),
-                0x03 => func_types = bin.parse::<Sized<_>>()?.unwrap(),
-                0x04 => result.tables = bin.parse::<Sized<_>>()?.unwrap(),
-                0x05 => result.mems = bin.parse::<Sized<_>>()?.unwrap(),
-                0x06 => result.globals = bin.parse::<Sized<_>>()?.unwrap(),
-                0x07 => result.exports = bin.parse::<Sized<_>>()?.unwrap(),
-                0x08 => result.start = Some(bin.parse::<Sized<_>>()?.unwrap()),
-                0x09 => result.elem = bin.parse::<Sized<_>>()?.unwrap(),
-                0x0A => {
-                    result.funcs = bin.parse::<Sized<UnwrappingVec<Sized<_>>>>()?.unwrap().unwrap();
-                    let n = func_types.len();
-                    if result.funcs.len() != n {
-                        return Err(Error::UnexpectedEndOfFile);
-                    }
-                    for i in 0..n {
-                        result.funcs[i].ty = func_types[i];
-                    }
-                },
-                0x0B => result.data = bin.parse::<Sized<_>>()?.unwrap(),
-                _ => return Err(Error::InvalidSectionId{ id: Byte(id) })
+fn parse_vec_slice<'a, T, F>(bin: &mut SliceBinary<'a>, mut parse: F) -> Result<Vec<T>>
+where F: FnMut(&mut SliceBinary<'a>) -> Result<T> {
+    let count = bin.read_u32()?;
+    // the count comes from the input, so it can not be trusted to reserve memory
+    let mut items = vec![];
+    for _ in 0..count {
+        items.push(parse(bin)?);
+    }
+    Ok(items)
+}
+
+fn parse_module<Binary: WasmBinary>(bin: &mut Positioned<Binary>, mut mode: Mode, features: Features) -> Result<module::Module> {
+    let magic: [Byte; 4] = bin.parse()?;
+    if magic != ['\0', 'a', 's', 'm'] {
+        return Err(Error::InvalidPreambleMagic)
+    }
+    let version: [Byte; 4] = bin.parse()?;
+    if version != [1, 0, 0, 0] {
+        return Err(Error::InvalidPreambleVersion)
+    }
+
+    let mut func_types = vec![];
+    let mut result = module::Module::default();
+
+    let mut last_id = 0;
+
+    while bin.peek().is_some() {
+        let start = bin.offset();
+        let Byte(id) = bin.parse()?;
+
+        if id != 0x00 {
+            let order = section_order(id).ok_or(Error::InvalidSectionId{ id: Byte(id) })?;
+            if order <= section_order(last_id).unwrap_or(0) {
+                // follow strict section order
+                return Err(Error::UnexpectedSectionId{ id: Byte(id) })
+            } else {
+                last_id = id;
             }
         }
 
-        Ok(result)
+        match id {
+            0x00 => {
+                let mut custom: module::Custom = bin.parse::<Sized<_>>()?.unwrap();
+                custom.after = last_id;
+                result.customs.push(custom);
+            },
+            0x01 => result.types = bin.parse::<Sized<_>>()?.unwrap(),
+            0x02 => result.imports = bin.parse::<Sized<_>>()?.unwrap(),
+            0x03 => func_types = bin.parse::<Sized<_>>()?.unwrap(),
+            0x04 => result.tables = bin.parse::<Sized<_>>()?.unwrap(),
+            0x05 => result.mems = bin.parse::<Sized<_>>()?.unwrap(),
+            0x06 => result.globals = bin.parse::<Sized<_>>()?.unwrap(),
+            0x07 => result.exports = bin.parse::<Sized<_>>()?.unwrap(),
+            0x08 => result.start = Some(bin.parse::<Sized<_>>()?.unwrap()),
+            0x09 => result.elem = bin.parse::<Sized<_>>()?.unwrap(),
+            0x0A => {
+                result.funcs = match mode {
+                    Mode::Eager => bin.parse::<Sized<UnwrappingVec<Sized<_>>>>()?.unwrap().unwrap(),
+                    Mode::Lazy => bin.parse::<Sized<UnwrappingVec<Sized<Lazy<_>>>>>()?.unwrap().unwrap()
+                        .into_iter().map(Lazy::unwrap).collect(),
+                    Mode::WithOffsets(ref mut offsets) => parse_code_with_offsets(bin, offsets)?,
+                };
+                if result.funcs.len() != func_types.len() {
+                    return Err(Error::UnexpectedEndOfFile);
+                }
+                for (func, ty) in result.funcs.iter_mut().zip(func_types.iter()) {
+                    func.ty = *ty;
+                }
+            },
+            0x0B => result.data = bin.parse::<Sized<_>>()?.unwrap(),
+            0x0C => result.data_count = Some(bin.parse::<Sized<_>>()?.unwrap()),
+            0x0D => result.tags = bin.parse::<Sized<_>>()?.unwrap(),
+            _ => return Err(Error::InvalidSectionId{ id: Byte(id) })
+        }
+        check_features(features, id, &result).map_err(|error| error.at(start))?;
+
+        if let Mode::WithOffsets(ref mut offsets) = mode {
+            offsets.sections.push(offsets::Section { id, range: start..bin.offset() });
+        }
+    }
+
+    // the bodies of the functions declared in the function section are missing
+    if !func_types.is_empty() && result.funcs.is_empty() {
+        return Err(Error::UnexpectedEndOfFile);
+    }
+
+    Ok(result)
+}
+
+/// Returns the position of a non-custom section in the order mandated by the binary format,
+/// or `None` if the section id is unknown.
+pub(crate) fn section_order(id: u8) -> Option<u8> {
+    match id {
+        0x01..=0x05 => Some(id),
+        // the tag section goes between the memory and global sections
+        0x0D => Some(0x06),
+        0x06..=0x09 => Some(id + 1),
+        // the data count section goes between the element and code sections
+        0x0C => Some(0x0B),
+        0x0A | 0x0B => Some(id + 2),
+        _ => None,
+    }
+}
+
+impl WasmBinaryEncode for module::Module {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        [Byte(0x00), Byte(0x61), Byte(0x73), Byte(0x6D)].encode(bin)?;
+        [Byte(0x01), Byte(0x00), Byte(0x00), Byte(0x00)].encode(bin)?;
+
+        let func_types: Vec<_> = self.funcs.iter().map(|func| func.ty).collect();
+        let code: Vec<_> = self.funcs.iter().map(Sized).collect();
+
+        encode_customs(0x00, &self.customs, bin)?;
+        if !self.types.is_empty() { encode_section(0x01, &self.types, bin)?; }
+        encode_customs(0x01, &self.customs, bin)?;
+        if !self.imports.is_empty() { encode_section(0x02, &self.imports, bin)?; }
+        encode_customs(0x02, &self.customs, bin)?;
+        if !func_types.is_empty() { encode_section(0x03, &func_types, bin)?; }
+        encode_customs(0x03, &self.customs, bin)?;
+        if !self.tables.is_empty() { encode_section(0x04, &self.tables, bin)?; }
+        encode_customs(0x04, &self.customs, bin)?;
+        if !self.mems.is_empty() { encode_section(0x05, &self.mems, bin)?; }
+        encode_customs(0x05, &self.customs, bin)?;
+        if !self.tags.is_empty() { encode_section(0x0D, &self.tags, bin)?; }
+        encode_customs(0x0D, &self.customs, bin)?;
+        if !self.globals.is_empty() { encode_section(0x06, &self.globals, bin)?; }
+        encode_customs(0x06, &self.customs, bin)?;
+        if !self.exports.is_empty() { encode_section(0x07, &self.exports, bin)?; }
+        encode_customs(0x07, &self.customs, bin)?;
+        if let Some(start) = &self.start { encode_section(0x08, start, bin)?; }
+        encode_customs(0x08, &self.customs, bin)?;
+        if !self.elem.is_empty() { encode_section(0x09, &self.elem, bin)?; }
+        encode_customs(0x09, &self.customs, bin)?;
+        if let Some(count) = &self.data_count { encode_section(0x0C, count, bin)?; }
+        encode_customs(0x0C, &self.customs, bin)?;
+        if !code.is_empty() { encode_section(0x0A, &code, bin)?; }
+        encode_customs(0x0A, &self.customs, bin)?;
+        if !self.data.is_empty() { encode_section(0x0B, &self.data, bin)?; }
+        encode_customs(0x0B, &self.customs, bin)?;
+
+        Ok(())
+    }
+}
+
+impl WasmBinaryParse for module::Custom {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self {
+            name: bin.parse()?,
+            data: bin.collect(),
+            after: 0x00,
+        })
+    }
+}
+
+impl WasmBinaryEncode for module::Custom {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.name.encode(bin)?;
+        bin.extend(self.data.iter().copied());
+        Ok(())
     }
 }
 
@@ -79,11 +436,102 @@ impl WasmBinaryParse for module::Func {
         Ok(Self {
             ty: idx::Type(0),
             locals: locals,
-            body: body,
+            body: module::Body::Expr(body),
         })
     }
 }
 
+impl WasmBinaryParse for Lazy<module::Func> {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let locals = bin.parse::<CompactVec<_>>()?.unwrap();
+        let bytes = bin.collect();
+
+        Ok(Lazy(module::Func {
+            ty: idx::Type(0),
+            locals,
+            body: module::Body::Raw { bytes, expr: Default::default() },
+        }))
+    }
+}
+
+fn parse_code_with_offsets<Binary: WasmBinary>(bin: &mut Positioned<Binary>, offsets: &mut Offsets) -> Result<Vec<module::Func>> {
+    let size: u32 = bin.parse()?;
+    let start = bin.offset();
+    let count: u32 = bin.parse()?;
+
+    // the count comes from the input, so it can not be trusted to reserve memory
+    let mut funcs = vec![];
+    for _ in 0..count {
+        let size: u32 = bin.parse()?;
+        let body_start = bin.offset();
+        let locals = bin.parse::<CompactVec<_>>()?.unwrap();
+        let mut instrs = vec![];
+        let body = parse_expr_with_offsets(bin, &mut instrs)?;
+        if bin.offset() - body_start != size as usize {
+            return Err(Error::FunctionSizeMissmatch);
+        }
+
+        funcs.push(module::Func { ty: idx::Type(0), locals, body: module::Body::Expr(body) });
+        offsets.funcs.push(offsets::Func { range: body_start..bin.offset(), instrs });
+    }
+
+    if bin.offset() - start != size as usize {
+        return Err(Error::SectionSizeMissmatch { id: Byte(0x0A) });
+    }
+    Ok(funcs)
+}
+
+impl WasmBinaryEncode for module::Func {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        CompactVec(self.locals.clone()).encode(bin)?;
+        self.body.encode(bin)
+    }
+}
+
+impl module::Body {
+    /// Returns the expression of the body, decoding it first if the body is raw.
+    pub fn expr(&self) -> Result<&instr::Expr> {
+        match self {
+            module::Body::Expr(expr) => Ok(expr),
+            module::Body::Raw { bytes, expr } => {
+                if let Some(expr) = expr.get() {
+                    return Ok(expr);
+                }
+                let mut bin = SliceBinary::new(bytes);
+                let parsed = bin.parse()?;
+                if bin.remaining() != 0 {
+                    return Err(Error::FunctionSizeMissmatch);
+                }
+                Ok(expr.get_or_init(|| parsed))
+            },
+        }
+    }
+}
+
+impl WasmBinaryEncode for module::Body {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            module::Body::Expr(expr) => expr.encode(bin),
+            module::Body::Raw { bytes, .. } => {
+                bin.extend(bytes.iter().copied());
+                Ok(())
+            },
+        }
+    }
+}
+
+impl PartialEq for module::Body {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (module::Body::Raw { bytes: a, .. }, module::Body::Raw { bytes: b, .. }) => a == b,
+            _ => match (self.expr(), other.expr()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+}
+
 impl WasmBinaryParse for module::Table {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
@@ -92,6 +540,12 @@ impl WasmBinaryParse for module::Table {
     }
 }
 
+impl WasmBinaryEncode for module::Table {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.ty.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for module::Mem {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
@@ -100,35 +554,149 @@ impl WasmBinaryParse for module::Mem {
     }
 }
 
-impl WasmBinaryParse for module::Global {
+impl WasmBinaryEncode for module::Mem {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.ty.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for module::Tag {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
             ty: bin.parse()?,
-            init: bin.parse()?,
         })
     }
 }
 
-impl WasmBinaryParse for module::Elem {
+impl WasmBinaryEncode for module::Tag {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.ty.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for module::Global {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
-            table: bin.parse()?,
-            offset: bin.parse()?,
+            ty: bin.parse()?,
             init: bin.parse()?,
         })
     }
 }
 
+impl WasmBinaryEncode for module::Global {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.ty.encode(bin)?;
+        self.init.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for module::Elem {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        // bit 0 marks passive or declarative segments, bit 1 marks an explicit table index
+        // (or a declarative segment, if bit 0 is set) and bit 2 marks expression initializers
+        let flags: u32 = bin.parse()?;
+        if flags > 0x07 {
+            return Err(Error::InvalidSegmentFlags{ flags, ty: "module::Elem" });
+        }
+        let mode = match flags & 0x03 {
+            0x00 => module::ElemMode::Active { table: idx::Table(0), offset: bin.parse()? },
+            0x01 => module::ElemMode::Passive,
+            0x02 => module::ElemMode::Active { table: bin.parse()?, offset: bin.parse()? },
+            _ => module::ElemMode::Declarative,
+        };
+        let ty = match (flags & 0x03, flags & 0x04) {
+            (0x00, _) => ty::Ref::FUNC,
+            (_, 0x00) => {
+                let Byte(kind) = bin.parse()?;
+                match kind {
+                    0x00 => ty::Ref::FUNC,
+                    id => return Err(Error::InvalidVariantId{ id, ty: "module::Elem" }),
+                }
+            },
+            _ => bin.parse()?,
+        };
+        let init = match flags & 0x04 {
+            0x00 => module::ElemInit::Funcs(bin.parse()?),
+            _ => module::ElemInit::Exprs(bin.parse()?),
+        };
+        Ok(Self { ty, mode, init })
+    }
+}
+
+impl WasmBinaryEncode for module::Elem {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        let (flags, explicit_ty): (u32, bool) = match &self.mode {
+            module::ElemMode::Active { table: idx::Table(0), .. } if self.ty == ty::Ref::FUNC => (0x00, false),
+            module::ElemMode::Active { .. } => (0x02, true),
+            module::ElemMode::Passive => (0x01, true),
+            module::ElemMode::Declarative => (0x03, true),
+        };
+        let flags = match self.init {
+            module::ElemInit::Funcs(_) => flags,
+            module::ElemInit::Exprs(_) => flags | 0x04,
+        };
+
+        flags.encode(bin)?;
+        if let module::ElemMode::Active { table, offset } = &self.mode {
+            if flags & 0x02 != 0 { table.encode(bin)?; }
+            offset.encode(bin)?;
+        }
+        match &self.init {
+            // function indices can only initialize segments of `funcref`
+            module::ElemInit::Funcs(_) if self.ty != ty::Ref::FUNC => {
+                Err(Error::UnencodableValue{ ty: "module::Elem" })
+            },
+            module::ElemInit::Funcs(funcs) => {
+                if explicit_ty { Byte(0x00).encode(bin)?; }
+                funcs.encode(bin)
+            },
+            module::ElemInit::Exprs(exprs) => {
+                if explicit_ty { self.ty.encode(bin)?; }
+                exprs.encode(bin)
+            },
+        }
+    }
+}
+
 impl WasmBinaryParse for module::Data {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let flags: u32 = bin.parse()?;
         Ok(Self {
-            mem: bin.parse()?,
-            offset: bin.parse()?,
+            mode: parse_data_mode(flags, bin)?,
             init: bin.parse::<UnwrappingVec<Byte>>()?.unwrap(),
         })
     }
 }
 
+fn parse_data_mode<Binary: WasmBinary>(flags: u32, bin: &mut Binary) -> Result<module::DataMode> {
+    match flags {
+        0x00 => Ok(module::DataMode::Active { mem: idx::Mem(0), offset: bin.parse()? }),
+        0x01 => Ok(module::DataMode::Passive),
+        0x02 => Ok(module::DataMode::Active { mem: bin.parse()?, offset: bin.parse()? }),
+        _ => Err(Error::InvalidSegmentFlags{ flags, ty: "module::Data" }),
+    }
+}
+
+impl WasmBinaryEncode for module::Data {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match &self.mode {
+            module::DataMode::Active { mem: idx::Mem(0), offset } => {
+                0x00u32.encode(bin)?;
+                offset.encode(bin)?;
+            },
+            module::DataMode::Passive => 0x01u32.encode(bin)?,
+            module::DataMode::Active { mem, offset } => {
+                0x02u32.encode(bin)?;
+                mem.encode(bin)?;
+                offset.encode(bin)?;
+            },
+        }
+        (self.init.len() as u32).encode(bin)?;
+        bin.extend(self.init.iter().copied());
+        Ok(())
+    }
+}
+
 impl WasmBinaryParse for module::Start {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
@@ -137,6 +705,12 @@ impl WasmBinaryParse for module::Start {
     }
 }
 
+impl WasmBinaryEncode for module::Start {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.func.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for module::desc::Import {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let Byte(byte) = bin.parse()?;
@@ -145,11 +719,24 @@ impl WasmBinaryParse for module::desc::Import {
             0x01 => Ok(Self::Table(bin.parse()?)),
             0x02 => Ok(Self::Mem(bin.parse()?)),
             0x03 => Ok(Self::Global(bin.parse()?)),
+            0x04 => Ok(Self::Tag(bin.parse()?)),
             id => Err(Error::InvalidVariantId {id, ty: "module::desc::Import"}),
         }
     }
 }
 
+impl WasmBinaryEncode for module::desc::Import {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::Func(ty) => { Byte(0x00).encode(bin)?; ty.encode(bin) },
+            Self::Table(ty) => { Byte(0x01).encode(bin)?; ty.encode(bin) },
+            Self::Mem(ty) => { Byte(0x02).encode(bin)?; ty.encode(bin) },
+            Self::Global(ty) => { Byte(0x03).encode(bin)?; ty.encode(bin) },
+            Self::Tag(ty) => { Byte(0x04).encode(bin)?; ty.encode(bin) },
+        }
+    }
+}
+
 impl WasmBinaryParse for module::desc::Export {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let Byte(byte) = bin.parse()?;
@@ -158,11 +745,24 @@ impl WasmBinaryParse for module::desc::Export {
             0x01 => Ok(Self::Table(bin.parse()?)),
             0x02 => Ok(Self::Mem(bin.parse()?)),
             0x03 => Ok(Self::Global(bin.parse()?)),
+            0x04 => Ok(Self::Tag(bin.parse()?)),
             id => Err(Error::InvalidVariantId {id, ty: "module::desc::Export"}),
         }
     }
 }
 
+impl WasmBinaryEncode for module::desc::Export {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::Func(idx) => { Byte(0x00).encode(bin)?; idx.encode(bin) },
+            Self::Table(idx) => { Byte(0x01).encode(bin)?; idx.encode(bin) },
+            Self::Mem(idx) => { Byte(0x02).encode(bin)?; idx.encode(bin) },
+            Self::Global(idx) => { Byte(0x03).encode(bin)?; idx.encode(bin) },
+            Self::Tag(idx) => { Byte(0x04).encode(bin)?; idx.encode(bin) },
+        }
+    }
+}
+
 impl WasmBinaryParse for module::Import {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
@@ -173,6 +773,14 @@ impl WasmBinaryParse for module::Import {
     }
 }
 
+impl WasmBinaryEncode for module::Import {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.module.encode(bin)?;
+        self.name.encode(bin)?;
+        self.desc.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for module::Export {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
@@ -182,10 +790,34 @@ impl WasmBinaryParse for module::Export {
     }
 }
 
+impl WasmBinaryEncode for module::Export {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.name.encode(bin)?;
+        self.desc.encode(bin)
+    }
+}
+
+fn encode_section<T, Binary>(id: u8, content: &T, bin: &mut Binary) -> Result<()>
+where T: WasmBinaryEncode, Binary: WasmBinarySink {
+    Byte(id).encode(bin)?;
+    Sized(content).encode(bin)
+}
+
+fn encode_customs<Binary>(after: u8, customs: &[module::Custom], bin: &mut Binary) -> Result<()>
+where Binary: WasmBinarySink {
+    for custom in customs.iter().filter(|custom| custom.after == after) {
+        encode_section(0x00, custom, bin)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use crate::structure::{module, ty, idx, instr};
-    use crate::binary::WasmBinary;
+    use crate::binary::{WasmBinary, WasmBinaryEncode, SliceBinary, Byte, Result, Error};
+    use crate::binary::offsets::WithOffsets;
+    use crate::binary::module::Lazy;
+    use crate::validation::{Features, ValidationEntry};
 
     #[test]
     fn can_deserialize_wasm_file() {
@@ -200,29 +832,31 @@ mod test {
 
         assert_eq!(result, module::Module {
             types: vec![
-                ty::Func { params: vec![], results: vec![ty::Val::I32] },
+                ty::Func { params: vec![], results: vec![ty::Val::I32] }.into(),
             ],
             funcs: vec![
                 module::Func {
                     ty: idx::Type(0),
                     locals: vec![],
-                    body: instr::Expr(vec![
+                    body: module::Body::Expr(instr::Expr(vec![
                         instr::Instr::ConstI32(42)
-                    ])
+                    ]))
                 },
             ],
             tables: vec![
                 module::Table {
                     ty: ty::Table {
                         lim: ty::Limits { min: 0, max: None },
-                        elem: ty::Elem::FuncRef
+                        elem: ty::Ref::FUNC
                     }
                 },
             ],
             mems: vec![],
+            tags: vec![],
             globals: vec![],
             elem: vec![],
             data: vec![],
+            data_count: None,
             start: None,
             imports: vec![],
             exports: vec![
@@ -230,7 +864,468 @@ mod test {
                     name: "test".into(),
                     desc: module::desc::Export::Func( idx::Func(0) )
                 },
-            ]
+            ],
+            customs: vec![],
         });
     }
-}
+
+    #[test]
+    fn can_reencode_wasm_file() {
+        let mut iter = include_bytes!("../../../test.wasm").iter().copied();
+        let module: module::Module = iter.parse().unwrap();
+
+        let mut bin = vec![];
+        module.encode(&mut bin).unwrap();
+        let result: module::Module = bin.iter().copied().parse().unwrap();
+
+        assert_eq!(result, module);
+    }
+
+    #[test]
+    fn can_encode_and_parse_module() {
+        let module = module::Module {
+            types: vec![
+                ty::Func { params: vec![ty::Val::I32], results: vec![ty::Val::I32] }.into(),
+                ty::Func { params: vec![], results: vec![] }.into(),
+            ],
+            funcs: vec![
+                module::Func {
+                    ty: idx::Type(0),
+                    locals: vec![ty::Val::I64, ty::Val::I64, ty::Val::F32],
+                    body: module::Body::Expr(instr::Expr(vec![
+                        instr::Instr::LocalGet(idx::Local(0)),
+                        instr::Instr::If(ty::RetVal::Val(ty::Val::I32),
+                            instr::Expr(vec![instr::Instr::ConstI32(-1)]),
+                            instr::Expr(vec![instr::Instr::I32Load(instr::MemArg { align: 2, offset: 1024, mem: idx::Mem(0) })]),
+                        ),
+                        instr::Instr::Block(ty::RetVal::Empty, instr::Expr(vec![
+                            instr::Instr::BrTable(vec![idx::Label(0)], idx::Label(0)),
+                        ])),
+                    ]))
+                },
+                module::Func {
+                    ty: idx::Type(1),
+                    locals: vec![],
+                    body: module::Body::Expr(instr::Expr(vec![])),
+                },
+            ],
+            tables: vec![],
+            mems: vec![
+                module::Mem { ty: ty::Mem { lim: ty::Limits { min: 1, max: Some(2) }, shared: false, addr: ty::Addr::I32 } },
+            ],
+            tags: vec![],
+            globals: vec![
+                module::Global {
+                    ty: ty::Global { mt: ty::Mut::Var, val: ty::Val::F64 },
+                    init: instr::ConstExpr(vec![instr::Instr::ConstF64(1.5)]),
+                },
+            ],
+            elem: vec![
+                module::Elem {
+                    ty: ty::Ref::FUNC,
+                    mode: module::ElemMode::Active {
+                        table: idx::Table(0),
+                        offset: instr::ConstExpr(vec![instr::Instr::ConstI32(0)]),
+                    },
+                    init: module::ElemInit::Funcs(vec![idx::Func(1), idx::Func(2)]),
+                },
+            ],
+            data: vec![
+                module::Data {
+                    mode: module::DataMode::Active {
+                        mem: idx::Mem(0),
+                        offset: instr::ConstExpr(vec![instr::Instr::ConstI32(8)]),
+                    },
+                    init: vec![0x00, 0x7F, 0x80, 0xFF],
+                },
+            ],
+            data_count: None,
+            start: Some(module::Start { func: idx::Func(2) }),
+            imports: vec![
+                module::Import {
+                    module: "env".into(),
+                    name: "table".into(),
+                    desc: module::desc::Import::Table(ty::Table {
+                        lim: ty::Limits { min: 2, max: None },
+                        elem: ty::Ref::FUNC,
+                    }),
+                },
+            ],
+            exports: vec![
+                module::Export {
+                    name: "f".into(),
+                    desc: module::desc::Export::Func( idx::Func(1) )
+                },
+            ],
+            customs: vec![
+                module::Custom { name: "first".into(), data: vec![0x01, 0x02], after: 0x00 },
+                module::Custom { name: "producers".into(), data: vec![], after: 0x07 },
+                module::Custom { name: "name".into(), data: vec![0x00, 0x01, 0x00], after: 0x0B },
+            ],
+        };
+
+        let mut bin = vec![];
+        module.encode(&mut bin).unwrap();
+        let result: module::Module = bin.iter().copied().parse().unwrap();
+
+        assert_eq!(result, module);
+        assert_eq!(SliceBinary::new(&bin).parse_slice::<module::Module>(), Ok(module));
+    }
+
+    #[test]
+    fn can_deserialize_custom_sections() {
+        let mut iter = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x03, 0x01, 0x61, 0x2A, 0x01, 0x01, 0x00,
+            0x00, 0x04, 0x01, 0x62, 0x2B, 0x2C,
+        ].iter().copied();
+        let result: module::Module = iter.parse().unwrap();
+
+        assert_eq!(result.customs, vec![
+            module::Custom { name: "a".into(), data: vec![0x2A], after: 0x00 },
+            module::Custom { name: "b".into(), data: vec![0x2B, 0x2C], after: 0x01 },
+        ]);
+    }
+
+    #[test]
+    fn can_parse_data_count_and_passive_data() {
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            0x0C, 0x01, 0x02,
+            0x0B, 0x0C, 0x02,
+                0x01, 0x02, 0x2A, 0x2B,
+                0x02, 0x01, 0x41, 0x08, 0x0B, 0x01, 0x2C,
+        ];
+        let result: module::Module = bytes.iter().copied().parse().unwrap();
+
+        assert_eq!(result.data_count, Some(2));
+        assert_eq!(result.data, vec![
+            module::Data { mode: module::DataMode::Passive, init: vec![0x2A, 0x2B] },
+            module::Data {
+                mode: module::DataMode::Active {
+                    mem: idx::Mem(1),
+                    offset: instr::ConstExpr(vec![instr::Instr::ConstI32(8)]),
+                },
+                init: vec![0x2C],
+            },
+        ]);
+
+        let mut bin = vec![];
+        result.encode(&mut bin).unwrap();
+        assert_eq!(bin, bytes);
+
+        let result: Result<module::Data> = [0x80, 0x02].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidSegmentFlags { flags: 0x100, ty: "module::Data" }));
+    }
+
+    #[test]
+    fn can_parse_all_element_segment_encodings() {
+        use instr::{ConstExpr, Instr};
+        let offset = || ConstExpr(vec![Instr::ConstI32(4)]);
+        let funcs = || module::ElemInit::Funcs(vec![idx::Func(1)]);
+        let exprs = || module::ElemInit::Exprs(vec![ConstExpr(vec![Instr::RefNull(ty::Heap::Func)])]);
+        let externs = || module::ElemInit::Exprs(vec![ConstExpr(vec![Instr::RefNull(ty::Heap::Extern)])]);
+        let active = |table| module::ElemMode::Active { table: idx::Table(table), offset: offset() };
+
+        let cases: Vec<(&[u8], module::Elem)> = vec![
+            (&[0x00, 0x41, 0x04, 0x0B, 0x01, 0x01],
+                module::Elem { ty: ty::Ref::FUNC, mode: active(0), init: funcs() }),
+            (&[0x01, 0x00, 0x01, 0x01],
+                module::Elem { ty: ty::Ref::FUNC, mode: module::ElemMode::Passive, init: funcs() }),
+            (&[0x02, 0x01, 0x41, 0x04, 0x0B, 0x00, 0x01, 0x01],
+                module::Elem { ty: ty::Ref::FUNC, mode: active(1), init: funcs() }),
+            (&[0x03, 0x00, 0x01, 0x01],
+                module::Elem { ty: ty::Ref::FUNC, mode: module::ElemMode::Declarative, init: funcs() }),
+            (&[0x04, 0x41, 0x04, 0x0B, 0x01, 0xD0, 0x70, 0x0B],
+                module::Elem { ty: ty::Ref::FUNC, mode: active(0), init: exprs() }),
+            (&[0x05, 0x6F, 0x01, 0xD0, 0x6F, 0x0B],
+                module::Elem { ty: ty::Ref::EXTERN, mode: module::ElemMode::Passive, init: externs() }),
+            (&[0x06, 0x01, 0x41, 0x04, 0x0B, 0x70, 0x01, 0xD0, 0x70, 0x0B],
+                module::Elem { ty: ty::Ref::FUNC, mode: active(1), init: exprs() }),
+            (&[0x07, 0x70, 0x01, 0xD0, 0x70, 0x0B],
+                module::Elem { ty: ty::Ref::FUNC, mode: module::ElemMode::Declarative, init: exprs() }),
+        ];
+        for (bytes, elem) in cases {
+            let result: module::Elem = bytes.iter().copied().parse().unwrap();
+            assert_eq!(result, elem);
+
+            let mut bin = vec![];
+            elem.encode(&mut bin).unwrap();
+            assert_eq!(bin, bytes);
+        }
+
+        let result: Result<module::Elem> = [0x08].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidSegmentFlags { flags: 0x08, ty: "module::Elem" }));
+        let result: Result<module::Elem> = [0x80, 0x02].iter().copied().parse();
+        assert_eq!(result, Err(Error::InvalidSegmentFlags { flags: 0x100, ty: "module::Elem" }));
+    }
+
+    #[test]
+    fn can_parse_shared_memories() {
+        let bytes = [0x03, 0x01, 0x02];
+        let result: ty::Mem = bytes.iter().copied().parse().unwrap();
+        assert_eq!(result, ty::Mem { lim: ty::Limits { min: 1, max: Some(2) }, shared: true, addr: ty::Addr::I32 });
+
+        let mut bin = vec![];
+        result.encode(&mut bin).unwrap();
+        assert_eq!(bin, bytes);
+    }
+
+    #[test]
+    fn can_parse_64_bit_memories() {
+        let bytes = [0x05, 0x01, 0x80, 0x80, 0x80, 0x80, 0x10];
+        let result: ty::Mem = bytes.iter().copied().parse().unwrap();
+        assert_eq!(result, ty::Mem { lim: ty::Limits { min: 1, max: Some(1 << 32) }, shared: false, addr: ty::Addr::I64 });
+
+        let mut bin = vec![];
+        result.encode(&mut bin).unwrap();
+        assert_eq!(bin, bytes);
+
+        // the limits of 32-bit memories are 32-bit integers
+        let result: Result<ty::Mem> = [0x01, 0x01, 0x80, 0x80, 0x80, 0x80, 0x10].iter().copied().parse();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn data_count_section_precedes_code_section() {
+        let mut iter = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            0x0A, 0x01, 0x00,
+            0x0C, 0x01, 0x00,
+        ].iter().copied();
+        let result: Result<module::Module> = iter.parse();
+        assert_eq!(result, Err(Error::UnexpectedSectionId { id: Byte(0x0C) }.at(11)));
+    }
+
+    #[test]
+    fn reports_offset_of_parse_errors() {
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x0E, 0x00,
+        ];
+        let expected = Err(Error::AtOffset {
+            offset: 11,
+            error: Box::new(Error::InvalidSectionId { id: Byte(0x0E) }),
+        });
+        assert_eq!(bytes.iter().copied().parse::<module::Module>(), expected);
+        assert_eq!(SliceBinary::new(&bytes).parse_slice::<module::Module>(), expected);
+
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05,
+        ];
+        let result: Result<module::Module> = bytes.iter().copied().parse();
+        assert_eq!(result.unwrap_err().offset(), Some(10));
+        let result: Result<module::Module> = SliceBinary::new(&bytes).parse_slice();
+        assert_eq!(result.unwrap_err().offset(), Some(10));
+
+        // an import whose name is cut short by the end of its section
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x02, 0x03, 0x01, 0x01, 0x61, 0x01, 0x62,
+        ];
+        let result: Result<module::Module> = SliceBinary::new(&bytes).parse_slice();
+        assert_eq!(result.unwrap_err().offset(), Some(13));
+    }
+
+    #[test]
+    fn rejects_disabled_proposals_while_parsing() {
+        let header = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        let func = [0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00];
+        let module = |sections: &[&[u8]]| -> Vec<u8> {
+            header.iter().chain(sections.iter().flat_map(|section| section.iter())).copied().collect()
+        };
+        let parse = |bytes: &[u8], features: Features| -> Result<module::Module> {
+            let result = bytes.iter().copied().parse_with(features);
+            assert_eq!(SliceBinary::new(bytes).parse_slice_with(features), result);
+            result
+        };
+        let disabled = |feature, offset| Err(Error::FeatureDisabled { feature }.at(offset));
+
+        // a function type returning a `v128`
+        let bytes = module(&[&[0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7B]]);
+        assert_eq!(parse(&bytes, Features::MVP), disabled("simd", 8));
+        assert!(parse(&bytes, Features::WASM2).is_ok());
+
+        // a function dropping a `v128.const`
+        let mut code = vec![0x0A, 0x17, 0x01, 0x15, 0x00, 0xFD, 0x0C];
+        code.extend_from_slice(&[0x00; 16]);
+        code.extend_from_slice(&[0x1A, 0x0B]);
+        let bytes = module(&[&func, &code]);
+        assert_eq!(parse(&bytes, Features::MVP), disabled("simd", 18));
+        assert!(parse(&bytes, Features::WASM2).is_ok());
+
+        // an empty struct type
+        let bytes = module(&[&[0x01, 0x03, 0x01, 0x5F, 0x00]]);
+        assert_eq!(parse(&bytes, Features::MVP), disabled("gc", 8));
+        assert_eq!(parse(&bytes, Features::WASM2), disabled("gc", 8));
+        assert!(parse(&bytes, Features::ALL).is_ok());
+
+        // a function dropping a `ref.i31`
+        let bytes = module(&[&func, &[0x0A, 0x09, 0x01, 0x07, 0x00, 0x41, 0x00, 0xFB, 0x1C, 0x1A, 0x0B]]);
+        assert_eq!(parse(&bytes, Features::MVP), disabled("gc", 18));
+        assert!(parse(&bytes, Features::ALL).is_ok());
+
+        // lazily parsed bodies are left to the validator
+        let Lazy(result) = bytes.iter().copied().parse_with(Features::MVP).unwrap();
+        assert!(result.validate_with(Features::MVP).is_err());
+    }
+
+    #[test]
+    fn can_parse_module_with_offsets() {
+        let bytes = include_bytes!("../../../test.wasm");
+        let module: module::Module = bytes.iter().copied().parse().unwrap();
+        let WithOffsets(result, offsets) = bytes.iter().copied().parse().unwrap();
+        assert_eq!(result, module);
+
+        let mut end = 8;
+        for section in offsets.sections.iter() {
+            assert_eq!(section.range.start, end);
+            end = section.range.end;
+        }
+        assert_eq!(end, bytes.len());
+
+        assert_eq!(offsets.funcs.len(), module.funcs.len());
+        for func in offsets.funcs.iter() {
+            assert_eq!(func.instrs.last().unwrap().end, func.range.end);
+            assert_eq!(bytes[func.range.end - 1], 0x0B);
+        }
+    }
+
+    #[test]
+    fn rejects_code_section_with_too_many_functions() {
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x0A, 0x05, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F,
+        ];
+        let result: Result<WithOffsets<module::Module>> = bytes.iter().copied().parse();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn can_parse_module_lazily() {
+        let bytes = include_bytes!("../../../test.wasm");
+        let module: module::Module = bytes.iter().copied().parse().unwrap();
+        let Lazy(result): Lazy<module::Module> = bytes.iter().copied().parse().unwrap();
+
+        for func in result.funcs.iter() {
+            match &func.body {
+                module::Body::Raw { expr, .. } => assert!(expr.get().is_none()),
+                _ => panic!("function body was parsed eagerly"),
+            }
+        }
+        assert_eq!(result, module);
+
+        let mut expected = vec![];
+        module.encode(&mut expected).unwrap();
+        let mut bin = vec![];
+        result.encode(&mut bin).unwrap();
+        assert_eq!(bin, expected);
+    }
+
+    #[test]
+    fn fails_to_decode_malformed_lazy_body() {
+        let body = module::Body::Raw { bytes: vec![0x01, 0x0B, 0x01], expr: Default::default() };
+        assert_eq!(body.expr(), Err(Error::FunctionSizeMissmatch));
+
+        let body = module::Body::Raw { bytes: vec![0x01, 0xFF, 0x0B], expr: Default::default() };
+        assert_eq!(body.expr(), Err(Error::InvalidVariantId { id: 0xFF, ty: "instr::Instr" }));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn can_parse_module_in_parallel() {
+        use crate::binary::module::Parallel;
+
+        let bytes = include_bytes!("../../../test.wasm");
+        let module: module::Module = bytes.iter().copied().parse().unwrap();
+        let Parallel(result): Parallel<module::Module> = bytes.iter().copied().parse().unwrap();
+
+        for func in result.funcs.iter() {
+            match &func.body {
+                module::Body::Raw { expr, .. } => assert!(expr.get().is_some()),
+                _ => panic!("function body was not parsed lazily"),
+            }
+        }
+        assert_eq!(result, module);
+    }
+
+    #[test]
+    fn can_parse_tags() {
+        let module = module::Module {
+            types: vec![ty::Func { params: vec![ty::Val::I32], results: vec![] }.into()],
+            imports: vec![module::Import {
+                module: "env".into(),
+                name: "error".into(),
+                desc: module::desc::Import::Tag(ty::Tag { ty: idx::Type(0) }),
+            }],
+            tags: vec![module::Tag { ty: ty::Tag { ty: idx::Type(0) } }],
+            exports: vec![module::Export { name: "error".into(), desc: module::desc::Export::Tag(idx::Tag(1)) }],
+            .. Default::default()
+        };
+        let mut bin = vec![];
+        module.encode(&mut bin).unwrap();
+        let tags = SliceBinary::new(&bin).sections().unwrap()
+            .map(Result::unwrap)
+            .find(|section| section.id == 0x0D)
+            .unwrap();
+        assert_eq!(tags.content.collect::<Vec<u8>>(), vec![0x01, 0x00, 0x00]);
+        let result: module::Module = bin.iter().copied().parse().unwrap();
+        assert_eq!(result, module);
+
+        // the tag section goes between the memory and global sections
+        let mut iter = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            0x06, 0x01, 0x00,
+            0x0D, 0x01, 0x00,
+        ].iter().copied();
+        let result: Result<module::Module> = iter.parse();
+        assert_eq!(result, Err(Error::UnexpectedSectionId { id: Byte(0x0D) }.at(11)));
+    }
+
+    #[test]
+    fn can_parse_gc_types() {
+        let field = |storage, mt| ty::Field { storage, mt };
+        let node = ty::Ref { nullable: true, heap: ty::Heap::Type(idx::Type(1)) };
+        let module = module::Module {
+            types: vec![
+                ty::Rec { types: vec![
+                    ty::Sub {
+                        is_final: false,
+                        supers: vec![],
+                        composite: ty::Composite::Struct(ty::Struct { fields: vec![field(ty::Storage::I8, ty::Mut::Var)] }),
+                    },
+                    ty::Sub {
+                        is_final: true,
+                        supers: vec![idx::Type(0)],
+                        composite: ty::Composite::Struct(ty::Struct { fields: vec![
+                            field(ty::Storage::I8, ty::Mut::Var),
+                            field(ty::Storage::Val(node.into()), ty::Mut::Const),
+                        ] }),
+                    },
+                ] },
+                ty::Func { params: vec![], results: vec![] }.into(),
+                ty::Rec { types: vec![ty::Sub {
+                    is_final: true,
+                    supers: vec![],
+                    composite: ty::Composite::Array(ty::Array { field: field(ty::Storage::Val(ty::Val::I64), ty::Mut::Var) }),
+                }] },
+            ],
+            .. Default::default()
+        };
+        let mut bin = vec![];
+        module.encode(&mut bin).unwrap();
+        assert_eq!(&bin[8..], &[
+            0x01, 0x19, 0x03,
+            0x4E, 0x02,
+                0x50, 0x00, 0x5F, 0x01, 0x78, 0x01,
+                0x4F, 0x01, 0x00, 0x5F, 0x02, 0x78, 0x01, 0x63, 0x01, 0x00,
+            0x60, 0x00, 0x00,
+            0x5E, 0x7E, 0x01,
+        ]);
+        let result: module::Module = bin.iter().copied().parse().unwrap();
+        assert_eq!(result, module);
+
+        // nullable references to abstract heap types have a short form
+        let result: ty::Val = [0x63, 0x6E].iter().copied().parse().unwrap();
+        assert_eq!(result, ty::Val::Ref(ty::Ref { nullable: true, heap: ty::Heap::Any }));
+        let mut bin = vec![];
+        result.encode(&mut bin).unwrap();
+        assert_eq!(bin, &[0x6E]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,221 @@
+//! This module defines the parsing and encoding of the "name" custom section.
+//! 
+//! The name section holds debug names for the module and for the entities in its index spaces.
+//! Its content is a sequence of subsections, each one starting with a byte identifying the
+//! subsection followed by its `Sized` content.
+//! Unknown subsections are skipped.
+//! 
+//! # Example
+//! 
+//! ```
+//! # use whasm::binary::WasmBinary;
+//! # use whasm::structure::{name, idx};
+//! let mut iter = [
+//!     0x00, 0x04, 0x03, 0x6D, 0x6F, 0x64, 0x01, 0x07, 0x01, 0x02, 0x04, 0x6D, 0x61, 0x69, 0x6E,
+//! ].iter().copied();
+//! let result: name::NameSection = iter.parse().unwrap();
+//! assert_eq!(result.module_name(), Some("mod"));
+//! assert_eq!(result.func_name(&idx::Func(2)), Some("main"));
+//! ```
+
+use crate::binary::{WasmBinary, WasmBinaryParse, WasmBinaryParseProxy, Byte, Result};
+use crate::binary::{WasmBinarySink, WasmBinaryEncode};
+use crate::binary::{Sized, Consume};
+use crate::structure::{module, name};
+
+impl WasmBinaryParse for name::NameSection {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let mut result = name::NameSection::default();
+        let mut bin = bin.peekable();
+
+        while bin.peek().is_some() {
+            let Byte(id) = bin.parse()?;
+            match id {
+                0x00 => result.module = Some(bin.parse::<Sized<_>>()?.unwrap()),
+                0x01 => result.funcs = bin.parse::<Sized<_>>()?.unwrap(),
+                0x02 => result.locals = bin.parse::<Sized<_>>()?.unwrap(),
+                0x03 => result.labels = bin.parse::<Sized<_>>()?.unwrap(),
+                0x04 => result.types = bin.parse::<Sized<_>>()?.unwrap(),
+                0x05 => result.tables = bin.parse::<Sized<_>>()?.unwrap(),
+                0x06 => result.mems = bin.parse::<Sized<_>>()?.unwrap(),
+                0x07 => result.globals = bin.parse::<Sized<_>>()?.unwrap(),
+                0x08 => result.elem = bin.parse::<Sized<_>>()?.unwrap(),
+                0x09 => result.data = bin.parse::<Sized<_>>()?.unwrap(),
+                0x0A => result.fields = bin.parse::<Sized<_>>()?.unwrap(),
+                0x0B => result.tags = bin.parse::<Sized<_>>()?.unwrap(),
+                _ => {
+                    let _: Sized<Consume> = bin.parse()?;
+                },
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl WasmBinaryEncode for name::NameSection {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        if let Some(module) = &self.module { encode_subsection(0x00, module, bin)?; }
+        if !self.funcs.0.is_empty() { encode_subsection(0x01, &self.funcs, bin)?; }
+        if !self.locals.0.is_empty() { encode_subsection(0x02, &self.locals, bin)?; }
+        if !self.labels.0.is_empty() { encode_subsection(0x03, &self.labels, bin)?; }
+        if !self.types.0.is_empty() { encode_subsection(0x04, &self.types, bin)?; }
+        if !self.tables.0.is_empty() { encode_subsection(0x05, &self.tables, bin)?; }
+        if !self.mems.0.is_empty() { encode_subsection(0x06, &self.mems, bin)?; }
+        if !self.globals.0.is_empty() { encode_subsection(0x07, &self.globals, bin)?; }
+        if !self.elem.0.is_empty() { encode_subsection(0x08, &self.elem, bin)?; }
+        if !self.data.0.is_empty() { encode_subsection(0x09, &self.data, bin)?; }
+        if !self.fields.0.is_empty() { encode_subsection(0x0A, &self.fields, bin)?; }
+        if !self.tags.0.is_empty() { encode_subsection(0x0B, &self.tags, bin)?; }
+        Ok(())
+    }
+}
+
+impl WasmBinaryParse for name::NameMap {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self(bin.parse()?))
+    }
+}
+
+impl WasmBinaryEncode for name::NameMap {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for name::NameAssoc {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self {
+            idx: bin.parse()?,
+            name: bin.parse()?,
+        })
+    }
+}
+
+impl WasmBinaryEncode for name::NameAssoc {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.idx.encode(bin)?;
+        self.name.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for name::IndirectNameMap {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self(bin.parse()?))
+    }
+}
+
+impl WasmBinaryEncode for name::IndirectNameMap {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.0.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for name::IndirectNameAssoc {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self {
+            idx: bin.parse()?,
+            names: bin.parse()?,
+        })
+    }
+}
+
+impl WasmBinaryEncode for name::IndirectNameAssoc {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.idx.encode(bin)?;
+        self.names.encode(bin)
+    }
+}
+
+impl module::Module {
+    /// Parses the content of the "name" custom section of the module, if there is one.
+    pub fn name_section(&self) -> Result<Option<name::NameSection>> {
+        match self.customs.iter().find(|custom| custom.name == "name") {
+            Some(custom) => Ok(Some(custom.data.iter().copied().parse()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn encode_subsection<T, Binary>(id: u8, content: &T, bin: &mut Binary) -> Result<()>
+where T: WasmBinaryEncode, Binary: WasmBinarySink {
+    Byte(id).encode(bin)?;
+    Sized(content).encode(bin)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::structure::{module, name, idx};
+    use crate::binary::{WasmBinary, WasmBinaryEncode};
+
+    #[test]
+    fn can_deserialize_name_section() {
+        let mut iter = [
+            0x00, 0x04, 0x03, 0x6D, 0x6F, 0x64, 0x01, 0x07, 0x01, 0x02, 0x04, 0x6D, 0x61, 0x69, 0x6E,
+            0x02, 0x0B, 0x01, 0x02, 0x02, 0x00, 0x01, 0x78, 0x03, 0x03, 0x74, 0x6D, 0x70,
+        ].iter().copied();
+        let result: name::NameSection = iter.parse().unwrap();
+
+        assert_eq!(result.module_name(), Some("mod"));
+        assert_eq!(result.func_name(&idx::Func(2)), Some("main"));
+        assert_eq!(result.func_name(&idx::Func(0)), None);
+        assert_eq!(result.local_name(&idx::Func(2), &idx::Local(0)), Some("x"));
+        assert_eq!(result.local_name(&idx::Func(2), &idx::Local(3)), Some("tmp"));
+        assert_eq!(result.local_name(&idx::Func(2), &idx::Local(1)), None);
+        assert_eq!(result.local_name(&idx::Func(0), &idx::Local(0)), None);
+    }
+
+    #[test]
+    fn can_skip_unknown_subsections() {
+        let mut iter = [
+            0x2A, 0x02, 0xFF, 0xFF, 0x07, 0x05, 0x01, 0x00, 0x02, 0x73, 0x70,
+        ].iter().copied();
+        let result: name::NameSection = iter.parse().unwrap();
+
+        assert_eq!(result.global_name(&idx::Global(0)), Some("sp"));
+    }
+
+    #[test]
+    fn can_look_up_extended_names() {
+        let names = |idx, name: &str| name::NameMap(vec![name::NameAssoc { idx, name: name.into() }]);
+        let result = name::NameSection {
+            labels: name::IndirectNameMap(vec![name::IndirectNameAssoc { idx: 1, names: names(2, "loop") }]),
+            elem: names(0, "table"),
+            data: names(1, "rodata"),
+            fields: name::IndirectNameMap(vec![name::IndirectNameAssoc { idx: 3, names: names(0, "x") }]),
+            tags: names(0, "error"),
+            ..Default::default()
+        };
+
+        assert_eq!(result.label_name(&idx::Func(1), 2), Some("loop"));
+        assert_eq!(result.label_name(&idx::Func(2), 2), None);
+        assert_eq!(result.elem_name(&idx::Elem(0)), Some("table"));
+        assert_eq!(result.data_name(&idx::Data(1)), Some("rodata"));
+        assert_eq!(result.data_name(&idx::Data(0)), None);
+        assert_eq!(result.field_name(&idx::Type(3), &idx::Field(0)), Some("x"));
+        assert_eq!(result.tag_name(&idx::Tag(0)), Some("error"));
+    }
+
+    #[test]
+    fn can_find_name_section_in_module() {
+        let names = name::NameSection {
+            module: Some("test".into()),
+            funcs: name::NameMap(vec![
+                name::NameAssoc { idx: 0, name: "main".into() },
+            ]),
+            ..Default::default()
+        };
+        let mut data = vec![];
+        names.encode(&mut data).unwrap();
+
+        let module = module::Module {
+            customs: vec![
+                module::Custom { name: "producers".into(), data: vec![], after: 0x00 },
+                module::Custom { name: "name".into(), data, after: 0x0B },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(module.name_section().unwrap(), Some(names));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,77 @@
+//! This module defines a side table with the byte offsets of the elements of a WebAssembly
+//! module in its binary encoding.
+//! 
+//! Parsing a `WithOffsets<module::Module>` rather than a `module::Module` records the byte range
+//! of every section, every function body and every instruction in the function bodies.
+//! The offsets are kept in an `Offsets` side table alongside the parsed module.
+//! 
+//! # Example
+//! 
+//! ```
+//! # use whasm::binary::{WasmBinary, offsets::WithOffsets};
+//! # use whasm::structure::module;
+//! let mut iter = [
+//!     0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00,
+//!     0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+//!     0x03, 0x02, 0x01, 0x00,
+//!     0x0A, 0x05, 0x01, 0x03, 0x00, 0x01, 0x0B,
+//! ].iter().copied();
+//! let WithOffsets(module, offsets): WithOffsets<module::Module> = iter.parse().unwrap();
+//! 
+//! assert_eq!(module.funcs.len(), 1);
+//! assert_eq!(offsets.sections.len(), 3);
+//! assert_eq!(offsets.sections[2].id, 0x0A);
+//! assert_eq!(offsets.sections[2].range, 18..25);
+//! assert_eq!(offsets.funcs[0].range, 22..25);
+//! assert_eq!(offsets.funcs[0].instrs, vec![23..24, 24..25]);
+//! ```
+
+use crate::binary::WasmBinaryParseProxy;
+use std::ops::Range;
+
+/// A parsed element together with the byte offsets of its content.
+/// 
+/// `WithOffsets<module::Module>` implements `WasmBinaryParseProxy<Inner=module::Module>`.
+pub struct WithOffsets<T> ( pub T, pub Offsets );
+
+impl<T> WasmBinaryParseProxy for WithOffsets<T>
+where Self: crate::binary::WasmBinaryParse {
+    type Inner = T;
+    fn unwrap(self) -> Self::Inner { self.0 }
+}
+
+/// Byte offsets of the sections and function bodies of a module.
+#[derive(Debug, PartialEq)]
+#[derive(Default)]
+pub struct Offsets {
+    /// The sections in the order they appear in the binary, including custom sections.
+    pub sections: Vec<Section>,
+    /// The function bodies, in the same order as `module::Module::funcs`.
+    pub funcs: Vec<Func>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Section {
+    pub id: u8,
+    /// Range of the whole section, including its id and size.
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Func {
+    /// Range of the function body (locals and expression), excluding its size.
+    pub range: Range<usize>,
+    /// Range of each instruction in the body, including `else` and `end`, in the order they
+    /// appear in the binary.
+    /// An `else` followed by an empty branch is not included.
+    pub instrs: Vec<Range<usize>>,
+}
+
+impl Offsets {
+    /// Returns the byte offset of the given instruction of the given function body.
+    /// The function is indexed among the functions defined in the module, excluding imports.
+    pub fn instr_offset(&self, func: usize, instr: usize) -> Option<usize> {
+        let range = self.funcs.get(func)?.instrs.get(instr)?;
+        Some(range.start)
+    }
+}
\ No newline at end of file
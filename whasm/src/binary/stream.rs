@@ -0,0 +1,424 @@
+//! This module defines a push-based parser for WebAssembly modules.
+//!
+//! The `ModuleParser` is fed chunks of bytes as they become available (e.g., from a network
+//! stream or a pipe) and emits `Event`s as soon as the elements of the module are complete.
+//! This allows to start processing a module before it is fully received, and to stop early by
+//! simply dropping the parser.
+//!
+//! Most sections are emitted as a whole once all their bytes are available.
+//! Imports and function bodies are emitted one by one.
+//!
+//! # Example
+//!
+//! ```
+//! # use whasm::binary::stream::{ModuleParser, Event};
+//! # use whasm::structure::{instr, module};
+//! let mut parser = ModuleParser::new();
+//! assert_eq!(parser.next_event(), Ok(None));
+//!
+//! parser.push(&[0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01]);
+//! assert_eq!(parser.next_event(), Ok(Some(Event::Header)));
+//! assert_eq!(parser.next_event(), Ok(None));
+//!
+//! parser.push(&[0x60, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0x0A, 0x05, 0x01, 0x03, 0x00]);
+//! assert!(matches!(parser.next_event(), Ok(Some(Event::Types(_)))));
+//! assert!(matches!(parser.next_event(), Ok(Some(Event::Funcs(_)))));
+//! assert_eq!(parser.next_event(), Ok(Some(Event::CodeSection { count: 1 })));
+//! assert_eq!(parser.next_event(), Ok(None));
+//!
+//! parser.push(&[0x01, 0x0B]);
+//! parser.finish();
+//! match parser.next_event() {
+//!     Ok(Some(Event::Func { idx: 0, func })) => {
+//!         assert_eq!(func.body.expr().unwrap(), &instr::Expr(vec![instr::Instr::Nop]));
+//!     },
+//!     _ => panic!("expected a function body"),
+//! }
+//! assert_eq!(parser.next_event(), Ok(Some(Event::End)));
+//! ```
+
+use crate::binary::{WasmBinary, WasmBinaryParse, WasmBinaryParseProxy, Byte, Result, Error};
+use crate::binary::{Sized, SliceBinary};
+use crate::binary::module::section_order;
+use crate::structure::{module, idx, ty};
+
+/// An element of a module, emitted by `ModuleParser::next_event`.
+#[derive(Debug, PartialEq)]
+pub enum Event {
+    /// The preamble of the module is valid.
+    Header,
+    Types(Vec<ty::Rec>),
+    /// The start of the import section, followed by `count` `Import` events.
+    ImportSection { count: u32 },
+    Import(module::Import),
+    Funcs(Vec<idx::Type>),
+    Tables(Vec<module::Table>),
+    Mems(Vec<module::Mem>),
+    Tags(Vec<module::Tag>),
+    Globals(Vec<module::Global>),
+    Exports(Vec<module::Export>),
+    Start(module::Start),
+    Elem(Vec<module::Elem>),
+    /// The start of the code section, followed by `count` `Func` events.
+    CodeSection { count: u32 },
+    /// The body of the function with index `idx` among the functions defined in the module.
+    Func { idx: u32, func: module::Func },
+    Data(Vec<module::Data>),
+    /// The number of data segments, announced before the code section.
+    DataCount(u32),
+    Custom(module::Custom),
+    /// The end of the module.
+    End,
+}
+
+enum State {
+    Header,
+    Section,
+    Imports { end: usize, count: Option<u32>, next: u32 },
+    Code { end: usize, count: Option<u32>, next: u32 },
+    Finished,
+}
+
+pub struct ModuleParser {
+    buffer: Vec<u8>,
+    // offset in the module of the first byte in `buffer`
+    base: usize,
+    // offset in the module of the next byte to parse
+    offset: usize,
+    eof: bool,
+    state: State,
+    last_id: u8,
+    func_types: Vec<idx::Type>,
+}
+
+impl Default for ModuleParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModuleParser {
+    pub fn new() -> Self {
+        Self {
+            buffer: vec![],
+            base: 0,
+            offset: 0,
+            eof: false,
+            state: State::Header,
+            last_id: 0,
+            func_types: vec![],
+        }
+    }
+
+    /// Returns the offset in the module of the next byte to parse.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Appends a chunk of bytes to the input.
+    pub fn push(&mut self, bytes: &[u8]) {
+        // drop the bytes already parsed
+        let parsed = self.offset - self.base;
+        if parsed > 0 && parsed >= self.buffer.len() / 2 {
+            self.buffer.drain(..parsed);
+            self.base = self.offset;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Signals that there are no more bytes in the input.
+    pub fn finish(&mut self) {
+        self.eof = true;
+    }
+
+    /// Returns the next event, or `None` if more bytes are needed to complete it.
+    ///
+    /// Once the input is finished, running out of bytes results in
+    /// `Error::UnexpectedEndOfFile`. Errors carry the offset at which they were found.
+    pub fn next_event(&mut self) -> Result<Option<Event>> {
+        loop {
+            match self.state {
+                State::Header => {
+                    let preamble: [Byte; 8] = match self.try_parse(usize::MAX)? {
+                        Some(preamble) => preamble,
+                        None => return Ok(None),
+                    };
+                    if preamble[..4] != ['\0', 'a', 's', 'm'] {
+                        return Err(Error::InvalidPreambleMagic.at(self.base));
+                    }
+                    if preamble[4..] != [1, 0, 0, 0] {
+                        return Err(Error::InvalidPreambleVersion.at(self.base + 4));
+                    }
+                    self.state = State::Section;
+                    return Ok(Some(Event::Header));
+                },
+                State::Section => {
+                    if self.offset == self.available() {
+                        if !self.eof {
+                            return Ok(None);
+                        }
+                        // the bodies of the functions declared in the function section are missing
+                        if !self.func_types.is_empty() && section_order(self.last_id) < section_order(0x0A) {
+                            return Err(Error::UnexpectedEndOfFile.at(self.offset));
+                        }
+                        self.state = State::Finished;
+                        return Ok(Some(Event::End));
+                    }
+                    return self.section();
+                },
+                State::Imports { end, count: None, .. } => {
+                    if let Some(count) = self.try_parse(end)? {
+                        self.state = State::Imports { end, count: Some(count), next: 0 };
+                        return Ok(Some(Event::ImportSection { count }));
+                    }
+                    return Ok(None);
+                },
+                State::Imports { end, count: Some(count), next } => {
+                    if next == count {
+                        self.end_section(0x02, end)?;
+                        continue;
+                    }
+                    if let Some(import) = self.try_parse(end)? {
+                        self.state = State::Imports { end, count: Some(count), next: next + 1 };
+                        return Ok(Some(Event::Import(import)));
+                    }
+                    return Ok(None);
+                },
+                State::Code { end, count: None, .. } => {
+                    if let Some(count) = self.try_parse(end)? {
+                        if count as usize != self.func_types.len() {
+                            return Err(Error::SectionSizeMissmatch { id: Byte(0x0A) }.at(self.offset));
+                        }
+                        self.state = State::Code { end, count: Some(count), next: 0 };
+                        return Ok(Some(Event::CodeSection { count }));
+                    }
+                    return Ok(None);
+                },
+                State::Code { end, count: Some(count), next } => {
+                    if next == count {
+                        self.end_section(0x0A, end)?;
+                        continue;
+                    }
+                    if !self.is_sized_available(end)? {
+                        return Ok(None);
+                    }
+                    let mut func: module::Func = match self.try_parse::<Sized<_>>(end)? {
+                        Some(func) => func.unwrap(),
+                        None => return Ok(None),
+                    };
+                    func.ty = self.func_types[next as usize];
+                    self.state = State::Code { end, count: Some(count), next: next + 1 };
+                    return Ok(Some(Event::Func { idx: next, func }));
+                },
+                State::Finished => return Ok(None),
+            }
+        }
+    }
+
+    fn available(&self) -> usize {
+        self.base + self.buffer.len()
+    }
+
+    fn section(&mut self) -> Result<Option<Event>> {
+        let start = self.offset;
+        let header: SectionHeader = match self.try_parse(usize::MAX)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let id = header.id;
+        let end = self.offset + header.size as usize;
+
+        if id != 0x02 && id != 0x0A && self.available() < end && !self.eof {
+            // wait for the whole section, keeping the header unparsed
+            self.offset = start;
+            return Ok(None);
+        }
+
+        if id != 0x00 {
+            let order = section_order(id).ok_or(Error::InvalidSectionId{ id: Byte(id) }.at(start))?;
+            if order <= section_order(self.last_id).unwrap_or(0) {
+                // follow strict section order
+                return Err(Error::UnexpectedSectionId{ id: Byte(id) }.at(start));
+            }
+            self.last_id = id;
+        }
+
+        match id {
+            0x02 => {
+                self.state = State::Imports { end, count: None, next: 0 };
+                return self.next_event();
+            },
+            0x0A => {
+                self.state = State::Code { end, count: None, next: 0 };
+                return self.next_event();
+            },
+            _ => (),
+        }
+
+        let event = match id {
+            0x00 => {
+                let mut custom: module::Custom = self.parse_section(id, end)?;
+                custom.after = self.last_id;
+                Event::Custom(custom)
+            },
+            0x01 => Event::Types(self.parse_section(id, end)?),
+            0x03 => {
+                let func_types: Vec<idx::Type> = self.parse_section(id, end)?;
+                self.func_types = func_types.clone();
+                Event::Funcs(func_types)
+            },
+            0x04 => Event::Tables(self.parse_section(id, end)?),
+            0x05 => Event::Mems(self.parse_section(id, end)?),
+            0x0D => Event::Tags(self.parse_section(id, end)?),
+            0x06 => Event::Globals(self.parse_section(id, end)?),
+            0x07 => Event::Exports(self.parse_section(id, end)?),
+            0x08 => Event::Start(self.parse_section(id, end)?),
+            0x09 => Event::Elem(self.parse_section(id, end)?),
+            0x0B => Event::Data(self.parse_section(id, end)?),
+            0x0C => Event::DataCount(self.parse_section(id, end)?),
+            _ => return Err(Error::InvalidSectionId{ id: Byte(id) }.at(start)),
+        };
+        Ok(Some(event))
+    }
+
+    fn end_section(&mut self, id: u8, end: usize) -> Result<()> {
+        if self.offset != end {
+            return Err(Error::SectionSizeMissmatch { id: Byte(id) }.at(self.offset));
+        }
+        self.state = State::Section;
+        Ok(())
+    }
+
+    // Parses the whole content of a section, which must be available.
+    fn parse_section<T: WasmBinaryParse>(&mut self, id: u8, end: usize) -> Result<T> {
+        let result = self.try_parse(end)?.ok_or(Error::UnexpectedEndOfFile.at(self.available()))?;
+        self.end_section(id, end)?;
+        Ok(result)
+    }
+
+    // Checks whether all the bytes of a sized element ending before `end` are available.
+    // Malformed elements are reported as available, so that parsing them reports the error.
+    fn is_sized_available(&self, end: usize) -> Result<bool> {
+        let mut bin = SliceBinary::new(&self.buffer[self.offset - self.base..]);
+        match bin.read_u32() {
+            Ok(size) => {
+                let needed = self.offset + bin.offset() + size as usize;
+                Ok(needed <= self.available() || needed > end || self.eof)
+            },
+            Err(Error::UnexpectedEndOfFile) => Ok(self.available() >= end || self.eof),
+            Err(error) => Err(error.at(self.offset)),
+        }
+    }
+
+    // Tries to parse an element from the bytes available up to `end`.
+    // Returns `None` without consuming any byte if the element is not complete yet.
+    fn try_parse<T: WasmBinaryParse>(&mut self, end: usize) -> Result<Option<T>> {
+        let limit = end.min(self.available());
+        let mut bin = SliceBinary::new(&self.buffer[self.offset - self.base..limit - self.base]);
+        match bin.parse() {
+            Ok(result) => {
+                self.offset += bin.offset();
+                Ok(Some(result))
+            },
+            Err(Error::UnexpectedEndOfFile) if limit < end && !self.eof => Ok(None),
+            Err(Error::UnexpectedEndOfFile) => Err(Error::UnexpectedEndOfFile.at(self.offset + bin.offset())),
+            Err(error) => Err(error.at(self.offset + bin.offset().saturating_sub(1))),
+        }
+    }
+}
+
+struct SectionHeader {
+    id: u8,
+    size: u32,
+}
+
+impl WasmBinaryParse for SectionHeader {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let Byte(id) = bin.parse()?;
+        let size = bin.parse()?;
+        Ok(Self { id, size })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::binary::{WasmBinary, Byte, Error};
+    use crate::binary::stream::{ModuleParser, Event};
+    use crate::structure::module;
+
+    fn apply(module: &mut module::Module, event: Event) {
+        match event {
+            Event::Types(types) => module.types = types,
+            Event::Import(import) => module.imports.push(import),
+            Event::Tables(tables) => module.tables = tables,
+            Event::Mems(mems) => module.mems = mems,
+            Event::Tags(tags) => module.tags = tags,
+            Event::Globals(globals) => module.globals = globals,
+            Event::Exports(exports) => module.exports = exports,
+            Event::Start(start) => module.start = Some(start),
+            Event::Elem(elem) => module.elem = elem,
+            Event::Func { func, .. } => module.funcs.push(func),
+            Event::Data(data) => module.data = data,
+            Event::Custom(custom) => module.customs.push(custom),
+            _ => (),
+        }
+    }
+
+    #[test]
+    fn can_parse_module_in_chunks() {
+        let bytes = include_bytes!("../../../test.wasm");
+        let expected: module::Module = bytes.iter().copied().parse().unwrap();
+
+        for chunk_size in 1..16 {
+            let mut parser = ModuleParser::new();
+            let mut result = module::Module::default();
+            let mut events = vec![];
+            for chunk in bytes.chunks(chunk_size) {
+                parser.push(chunk);
+                while let Some(event) = parser.next_event().unwrap() {
+                    events.push(std::mem::discriminant(&event));
+                    apply(&mut result, event);
+                }
+            }
+            parser.finish();
+            assert_eq!(parser.next_event(), Ok(Some(Event::End)));
+            assert_eq!(parser.next_event(), Ok(None));
+            assert_eq!(result, expected);
+            assert_eq!(events[0], std::mem::discriminant(&Event::Header));
+        }
+    }
+
+    #[test]
+    fn reports_errors_with_offset() {
+        let mut parser = ModuleParser::new();
+        parser.push(&[0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x0E, 0x00]);
+        assert_eq!(parser.next_event(), Ok(Some(Event::Header)));
+        assert_eq!(parser.next_event(), Err(Error::AtOffset {
+            offset: 8,
+            error: Box::new(Error::InvalidSectionId { id: Byte(0x0E) }),
+        }));
+
+        let mut parser = ModuleParser::new();
+        parser.push(&[0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01]);
+        assert_eq!(parser.next_event(), Ok(Some(Event::Header)));
+        assert_eq!(parser.next_event(), Ok(None));
+        parser.finish();
+        assert_eq!(parser.next_event().unwrap_err().offset(), Some(11));
+    }
+
+    #[test]
+    fn rejects_functions_without_code_section() {
+        let bytes = [0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00];
+        let mut parser = ModuleParser::new();
+        parser.push(&bytes);
+        parser.finish();
+        assert_eq!(parser.next_event(), Ok(Some(Event::Header)));
+        assert!(matches!(parser.next_event(), Ok(Some(Event::Types(_)))));
+        assert!(matches!(parser.next_event(), Ok(Some(Event::Funcs(_)))));
+        assert_eq!(parser.next_event(), Err(Error::UnexpectedEndOfFile.at(18)));
+
+        let result: Result<module::Module, Error> = bytes.iter().copied().parse();
+        assert_eq!(result, Err(Error::UnexpectedEndOfFile.at(18)));
+    }
+}
\ No newline at end of file
@@ -1,44 +1,158 @@
-//! This module defines the parsing of a WebAssembly types.
+//! This module defines the parsing and encoding of a WebAssembly types.
 
-use crate::binary::{WasmBinaryParse, WasmBinary, Result, Byte, Error};
-use crate::structure::ty;
+use crate::binary::{WasmBinaryParse, WasmBinary, WasmBinaryEncode, WasmBinarySink, Result, Byte, Error};
+use crate::structure::{idx, ty};
+use std::convert::TryFrom;
 
 impl WasmBinaryParse for ty::Limits {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let Byte(byte) = bin.parse()?;
         match byte {
             0x00 => Ok(Self {
-                min: bin.parse()?,
+                min: bin.parse::<u32>()?.into(),
                 max: None,
             }),
             0x01 => Ok(Self {
-                min: bin.parse()?,
-                max: Some(bin.parse()?),
+                min: bin.parse::<u32>()?.into(),
+                max: Some(bin.parse::<u32>()?.into()),
             }),
             id => Err(Error::InvalidVariantId{id, ty: "ty::Limits"})
         }
     }
 }
 
+impl WasmBinaryEncode for ty::Limits {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self.max {
+            None => {
+                Byte(0x00).encode(bin)?;
+                self.min.encode(bin)
+            },
+            Some(max) => {
+                Byte(0x01).encode(bin)?;
+                self.min.encode(bin)?;
+                max.encode(bin)
+            },
+        }
+    }
+}
+
 impl WasmBinaryParse for ty::Val {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let Byte(byte) = bin.parse()?;
-        match byte {
-            0x7F => Ok(Self::I32),
-            0x7E => Ok(Self::I64),
-            0x7D => Ok(Self::F32),
-            0x7C => Ok(Self::F64),
-            id => Err(Error::InvalidVariantId{id, ty: "ty::Val"})
+        parse_val(byte, bin)
+    }
+}
+
+impl WasmBinaryEncode for ty::Val {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::I32 => Byte(0x7F).encode(bin),
+            Self::I64 => Byte(0x7E).encode(bin),
+            Self::F32 => Byte(0x7D).encode(bin),
+            Self::F64 => Byte(0x7C).encode(bin),
+            Self::V128 => Byte(0x7B).encode(bin),
+            Self::Ref(r) => r.encode(bin),
         }
     }
 }
 
-impl WasmBinaryParse for ty::Elem {
+/// Parses a value type whose first byte has already been read.
+fn parse_val<Binary: WasmBinary>(byte: u8, bin: &mut Binary) -> Result<ty::Val> {
+    match byte {
+        0x7F => Ok(ty::Val::I32),
+        0x7E => Ok(ty::Val::I64),
+        0x7D => Ok(ty::Val::F32),
+        0x7C => Ok(ty::Val::F64),
+        0x7B => Ok(ty::Val::V128),
+        _ => Ok(ty::Val::Ref(parse_ref(byte, bin, "ty::Val")?)),
+    }
+}
+
+impl WasmBinaryParse for ty::Ref {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let Byte(byte) = bin.parse()?;
-        match byte {
-            0x70 => Ok(Self::FuncRef),
-            id => Err(Error::InvalidVariantId{id, ty: "ty::Elem"})
+        parse_ref(byte, bin, "ty::Ref")
+    }
+}
+
+impl WasmBinaryEncode for ty::Ref {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        // nullable references to abstract heap types are encoded in their short form
+        match (self.nullable, self.heap) {
+            (true, ty::Heap::Type(_)) => Byte(0x63).encode(bin)?,
+            (true, _) => return self.heap.encode(bin),
+            (false, _) => Byte(0x64).encode(bin)?,
+        }
+        self.heap.encode(bin)
+    }
+}
+
+/// Parses a reference type whose first byte has already been read, which is either `0x64`
+/// (`ref ht`), `0x63` (`ref null ht`) or the short form of a nullable abstract heap type.
+fn parse_ref<Binary: WasmBinary>(byte: u8, bin: &mut Binary, ty: &'static str) -> Result<ty::Ref> {
+    match byte {
+        0x64 => Ok(ty::Ref { nullable: false, heap: bin.parse()? }),
+        0x63 => Ok(ty::Ref { nullable: true, heap: bin.parse()? }),
+        id => match abstract_heap(id) {
+            Some(heap) => Ok(ty::Ref { nullable: true, heap }),
+            None => Err(Error::InvalidVariantId{id, ty}),
+        },
+    }
+}
+
+fn abstract_heap(byte: u8) -> Option<ty::Heap> {
+    match byte {
+        0x74 => Some(ty::Heap::NoExn),
+        0x73 => Some(ty::Heap::NoFunc),
+        0x72 => Some(ty::Heap::NoExtern),
+        0x71 => Some(ty::Heap::None),
+        0x70 => Some(ty::Heap::Func),
+        0x6F => Some(ty::Heap::Extern),
+        0x6E => Some(ty::Heap::Any),
+        0x6D => Some(ty::Heap::Eq),
+        0x6C => Some(ty::Heap::I31),
+        0x6B => Some(ty::Heap::Struct),
+        0x6A => Some(ty::Heap::Array),
+        0x69 => Some(ty::Heap::Exn),
+        _ => None,
+    }
+}
+
+impl WasmBinaryParse for ty::Heap {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let Byte(byte) = bin.parse()?;
+        if let Some(heap) = abstract_heap(byte) {
+            return Ok(heap);
+        }
+        // type indices are encoded as non-negative 33 bit signed integers
+        let idx: i64 = std::iter::once(byte).chain(bin).parse()?;
+        if idx < 0 {
+            return Err(Error::InvalidVariantId{id: byte, ty: "ty::Heap"});
+        }
+        if idx > i64::from(u32::MAX) {
+            return Err(Error::OutOfRangeSignedInteger);
+        }
+        Ok(Self::Type(idx::Type(idx as u32)))
+    }
+}
+
+impl WasmBinaryEncode for ty::Heap {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::NoExn => Byte(0x74).encode(bin),
+            Self::NoFunc => Byte(0x73).encode(bin),
+            Self::NoExtern => Byte(0x72).encode(bin),
+            Self::None => Byte(0x71).encode(bin),
+            Self::Func => Byte(0x70).encode(bin),
+            Self::Extern => Byte(0x6F).encode(bin),
+            Self::Any => Byte(0x6E).encode(bin),
+            Self::Eq => Byte(0x6D).encode(bin),
+            Self::I31 => Byte(0x6C).encode(bin),
+            Self::Struct => Byte(0x6B).encode(bin),
+            Self::Array => Byte(0x6A).encode(bin),
+            Self::Exn => Byte(0x69).encode(bin),
+            Self::Type(idx::Type(idx)) => i64::from(*idx).encode(bin),
         }
     }
 }
@@ -47,12 +161,25 @@ impl WasmBinaryParse for ty::RetVal {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         let Byte(byte) = bin.parse()?;
         match byte {
-            0x40 => Ok(Self(vec![])),
+            0x40 => Ok(Self::Empty),
+            // value types are encoded as single byte negative numbers
+            0x41..=0x7F => Ok(Self::Val(parse_val(byte, bin)?)),
+            // type indices are encoded as non-negative 33 bit signed integers
             _ => {
-                let byte = [byte];
-                let mut iter = byte.iter().copied();
-                Ok(Self(vec![iter.parse()?]))
-            }
+                let idx: i64 = std::iter::once(byte).chain(bin).parse()?;
+                let idx = u32::try_from(idx).map_err(|_| Error::OutOfRangeSignedInteger)?;
+                Ok(Self::Type(idx::Type(idx)))
+            },
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::RetVal {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::Empty => Byte(0x40).encode(bin),
+            Self::Val(val) => val.encode(bin),
+            Self::Type(idx::Type(idx)) => (*idx as i64).encode(bin),
         }
     }
 }
@@ -70,6 +197,173 @@ impl WasmBinaryParse for ty::Func {
     }
 }
 
+impl WasmBinaryEncode for ty::Func {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        Byte(0x60).encode(bin)?;
+        self.params.encode(bin)?;
+        self.results.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for ty::Storage {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x78 => Ok(Self::I8),
+            0x77 => Ok(Self::I16),
+            _ => Ok(Self::Val(parse_val(byte, bin)?)),
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Storage {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::Val(val) => val.encode(bin),
+            Self::I8 => Byte(0x78).encode(bin),
+            Self::I16 => Byte(0x77).encode(bin),
+        }
+    }
+}
+
+impl WasmBinaryParse for ty::Field {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        Ok(Self {
+            storage: bin.parse()?,
+            mt: bin.parse()?,
+        })
+    }
+}
+
+impl WasmBinaryEncode for ty::Field {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.storage.encode(bin)?;
+        self.mt.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for ty::Struct {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x5F => Ok(Self {
+                fields: bin.parse()?,
+            }),
+            id => Err(Error::InvalidVariantId{id, ty: "ty::Struct"})
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Struct {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        Byte(0x5F).encode(bin)?;
+        self.fields.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for ty::Array {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x5E => Ok(Self {
+                field: bin.parse()?,
+            }),
+            id => Err(Error::InvalidVariantId{id, ty: "ty::Array"})
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Array {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        Byte(0x5E).encode(bin)?;
+        self.field.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for ty::Composite {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        let Byte(byte) = bin.parse()?;
+        let mut bin = std::iter::once(byte).chain(bin);
+        match byte {
+            0x60 => Ok(Self::Func(bin.parse()?)),
+            0x5F => Ok(Self::Struct(bin.parse()?)),
+            0x5E => Ok(Self::Array(bin.parse()?)),
+            id => Err(Error::InvalidVariantId{id, ty: "ty::Composite"})
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Composite {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::Func(func) => func.encode(bin),
+            Self::Struct(st) => st.encode(bin),
+            Self::Array(array) => array.encode(bin),
+        }
+    }
+}
+
+impl WasmBinaryParse for ty::Sub {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        // a composite type without a `sub` prefix is final and has no supertypes
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x50 => Ok(Self {
+                is_final: false,
+                supers: bin.parse()?,
+                composite: bin.parse()?,
+            }),
+            0x4F => Ok(Self {
+                is_final: true,
+                supers: bin.parse()?,
+                composite: bin.parse()?,
+            }),
+            _ => Ok(Self {
+                is_final: true,
+                supers: vec![],
+                composite: std::iter::once(byte).chain(bin).parse()?,
+            }),
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Sub {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match (self.is_final, self.supers.is_empty()) {
+            (true, true) => return self.composite.encode(bin),
+            (true, false) => Byte(0x4F).encode(bin)?,
+            (false, _) => Byte(0x50).encode(bin)?,
+        }
+        self.supers.encode(bin)?;
+        self.composite.encode(bin)
+    }
+}
+
+impl WasmBinaryParse for ty::Rec {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        // a type outside of a `rec` group forms a group of its own
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x4E => Ok(Self {
+                types: bin.parse()?,
+            }),
+            _ => Ok(Self {
+                types: vec![std::iter::once(byte).chain(bin).parse()?],
+            }),
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Rec {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        if let [ty] = &self.types[..] {
+            return ty.encode(bin);
+        }
+        Byte(0x4E).encode(bin)?;
+        self.types.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for ty::Table {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
@@ -79,11 +373,74 @@ impl WasmBinaryParse for ty::Table {
     }
 }
 
+impl WasmBinaryEncode for ty::Table {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.elem.encode(bin)?;
+        self.lim.encode(bin)
+    }
+}
+
 impl WasmBinaryParse for ty::Mem {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
-        Ok(Self {
-            lim: bin.parse()?,
-        })
+        // the limits of a memory can additionally be flagged as shared and as 64-bit
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x00..=0x03 => Ok(Self {
+                lim: ty::Limits {
+                    min: bin.parse::<u32>()?.into(),
+                    max: if byte & 0x01 != 0 { Some(bin.parse::<u32>()?.into()) } else { None },
+                },
+                shared: byte & 0x02 != 0,
+                addr: ty::Addr::I32,
+            }),
+            0x04..=0x07 => Ok(Self {
+                lim: ty::Limits {
+                    min: bin.parse()?,
+                    max: if byte & 0x01 != 0 { Some(bin.parse()?) } else { None },
+                },
+                shared: byte & 0x02 != 0,
+                addr: ty::Addr::I64,
+            }),
+            id => Err(Error::InvalidVariantId{id, ty: "ty::Mem"})
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Mem {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        let shared = if self.shared { 0x02 } else { 0x00 };
+        let addr = if self.addr == ty::Addr::I64 { 0x04 } else { 0x00 };
+        match self.lim.max {
+            None => {
+                Byte(shared | addr).encode(bin)?;
+                self.lim.min.encode(bin)
+            },
+            Some(max) => {
+                Byte(shared | addr | 0x01).encode(bin)?;
+                self.lim.min.encode(bin)?;
+                max.encode(bin)
+            },
+        }
+    }
+}
+
+impl WasmBinaryParse for ty::Tag {
+    fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
+        // the attribute byte is reserved, and the only defined value is an exception
+        let Byte(byte) = bin.parse()?;
+        match byte {
+            0x00 => Ok(Self {
+                ty: bin.parse()?,
+            }),
+            id => Err(Error::InvalidVariantId{id, ty: "ty::Tag"})
+        }
+    }
+}
+
+impl WasmBinaryEncode for ty::Tag {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        Byte(0x00).encode(bin)?;
+        self.ty.encode(bin)
     }
 }
 
@@ -98,6 +455,15 @@ impl WasmBinaryParse for ty::Mut {
     }
 }
 
+impl WasmBinaryEncode for ty::Mut {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        match self {
+            Self::Const => Byte(0x00).encode(bin),
+            Self::Var => Byte(0x01).encode(bin),
+        }
+    }
+}
+
 impl WasmBinaryParse for ty::Global {
     fn parse<Binary: WasmBinary>(bin: &mut Binary) -> Result<Self> {
         Ok(Self {
@@ -105,4 +471,11 @@ impl WasmBinaryParse for ty::Global {
             mt: bin.parse()?,
         })
     }
+}
+
+impl WasmBinaryEncode for ty::Global {
+    fn encode<Binary: WasmBinarySink>(&self, bin: &mut Binary) -> Result<()> {
+        self.val.encode(bin)?;
+        self.mt.encode(bin)
+    }
 }
\ No newline at end of file
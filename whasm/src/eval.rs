@@ -0,0 +1,146 @@
+//! This module defines the evaluation of constant expressions, such as the initial values of
+//! globals and the offsets of active element and data segments.
+//! 
+//! # Example
+//! 
+//! ```
+//! # use whasm::eval::Value;
+//! # use whasm::structure::{idx, instr::{ConstExpr, Instr}};
+//! let offset = ConstExpr(vec![Instr::GlobalGet(idx::Global(0)), Instr::ConstI32(16), Instr::I32Add]);
+//! assert_eq!(offset.eval(&[Value::I32(1024)]), Ok(Value::I32(1040)));
+//! ```
+
+use err_derive::Error;
+use crate::structure::{module, instr, instr::Instr, ty};
+
+/// The value of a constant expression.
+#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    V128(u128),
+    Null(ty::Heap),
+    Func(u32),
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error(display = "Unknown global `{}`.", idx)]
+    UnknownGlobal { idx: u32 },
+
+    #[error(display = "Instruction `{}` can not be evaluated in a constant expression.", instr)]
+    NonConstantInstruction { instr: String },
+
+    #[error(display = "Operands of instruction `{}` have the wrong type.", instr)]
+    TypeMismatch { instr: String },
+
+    #[error(display = "Expected a single value at the end of the expression, found {}.", found)]
+    UnbalancedStack { found: usize },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl instr::ConstExpr {
+    /// Evaluates the expression, where `globals` are the values of the globals it can refer to.
+    /// 
+    /// The expression is not validated, so the instructions of extended constant expressions are
+    /// evaluated even if the `extended_const` feature is disabled. Validate the module first with
+    /// the features that it may use.
+    pub fn eval(&self, globals: &[Value]) -> Result<Value> {
+        let mut stack = vec![];
+        for instr in self.0.iter() {
+            let value = match instr {
+                Instr::ConstI32(val) => Value::I32(*val),
+                Instr::ConstI64(val) => Value::I64(*val),
+                Instr::ConstF32(val) => Value::F32(*val),
+                Instr::ConstF64(val) => Value::F64(*val),
+                Instr::ConstV128(val) => Value::V128(*val),
+                Instr::RefNull(ty) => Value::Null(*ty),
+                Instr::RefFunc(idx) => Value::Func(idx.0),
+                Instr::GlobalGet(idx) => *globals.get(idx.0 as usize).ok_or(Error::UnknownGlobal { idx: idx.0 })?,
+                Instr::I32Add => eval_i32(instr, &mut stack, i32::wrapping_add)?,
+                Instr::I32Sub => eval_i32(instr, &mut stack, i32::wrapping_sub)?,
+                Instr::I32Mul => eval_i32(instr, &mut stack, i32::wrapping_mul)?,
+                Instr::I64Add => eval_i64(instr, &mut stack, i64::wrapping_add)?,
+                Instr::I64Sub => eval_i64(instr, &mut stack, i64::wrapping_sub)?,
+                Instr::I64Mul => eval_i64(instr, &mut stack, i64::wrapping_mul)?,
+                _ => return Err(Error::NonConstantInstruction { instr: format!("{:?}", instr) }),
+            };
+            stack.push(value);
+        }
+        match stack[..] {
+            [value] => Ok(value),
+            _ => Err(Error::UnbalancedStack { found: stack.len() }),
+        }
+    }
+}
+
+impl module::Module {
+    /// Evaluates the initial values of the globals defined in the module, given the values of
+    /// its imported globals. The result holds the values of all the globals, imported first.
+    pub fn eval_globals(&self, imports: &[Value]) -> Result<Vec<Value>> {
+        let mut globals = imports.to_vec();
+        for global in self.globals.iter() {
+            let value = global.init.eval(&globals)?;
+            globals.push(value);
+        }
+        Ok(globals)
+    }
+}
+
+fn eval_i32(instr: &Instr, stack: &mut Vec<Value>, op: fn(i32, i32) -> i32) -> Result<Value> {
+    match (stack.pop(), stack.pop()) {
+        (Some(Value::I32(rhs)), Some(Value::I32(lhs))) => Ok(Value::I32(op(lhs, rhs))),
+        _ => Err(Error::TypeMismatch { instr: format!("{:?}", instr) }),
+    }
+}
+
+fn eval_i64(instr: &Instr, stack: &mut Vec<Value>, op: fn(i64, i64) -> i64) -> Result<Value> {
+    match (stack.pop(), stack.pop()) {
+        (Some(Value::I64(rhs)), Some(Value::I64(lhs))) => Ok(Value::I64(op(lhs, rhs))),
+        _ => Err(Error::TypeMismatch { instr: format!("{:?}", instr) }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::eval::{Value, Error};
+    use crate::structure::{module, idx, ty, instr::{ConstExpr, Instr}};
+
+    #[test]
+    fn can_evaluate_constant_expressions() {
+        let expr = ConstExpr(vec![Instr::ConstI32(i32::MAX), Instr::ConstI32(1), Instr::I32Add]);
+        assert_eq!(expr.eval(&[]), Ok(Value::I32(i32::MIN)));
+
+        let expr = ConstExpr(vec![Instr::ConstI64(10), Instr::GlobalGet(idx::Global(0)), Instr::I64Sub, Instr::ConstI64(3), Instr::I64Mul]);
+        assert_eq!(expr.eval(&[Value::I64(4)]), Ok(Value::I64(18)));
+
+        let expr = ConstExpr(vec![Instr::RefNull(ty::Heap::Func)]);
+        assert_eq!(expr.eval(&[]), Ok(Value::Null(ty::Heap::Func)));
+
+        let expr = ConstExpr(vec![Instr::ConstI32(1), Instr::ConstI64(1), Instr::I32Add]);
+        assert_eq!(expr.eval(&[]), Err(Error::TypeMismatch { instr: "I32Add".into() }));
+
+        let expr = ConstExpr(vec![Instr::GlobalGet(idx::Global(1))]);
+        assert_eq!(expr.eval(&[Value::I32(0)]), Err(Error::UnknownGlobal { idx: 1 }));
+
+        let expr = ConstExpr(vec![Instr::ConstI32(1), Instr::ConstI32(2)]);
+        assert_eq!(expr.eval(&[]), Err(Error::UnbalancedStack { found: 2 }));
+    }
+
+    #[test]
+    fn can_evaluate_globals() {
+        let global = |init| module::Global { ty: ty::Global { mt: ty::Mut::Const, val: ty::Val::I32 }, init: ConstExpr(init) };
+        let module = module::Module {
+            globals: vec![
+                global(vec![Instr::GlobalGet(idx::Global(0)), Instr::ConstI32(8), Instr::I32Add]),
+                global(vec![Instr::GlobalGet(idx::Global(1)), Instr::ConstI32(2), Instr::I32Mul]),
+            ],
+            .. Default::default()
+        };
+        assert_eq!(module.eval_globals(&[Value::I32(1024)]), Ok(vec![Value::I32(1024), Value::I32(1032), Value::I32(2064)]));
+    }
+}
\ No newline at end of file
@@ -1,3 +1,5 @@
 pub mod structure;
 pub mod binary;
-pub mod validation;
\ No newline at end of file
+pub mod validation;
+
+pub mod eval;
\ No newline at end of file
@@ -1,5 +1,5 @@
 #[derive(Copy, Clone)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct Type(pub u32);
 
 #[derive(Debug, PartialEq)]
@@ -14,6 +14,18 @@ pub struct Mem(pub u32);
 #[derive(Debug, PartialEq)]
 pub struct Global(pub u32);
 
+#[derive(Debug, PartialEq)]
+pub struct Elem(pub u32);
+
+#[derive(Debug, PartialEq)]
+pub struct Data(pub u32);
+
+#[derive(Debug, PartialEq)]
+pub struct Tag(pub u32);
+
+#[derive(Debug, PartialEq)]
+pub struct Field(pub u32);
+
 #[derive(Debug, PartialEq)]
 pub struct Local(pub u32);
 
@@ -15,7 +15,12 @@ pub enum Instr {
     /* 0x03 */ Loop(ty::RetVal, Expr),
     /* 0x04 */ If(ty::RetVal, Expr, Expr),
     /* 0x05 */ Else,
-        // ... reserved ...
+    /* 0x06 */ Try(ty::RetVal, Expr, Vec<(idx::Tag, Expr)>, Option<Expr>),
+    /* 0x06 */ TryDelegate(ty::RetVal, Expr, idx::Label),
+    /* 0x07 */ Catch(idx::Tag),
+    /* 0x08 */ Throw(idx::Tag),
+    /* 0x09 */ Rethrow(idx::Label),
+    /* 0x0A */ ThrowRef,
     /* 0x0B */ End,
     /* 0x0C */ Br(idx::Label),
     /* 0x0D */ BrIf(idx::Label),
@@ -23,43 +28,55 @@ pub enum Instr {
     /* 0x0F */ Return,
     /* 0x10 */ Call(idx::Func),
     /* 0x11 */ CallIndirect(idx::Type, idx::Table),
+    /* 0x12 */ ReturnCall(idx::Func),
+    /* 0x13 */ ReturnCallIndirect(idx::Type, idx::Table),
+    /* 0x14 */ CallRef(idx::Type),
+    /* 0x15 */ ReturnCallRef(idx::Type),
         // ... reserved ...
+    /* 0x18 */ Delegate(idx::Label),
+    /* 0x19 */ CatchAll,
     // Parametric
     /* 0x1A */ Drop,
     /* 0x1B */ Select,
+    /* 0x1C */ SelectTyped(Vec<ty::Val>),
         // ... reserved ...
+    // Exceptions
+    /* 0x1F */ TryTable(ty::RetVal, Vec<Catch>, Expr),
     // Variable
     /* 0x20 */ LocalGet(idx::Local),
     /* 0x21 */ LocalSet(idx::Local),
     /* 0x22 */ LocalTee(idx::Local),
     /* 0x23 */ GlobalGet(idx::Global),
     /* 0x24 */ GlobalSet(idx::Global),
+    // Table
+    /* 0x25 */ TableGet(idx::Table),
+    /* 0x26 */ TableSet(idx::Table),
         // ... reserved ...
     // Memory load
-    /* 0x28 */ I32Load(u32, u32),
-    /* 0x29 */ I64Load(u32, u32),
-    /* 0x2A */ F32Load(u32, u32),
-    /* 0x2B */ F64Load(u32, u32),
-    /* 0x2C */ I32Load8S(u32, u32),
-    /* 0x2D */ I32Load8U(u32, u32),
-    /* 0x2E */ I32Load16S(u32, u32),
-    /* 0x2F */ I32Load16U(u32, u32),
-    /* 0x30 */ I64Load8S(u32, u32),
-    /* 0x31 */ I64Load8U(u32, u32),
-    /* 0x32 */ I64Load16S(u32, u32),
-    /* 0x33 */ I64Load16U(u32, u32),
-    /* 0x34 */ I64Load32S(u32, u32),
-    /* 0x35 */ I64Load32U(u32, u32),
+    /* 0x28 */ I32Load(MemArg),
+    /* 0x29 */ I64Load(MemArg),
+    /* 0x2A */ F32Load(MemArg),
+    /* 0x2B */ F64Load(MemArg),
+    /* 0x2C */ I32Load8S(MemArg),
+    /* 0x2D */ I32Load8U(MemArg),
+    /* 0x2E */ I32Load16S(MemArg),
+    /* 0x2F */ I32Load16U(MemArg),
+    /* 0x30 */ I64Load8S(MemArg),
+    /* 0x31 */ I64Load8U(MemArg),
+    /* 0x32 */ I64Load16S(MemArg),
+    /* 0x33 */ I64Load16U(MemArg),
+    /* 0x34 */ I64Load32S(MemArg),
+    /* 0x35 */ I64Load32U(MemArg),
     // Memory store
-    /* 0x36 */ I32Store(u32, u32),
-    /* 0x37 */ I64Store(u32, u32),
-    /* 0x38 */ F32Store(u32, u32),
-    /* 0x39 */ F64Store(u32, u32),
-    /* 0x3A */ I32Store8(u32, u32),
-    /* 0x3B */ I32Store16(u32, u32),
-    /* 0x3C */ I64Store8(u32, u32),
-    /* 0x3D */ I64Store16(u32, u32),
-    /* 0x3E */ I64Store32(u32, u32),
+    /* 0x36 */ I32Store(MemArg),
+    /* 0x37 */ I64Store(MemArg),
+    /* 0x38 */ F32Store(MemArg),
+    /* 0x39 */ F64Store(MemArg),
+    /* 0x3A */ I32Store8(MemArg),
+    /* 0x3B */ I32Store16(MemArg),
+    /* 0x3C */ I64Store8(MemArg),
+    /* 0x3D */ I64Store16(MemArg),
+    /* 0x3E */ I64Store32(MemArg),
     // Memory management
     /* 0x3F */ MemSize(idx::Mem),
     /* 0x40 */ MemGrow(idx::Mem),
@@ -200,5 +217,433 @@ pub enum Instr {
     /* 0xBD */ I64ReinterpretF64,
     /* 0xBE */ F32ReinterpretI32,
     /* 0xBF */ F64ReinterpretI64,
+    // Sign extension
+    /* 0xC0 */ I32Extend8S,
+    /* 0xC1 */ I32Extend16S,
+    /* 0xC2 */ I64Extend8S,
+    /* 0xC3 */ I64Extend16S,
+    /* 0xC4 */ I64Extend32S,
+        // ... reserved ...
+    // Reference
+    /* 0xD0 */ RefNull(ty::Heap),
+    /* 0xD1 */ RefIsNull,
+    /* 0xD2 */ RefFunc(idx::Func),
+    /* 0xD3 */ RefEq,
+    /* 0xD4 */ RefAsNonNull,
+    /* 0xD5 */ BrOnNull(idx::Label),
+    /* 0xD6 */ BrOnNonNull(idx::Label),
+        // ... reserved ...
+    // Aggregate
+    /* 0xFB 0x00 */ StructNew(idx::Type),
+    /* 0xFB 0x01 */ StructNewDefault(idx::Type),
+    /* 0xFB 0x02 */ StructGet(idx::Type, idx::Field),
+    /* 0xFB 0x03 */ StructGetS(idx::Type, idx::Field),
+    /* 0xFB 0x04 */ StructGetU(idx::Type, idx::Field),
+    /* 0xFB 0x05 */ StructSet(idx::Type, idx::Field),
+    /* 0xFB 0x06 */ ArrayNew(idx::Type),
+    /* 0xFB 0x07 */ ArrayNewDefault(idx::Type),
+    /* 0xFB 0x08 */ ArrayNewFixed(idx::Type, u32),
+    /* 0xFB 0x09 */ ArrayNewData(idx::Type, idx::Data),
+    /* 0xFB 0x0A */ ArrayNewElem(idx::Type, idx::Elem),
+    /* 0xFB 0x0B */ ArrayGet(idx::Type),
+    /* 0xFB 0x0C */ ArrayGetS(idx::Type),
+    /* 0xFB 0x0D */ ArrayGetU(idx::Type),
+    /* 0xFB 0x0E */ ArraySet(idx::Type),
+    /* 0xFB 0x0F */ ArrayLen,
+    /* 0xFB 0x10 */ ArrayFill(idx::Type),
+    /* 0xFB 0x11 */ ArrayCopy(idx::Type, idx::Type),
+    /* 0xFB 0x12 */ ArrayInitData(idx::Type, idx::Data),
+    /* 0xFB 0x13 */ ArrayInitElem(idx::Type, idx::Elem),
+    // Casts, whose nullable forms use the following opcode
+    /* 0xFB 0x14 */ RefTest(ty::Ref),
+    /* 0xFB 0x16 */ RefCast(ty::Ref),
+    /* 0xFB 0x18 */ BrOnCast(idx::Label, ty::Ref, ty::Ref),
+    /* 0xFB 0x19 */ BrOnCastFail(idx::Label, ty::Ref, ty::Ref),
+    /* 0xFB 0x1A */ AnyConvertExtern,
+    /* 0xFB 0x1B */ ExternConvertAny,
+    // Scalar references
+    /* 0xFB 0x1C */ RefI31,
+    /* 0xFB 0x1D */ I31GetS,
+    /* 0xFB 0x1E */ I31GetU,
+    // Saturating truncation
+    /* 0xFC 0x00 */ I32TruncSatF32S,
+    /* 0xFC 0x01 */ I32TruncSatF32U,
+    /* 0xFC 0x02 */ I32TruncSatF64S,
+    /* 0xFC 0x03 */ I32TruncSatF64U,
+    /* 0xFC 0x04 */ I64TruncSatF32S,
+    /* 0xFC 0x05 */ I64TruncSatF32U,
+    /* 0xFC 0x06 */ I64TruncSatF64S,
+    /* 0xFC 0x07 */ I64TruncSatF64U,
+    // Bulk memory
+    /* 0xFC 0x08 */ MemInit(idx::Data, idx::Mem),
+    /* 0xFC 0x09 */ DataDrop(idx::Data),
+    /* 0xFC 0x0A */ MemCopy(idx::Mem, idx::Mem),
+    /* 0xFC 0x0B */ MemFill(idx::Mem),
+    /* 0xFC 0x0C */ TableInit(idx::Elem, idx::Table),
+    /* 0xFC 0x0D */ ElemDrop(idx::Elem),
+    /* 0xFC 0x0E */ TableCopy(idx::Table, idx::Table),
+    // Table management
+    /* 0xFC 0x0F */ TableGrow(idx::Table),
+    /* 0xFC 0x10 */ TableSize(idx::Table),
+    /* 0xFC 0x11 */ TableFill(idx::Table),
+    // Vector
+    /* 0xFD 0x00 */ V128Load(MemArg),
+    /* 0xFD 0x01 */ V128Load8x8S(MemArg),
+    /* 0xFD 0x02 */ V128Load8x8U(MemArg),
+    /* 0xFD 0x03 */ V128Load16x4S(MemArg),
+    /* 0xFD 0x04 */ V128Load16x4U(MemArg),
+    /* 0xFD 0x05 */ V128Load32x2S(MemArg),
+    /* 0xFD 0x06 */ V128Load32x2U(MemArg),
+    /* 0xFD 0x07 */ V128Load8Splat(MemArg),
+    /* 0xFD 0x08 */ V128Load16Splat(MemArg),
+    /* 0xFD 0x09 */ V128Load32Splat(MemArg),
+    /* 0xFD 0x0A */ V128Load64Splat(MemArg),
+    /* 0xFD 0x0B */ V128Store(MemArg),
+    /* 0xFD 0x0C */ ConstV128(u128),
+    /* 0xFD 0x0D */ I8x16Shuffle([u8; 16]),
+    /* 0xFD 0x0E */ I8x16Swizzle,
+    /* 0xFD 0x0F */ I8x16Splat,
+    /* 0xFD 0x10 */ I16x8Splat,
+    /* 0xFD 0x11 */ I32x4Splat,
+    /* 0xFD 0x12 */ I64x2Splat,
+    /* 0xFD 0x13 */ F32x4Splat,
+    /* 0xFD 0x14 */ F64x2Splat,
+    /* 0xFD 0x15 */ I8x16ExtractLaneS(u8),
+    /* 0xFD 0x16 */ I8x16ExtractLaneU(u8),
+    /* 0xFD 0x17 */ I8x16ReplaceLane(u8),
+    /* 0xFD 0x18 */ I16x8ExtractLaneS(u8),
+    /* 0xFD 0x19 */ I16x8ExtractLaneU(u8),
+    /* 0xFD 0x1A */ I16x8ReplaceLane(u8),
+    /* 0xFD 0x1B */ I32x4ExtractLane(u8),
+    /* 0xFD 0x1C */ I32x4ReplaceLane(u8),
+    /* 0xFD 0x1D */ I64x2ExtractLane(u8),
+    /* 0xFD 0x1E */ I64x2ReplaceLane(u8),
+    /* 0xFD 0x1F */ F32x4ExtractLane(u8),
+    /* 0xFD 0x20 */ F32x4ReplaceLane(u8),
+    /* 0xFD 0x21 */ F64x2ExtractLane(u8),
+    /* 0xFD 0x22 */ F64x2ReplaceLane(u8),
+    /* 0xFD 0x23 */ I8x16Eq,
+    /* 0xFD 0x24 */ I8x16Ne,
+    /* 0xFD 0x25 */ I8x16LtS,
+    /* 0xFD 0x26 */ I8x16LtU,
+    /* 0xFD 0x27 */ I8x16GtS,
+    /* 0xFD 0x28 */ I8x16GtU,
+    /* 0xFD 0x29 */ I8x16LeS,
+    /* 0xFD 0x2A */ I8x16LeU,
+    /* 0xFD 0x2B */ I8x16GeS,
+    /* 0xFD 0x2C */ I8x16GeU,
+    /* 0xFD 0x2D */ I16x8Eq,
+    /* 0xFD 0x2E */ I16x8Ne,
+    /* 0xFD 0x2F */ I16x8LtS,
+    /* 0xFD 0x30 */ I16x8LtU,
+    /* 0xFD 0x31 */ I16x8GtS,
+    /* 0xFD 0x32 */ I16x8GtU,
+    /* 0xFD 0x33 */ I16x8LeS,
+    /* 0xFD 0x34 */ I16x8LeU,
+    /* 0xFD 0x35 */ I16x8GeS,
+    /* 0xFD 0x36 */ I16x8GeU,
+    /* 0xFD 0x37 */ I32x4Eq,
+    /* 0xFD 0x38 */ I32x4Ne,
+    /* 0xFD 0x39 */ I32x4LtS,
+    /* 0xFD 0x3A */ I32x4LtU,
+    /* 0xFD 0x3B */ I32x4GtS,
+    /* 0xFD 0x3C */ I32x4GtU,
+    /* 0xFD 0x3D */ I32x4LeS,
+    /* 0xFD 0x3E */ I32x4LeU,
+    /* 0xFD 0x3F */ I32x4GeS,
+    /* 0xFD 0x40 */ I32x4GeU,
+    /* 0xFD 0x41 */ F32x4Eq,
+    /* 0xFD 0x42 */ F32x4Ne,
+    /* 0xFD 0x43 */ F32x4Lt,
+    /* 0xFD 0x44 */ F32x4Gt,
+    /* 0xFD 0x45 */ F32x4Le,
+    /* 0xFD 0x46 */ F32x4Ge,
+    /* 0xFD 0x47 */ F64x2Eq,
+    /* 0xFD 0x48 */ F64x2Ne,
+    /* 0xFD 0x49 */ F64x2Lt,
+    /* 0xFD 0x4A */ F64x2Gt,
+    /* 0xFD 0x4B */ F64x2Le,
+    /* 0xFD 0x4C */ F64x2Ge,
+    /* 0xFD 0x4D */ V128Not,
+    /* 0xFD 0x4E */ V128And,
+    /* 0xFD 0x4F */ V128AndNot,
+    /* 0xFD 0x50 */ V128Or,
+    /* 0xFD 0x51 */ V128Xor,
+    /* 0xFD 0x52 */ V128Bitselect,
+    /* 0xFD 0x53 */ V128AnyTrue,
+    /* 0xFD 0x54 */ V128Load8Lane(MemArg, u8),
+    /* 0xFD 0x55 */ V128Load16Lane(MemArg, u8),
+    /* 0xFD 0x56 */ V128Load32Lane(MemArg, u8),
+    /* 0xFD 0x57 */ V128Load64Lane(MemArg, u8),
+    /* 0xFD 0x58 */ V128Store8Lane(MemArg, u8),
+    /* 0xFD 0x59 */ V128Store16Lane(MemArg, u8),
+    /* 0xFD 0x5A */ V128Store32Lane(MemArg, u8),
+    /* 0xFD 0x5B */ V128Store64Lane(MemArg, u8),
+    /* 0xFD 0x5C */ V128Load32Zero(MemArg),
+    /* 0xFD 0x5D */ V128Load64Zero(MemArg),
+    /* 0xFD 0x5E */ F32x4DemoteF64x2Zero,
+    /* 0xFD 0x5F */ F64x2PromoteLowF32x4,
+    /* 0xFD 0x60 */ I8x16Abs,
+    /* 0xFD 0x61 */ I8x16Neg,
+    /* 0xFD 0x62 */ I8x16Popcnt,
+    /* 0xFD 0x63 */ I8x16AllTrue,
+    /* 0xFD 0x64 */ I8x16Bitmask,
+    /* 0xFD 0x65 */ I8x16NarrowI16x8S,
+    /* 0xFD 0x66 */ I8x16NarrowI16x8U,
+    /* 0xFD 0x67 */ F32x4Ceil,
+    /* 0xFD 0x68 */ F32x4Floor,
+    /* 0xFD 0x69 */ F32x4Trunc,
+    /* 0xFD 0x6A */ F32x4Nearest,
+    /* 0xFD 0x6B */ I8x16Shl,
+    /* 0xFD 0x6C */ I8x16ShrS,
+    /* 0xFD 0x6D */ I8x16ShrU,
+    /* 0xFD 0x6E */ I8x16Add,
+    /* 0xFD 0x6F */ I8x16AddSatS,
+    /* 0xFD 0x70 */ I8x16AddSatU,
+    /* 0xFD 0x71 */ I8x16Sub,
+    /* 0xFD 0x72 */ I8x16SubSatS,
+    /* 0xFD 0x73 */ I8x16SubSatU,
+    /* 0xFD 0x74 */ F64x2Ceil,
+    /* 0xFD 0x75 */ F64x2Floor,
+    /* 0xFD 0x76 */ I8x16MinS,
+    /* 0xFD 0x77 */ I8x16MinU,
+    /* 0xFD 0x78 */ I8x16MaxS,
+    /* 0xFD 0x79 */ I8x16MaxU,
+    /* 0xFD 0x7A */ F64x2Trunc,
+    /* 0xFD 0x7B */ I8x16AvgrU,
+    /* 0xFD 0x7C */ I16x8ExtaddPairwiseI8x16S,
+    /* 0xFD 0x7D */ I16x8ExtaddPairwiseI8x16U,
+    /* 0xFD 0x7E */ I32x4ExtaddPairwiseI16x8S,
+    /* 0xFD 0x7F */ I32x4ExtaddPairwiseI16x8U,
+    /* 0xFD 0x80 */ I16x8Abs,
+    /* 0xFD 0x81 */ I16x8Neg,
+    /* 0xFD 0x82 */ I16x8Q15mulrSatS,
+    /* 0xFD 0x83 */ I16x8AllTrue,
+    /* 0xFD 0x84 */ I16x8Bitmask,
+    /* 0xFD 0x85 */ I16x8NarrowI32x4S,
+    /* 0xFD 0x86 */ I16x8NarrowI32x4U,
+    /* 0xFD 0x87 */ I16x8ExtendLowI8x16S,
+    /* 0xFD 0x88 */ I16x8ExtendHighI8x16S,
+    /* 0xFD 0x89 */ I16x8ExtendLowI8x16U,
+    /* 0xFD 0x8A */ I16x8ExtendHighI8x16U,
+    /* 0xFD 0x8B */ I16x8Shl,
+    /* 0xFD 0x8C */ I16x8ShrS,
+    /* 0xFD 0x8D */ I16x8ShrU,
+    /* 0xFD 0x8E */ I16x8Add,
+    /* 0xFD 0x8F */ I16x8AddSatS,
+    /* 0xFD 0x90 */ I16x8AddSatU,
+    /* 0xFD 0x91 */ I16x8Sub,
+    /* 0xFD 0x92 */ I16x8SubSatS,
+    /* 0xFD 0x93 */ I16x8SubSatU,
+    /* 0xFD 0x94 */ F64x2Nearest,
+    /* 0xFD 0x95 */ I16x8Mul,
+    /* 0xFD 0x96 */ I16x8MinS,
+    /* 0xFD 0x97 */ I16x8MinU,
+    /* 0xFD 0x98 */ I16x8MaxS,
+    /* 0xFD 0x99 */ I16x8MaxU,
+        // ... reserved ...
+    /* 0xFD 0x9B */ I16x8AvgrU,
+    /* 0xFD 0x9C */ I16x8ExtmulLowI8x16S,
+    /* 0xFD 0x9D */ I16x8ExtmulHighI8x16S,
+    /* 0xFD 0x9E */ I16x8ExtmulLowI8x16U,
+    /* 0xFD 0x9F */ I16x8ExtmulHighI8x16U,
+    /* 0xFD 0xA0 */ I32x4Abs,
+    /* 0xFD 0xA1 */ I32x4Neg,
+        // ... reserved ...
+    /* 0xFD 0xA3 */ I32x4AllTrue,
+    /* 0xFD 0xA4 */ I32x4Bitmask,
+        // ... reserved ...
+    /* 0xFD 0xA7 */ I32x4ExtendLowI16x8S,
+    /* 0xFD 0xA8 */ I32x4ExtendHighI16x8S,
+    /* 0xFD 0xA9 */ I32x4ExtendLowI16x8U,
+    /* 0xFD 0xAA */ I32x4ExtendHighI16x8U,
+    /* 0xFD 0xAB */ I32x4Shl,
+    /* 0xFD 0xAC */ I32x4ShrS,
+    /* 0xFD 0xAD */ I32x4ShrU,
+    /* 0xFD 0xAE */ I32x4Add,
+        // ... reserved ...
+    /* 0xFD 0xB1 */ I32x4Sub,
+        // ... reserved ...
+    /* 0xFD 0xB5 */ I32x4Mul,
+    /* 0xFD 0xB6 */ I32x4MinS,
+    /* 0xFD 0xB7 */ I32x4MinU,
+    /* 0xFD 0xB8 */ I32x4MaxS,
+    /* 0xFD 0xB9 */ I32x4MaxU,
+    /* 0xFD 0xBA */ I32x4DotI16x8S,
+        // ... reserved ...
+    /* 0xFD 0xBC */ I32x4ExtmulLowI16x8S,
+    /* 0xFD 0xBD */ I32x4ExtmulHighI16x8S,
+    /* 0xFD 0xBE */ I32x4ExtmulLowI16x8U,
+    /* 0xFD 0xBF */ I32x4ExtmulHighI16x8U,
+    /* 0xFD 0xC0 */ I64x2Abs,
+    /* 0xFD 0xC1 */ I64x2Neg,
+        // ... reserved ...
+    /* 0xFD 0xC3 */ I64x2AllTrue,
+    /* 0xFD 0xC4 */ I64x2Bitmask,
+        // ... reserved ...
+    /* 0xFD 0xC7 */ I64x2ExtendLowI32x4S,
+    /* 0xFD 0xC8 */ I64x2ExtendHighI32x4S,
+    /* 0xFD 0xC9 */ I64x2ExtendLowI32x4U,
+    /* 0xFD 0xCA */ I64x2ExtendHighI32x4U,
+    /* 0xFD 0xCB */ I64x2Shl,
+    /* 0xFD 0xCC */ I64x2ShrS,
+    /* 0xFD 0xCD */ I64x2ShrU,
+    /* 0xFD 0xCE */ I64x2Add,
         // ... reserved ...
+    /* 0xFD 0xD1 */ I64x2Sub,
+        // ... reserved ...
+    /* 0xFD 0xD5 */ I64x2Mul,
+    /* 0xFD 0xD6 */ I64x2Eq,
+    /* 0xFD 0xD7 */ I64x2Ne,
+    /* 0xFD 0xD8 */ I64x2LtS,
+    /* 0xFD 0xD9 */ I64x2GtS,
+    /* 0xFD 0xDA */ I64x2LeS,
+    /* 0xFD 0xDB */ I64x2GeS,
+    /* 0xFD 0xDC */ I64x2ExtmulLowI32x4S,
+    /* 0xFD 0xDD */ I64x2ExtmulHighI32x4S,
+    /* 0xFD 0xDE */ I64x2ExtmulLowI32x4U,
+    /* 0xFD 0xDF */ I64x2ExtmulHighI32x4U,
+    /* 0xFD 0xE0 */ F32x4Abs,
+    /* 0xFD 0xE1 */ F32x4Neg,
+        // ... reserved ...
+    /* 0xFD 0xE3 */ F32x4Sqrt,
+    /* 0xFD 0xE4 */ F32x4Add,
+    /* 0xFD 0xE5 */ F32x4Sub,
+    /* 0xFD 0xE6 */ F32x4Mul,
+    /* 0xFD 0xE7 */ F32x4Div,
+    /* 0xFD 0xE8 */ F32x4Min,
+    /* 0xFD 0xE9 */ F32x4Max,
+    /* 0xFD 0xEA */ F32x4Pmin,
+    /* 0xFD 0xEB */ F32x4Pmax,
+    /* 0xFD 0xEC */ F64x2Abs,
+    /* 0xFD 0xED */ F64x2Neg,
+        // ... reserved ...
+    /* 0xFD 0xEF */ F64x2Sqrt,
+    /* 0xFD 0xF0 */ F64x2Add,
+    /* 0xFD 0xF1 */ F64x2Sub,
+    /* 0xFD 0xF2 */ F64x2Mul,
+    /* 0xFD 0xF3 */ F64x2Div,
+    /* 0xFD 0xF4 */ F64x2Min,
+    /* 0xFD 0xF5 */ F64x2Max,
+    /* 0xFD 0xF6 */ F64x2Pmin,
+    /* 0xFD 0xF7 */ F64x2Pmax,
+    /* 0xFD 0xF8 */ I32x4TruncSatF32x4S,
+    /* 0xFD 0xF9 */ I32x4TruncSatF32x4U,
+    /* 0xFD 0xFA */ F32x4ConvertI32x4S,
+    /* 0xFD 0xFB */ F32x4ConvertI32x4U,
+    /* 0xFD 0xFC */ I32x4TruncSatF64x2SZero,
+    /* 0xFD 0xFD */ I32x4TruncSatF64x2UZero,
+    /* 0xFD 0xFE */ F64x2ConvertLowI32x4S,
+    /* 0xFD 0xFF */ F64x2ConvertLowI32x4U,
+    // Relaxed vector
+    /* 0xFD 0x100 */ I8x16RelaxedSwizzle,
+    /* 0xFD 0x101 */ I32x4RelaxedTruncF32x4S,
+    /* 0xFD 0x102 */ I32x4RelaxedTruncF32x4U,
+    /* 0xFD 0x103 */ I32x4RelaxedTruncF64x2SZero,
+    /* 0xFD 0x104 */ I32x4RelaxedTruncF64x2UZero,
+    /* 0xFD 0x105 */ F32x4RelaxedMadd,
+    /* 0xFD 0x106 */ F32x4RelaxedNmadd,
+    /* 0xFD 0x107 */ F64x2RelaxedMadd,
+    /* 0xFD 0x108 */ F64x2RelaxedNmadd,
+    /* 0xFD 0x109 */ I8x16RelaxedLaneselect,
+    /* 0xFD 0x10A */ I16x8RelaxedLaneselect,
+    /* 0xFD 0x10B */ I32x4RelaxedLaneselect,
+    /* 0xFD 0x10C */ I64x2RelaxedLaneselect,
+    /* 0xFD 0x10D */ F32x4RelaxedMin,
+    /* 0xFD 0x10E */ F32x4RelaxedMax,
+    /* 0xFD 0x10F */ F64x2RelaxedMin,
+    /* 0xFD 0x110 */ F64x2RelaxedMax,
+    /* 0xFD 0x111 */ I16x8RelaxedQ15mulrS,
+    /* 0xFD 0x112 */ I16x8RelaxedDotI8x16I7x16S,
+    /* 0xFD 0x113 */ I32x4RelaxedDotI8x16I7x16AddS,
+    // Atomic memory
+    /* 0xFE 0x00 */ MemAtomicNotify(MemArg),
+    /* 0xFE 0x01 */ MemAtomicWait32(MemArg),
+    /* 0xFE 0x02 */ MemAtomicWait64(MemArg),
+    /* 0xFE 0x03 */ AtomicFence,
+        // ... reserved ...
+    /* 0xFE 0x10 */ I32AtomicLoad(MemArg),
+    /* 0xFE 0x11 */ I64AtomicLoad(MemArg),
+    /* 0xFE 0x12 */ I32AtomicLoad8U(MemArg),
+    /* 0xFE 0x13 */ I32AtomicLoad16U(MemArg),
+    /* 0xFE 0x14 */ I64AtomicLoad8U(MemArg),
+    /* 0xFE 0x15 */ I64AtomicLoad16U(MemArg),
+    /* 0xFE 0x16 */ I64AtomicLoad32U(MemArg),
+    /* 0xFE 0x17 */ I32AtomicStore(MemArg),
+    /* 0xFE 0x18 */ I64AtomicStore(MemArg),
+    /* 0xFE 0x19 */ I32AtomicStore8(MemArg),
+    /* 0xFE 0x1A */ I32AtomicStore16(MemArg),
+    /* 0xFE 0x1B */ I64AtomicStore8(MemArg),
+    /* 0xFE 0x1C */ I64AtomicStore16(MemArg),
+    /* 0xFE 0x1D */ I64AtomicStore32(MemArg),
+    /* 0xFE 0x1E */ I32AtomicRmwAdd(MemArg),
+    /* 0xFE 0x1F */ I64AtomicRmwAdd(MemArg),
+    /* 0xFE 0x20 */ I32AtomicRmw8AddU(MemArg),
+    /* 0xFE 0x21 */ I32AtomicRmw16AddU(MemArg),
+    /* 0xFE 0x22 */ I64AtomicRmw8AddU(MemArg),
+    /* 0xFE 0x23 */ I64AtomicRmw16AddU(MemArg),
+    /* 0xFE 0x24 */ I64AtomicRmw32AddU(MemArg),
+    /* 0xFE 0x25 */ I32AtomicRmwSub(MemArg),
+    /* 0xFE 0x26 */ I64AtomicRmwSub(MemArg),
+    /* 0xFE 0x27 */ I32AtomicRmw8SubU(MemArg),
+    /* 0xFE 0x28 */ I32AtomicRmw16SubU(MemArg),
+    /* 0xFE 0x29 */ I64AtomicRmw8SubU(MemArg),
+    /* 0xFE 0x2A */ I64AtomicRmw16SubU(MemArg),
+    /* 0xFE 0x2B */ I64AtomicRmw32SubU(MemArg),
+    /* 0xFE 0x2C */ I32AtomicRmwAnd(MemArg),
+    /* 0xFE 0x2D */ I64AtomicRmwAnd(MemArg),
+    /* 0xFE 0x2E */ I32AtomicRmw8AndU(MemArg),
+    /* 0xFE 0x2F */ I32AtomicRmw16AndU(MemArg),
+    /* 0xFE 0x30 */ I64AtomicRmw8AndU(MemArg),
+    /* 0xFE 0x31 */ I64AtomicRmw16AndU(MemArg),
+    /* 0xFE 0x32 */ I64AtomicRmw32AndU(MemArg),
+    /* 0xFE 0x33 */ I32AtomicRmwOr(MemArg),
+    /* 0xFE 0x34 */ I64AtomicRmwOr(MemArg),
+    /* 0xFE 0x35 */ I32AtomicRmw8OrU(MemArg),
+    /* 0xFE 0x36 */ I32AtomicRmw16OrU(MemArg),
+    /* 0xFE 0x37 */ I64AtomicRmw8OrU(MemArg),
+    /* 0xFE 0x38 */ I64AtomicRmw16OrU(MemArg),
+    /* 0xFE 0x39 */ I64AtomicRmw32OrU(MemArg),
+    /* 0xFE 0x3A */ I32AtomicRmwXor(MemArg),
+    /* 0xFE 0x3B */ I64AtomicRmwXor(MemArg),
+    /* 0xFE 0x3C */ I32AtomicRmw8XorU(MemArg),
+    /* 0xFE 0x3D */ I32AtomicRmw16XorU(MemArg),
+    /* 0xFE 0x3E */ I64AtomicRmw8XorU(MemArg),
+    /* 0xFE 0x3F */ I64AtomicRmw16XorU(MemArg),
+    /* 0xFE 0x40 */ I64AtomicRmw32XorU(MemArg),
+    /* 0xFE 0x41 */ I32AtomicRmwXchg(MemArg),
+    /* 0xFE 0x42 */ I64AtomicRmwXchg(MemArg),
+    /* 0xFE 0x43 */ I32AtomicRmw8XchgU(MemArg),
+    /* 0xFE 0x44 */ I32AtomicRmw16XchgU(MemArg),
+    /* 0xFE 0x45 */ I64AtomicRmw8XchgU(MemArg),
+    /* 0xFE 0x46 */ I64AtomicRmw16XchgU(MemArg),
+    /* 0xFE 0x47 */ I64AtomicRmw32XchgU(MemArg),
+    /* 0xFE 0x48 */ I32AtomicRmwCmpxchg(MemArg),
+    /* 0xFE 0x49 */ I64AtomicRmwCmpxchg(MemArg),
+    /* 0xFE 0x4A */ I32AtomicRmw8CmpxchgU(MemArg),
+    /* 0xFE 0x4B */ I32AtomicRmw16CmpxchgU(MemArg),
+    /* 0xFE 0x4C */ I64AtomicRmw8CmpxchgU(MemArg),
+    /* 0xFE 0x4D */ I64AtomicRmw16CmpxchgU(MemArg),
+    /* 0xFE 0x4E */ I64AtomicRmw32CmpxchgU(MemArg),
+        // ... reserved ...
+}
+
+
+// The immediate of memory instructions: the alignment exponent, the offset added to the address,
+// and the memory being accessed.
+#[derive(Debug, PartialEq)]
+pub struct MemArg {
+    pub align: u32,
+    pub offset: u64,
+    pub mem: idx::Mem,
+}
+
+// The catch clauses of a `try_table` block, each branching to a label when a matching exception
+// is caught. The `Ref` variants additionally push the caught exception as an `exnref`.
+#[derive(Debug, PartialEq)]
+pub enum Catch {
+    /* 0x00 */ Catch(idx::Tag, idx::Label),
+    /* 0x01 */ CatchRef(idx::Tag, idx::Label),
+    /* 0x02 */ CatchAll(idx::Label),
+    /* 0x03 */ CatchAllRef(idx::Label),
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 pub mod idx;
 pub mod instr;
 pub mod module;
+pub mod name;
 pub mod ty;
\ No newline at end of file
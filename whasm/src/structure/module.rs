@@ -3,23 +3,40 @@ use super::{ty, idx, instr};
 #[derive(Debug, PartialEq)]
 #[derive(Default)]
 pub struct Module {
-    pub types: Vec<ty::Func>,
+    pub types: Vec<ty::Rec>,
     pub funcs: Vec<Func>,
     pub tables: Vec<Table>,
     pub mems: Vec<Mem>,
+    pub tags: Vec<Tag>,
     pub globals: Vec<Global>,
     pub elem: Vec<Elem>,
     pub data: Vec<Data>,
+    pub data_count: Option<u32>,
     pub start: Option<Start>,
     pub imports: Vec<Import>,
     pub exports: Vec<Export>,
+    pub customs: Vec<Custom>,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Func {
     pub ty: idx::Type,
     pub locals: Vec<ty::Val>,
-    pub body: instr::Expr,
+    pub body: Body,
+}
+
+// The body of a function is either a parsed expression, or the raw bytes of its encoding when
+// the module was parsed lazily. The expression of a raw body is decoded on demand and cached.
+#[derive(Debug)]
+pub enum Body {
+    Expr(instr::Expr),
+    Raw { bytes: Vec<u8>, expr: std::sync::OnceLock<instr::Expr> },
+}
+
+impl From<instr::Expr> for Body {
+    fn from(expr: instr::Expr) -> Body {
+        Body::Expr(expr)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -32,6 +49,11 @@ pub struct Mem {
     pub ty: ty::Mem,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct Tag {
+    pub ty: ty::Tag,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Global {
     pub ty: ty::Global,
@@ -40,18 +62,43 @@ pub struct Global {
 
 #[derive(Debug, PartialEq)]
 pub struct Elem {
-    pub table: idx::Table,
-    pub offset: instr::ConstExpr,
-    pub init: Vec<idx::Func>,
+    pub ty: ty::Ref,
+    pub mode: ElemMode,
+    pub init: ElemInit,
+}
+
+// Active segments are copied into a table when the module is instantiated, passive segments
+// are only copied explicitly by `table.init`, and declarative segments only forward-declare
+// the functions referenced with `ref.func`.
+#[derive(Debug, PartialEq)]
+pub enum ElemMode {
+    Passive,
+    Active { table: idx::Table, offset: instr::ConstExpr },
+    Declarative,
+}
+
+// The initial references of an element segment, either as function indices or as constant
+// expressions.
+#[derive(Debug, PartialEq)]
+pub enum ElemInit {
+    Funcs(Vec<idx::Func>),
+    Exprs(Vec<instr::ConstExpr>),
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Data {
-    pub mem: idx::Mem,
-    pub offset: instr::ConstExpr,
+    pub mode: DataMode,
     pub init: Vec<u8>,
 }
 
+// Active segments are copied into a memory when the module is instantiated, while passive
+// segments are only copied explicitly by `memory.init`.
+#[derive(Debug, PartialEq)]
+pub enum DataMode {
+    Passive,
+    Active { mem: idx::Mem, offset: instr::ConstExpr },
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Start {
     pub func: idx::Func,
@@ -66,6 +113,7 @@ pub mod desc {
         Table(ty::Table),
         Mem(ty::Mem),
         Global(ty::Global),
+        Tag(ty::Tag),
     }
 
     #[derive(Debug, PartialEq)]
@@ -74,6 +122,7 @@ pub mod desc {
         Table(idx::Table),
         Mem(idx::Mem),
         Global(idx::Global),
+        Tag(idx::Tag),
     }
 }
 
@@ -88,4 +137,22 @@ pub struct Import {
 pub struct Export {
     pub name: String,
     pub desc: desc::Export,
+}
+
+#[derive(PartialEq)]
+pub struct Custom {
+    pub name: String,
+    pub data: Vec<u8>,
+    // id of the last non-custom section preceding this one, or 0x00 if there is none
+    pub after: u8,
+}
+
+impl std::fmt::Debug for Custom {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("Custom")
+            .field("name", &self.name)
+            .field("size", &self.data.len())
+            .field("after", &self.after)
+            .finish()
+    }
 }
\ No newline at end of file
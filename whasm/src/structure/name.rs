@@ -0,0 +1,106 @@
+use super::idx;
+
+#[derive(Debug, PartialEq)]
+#[derive(Default)]
+pub struct NameSection {
+    pub module: Option<String>,
+    pub funcs: NameMap,
+    pub locals: IndirectNameMap,
+    pub labels: IndirectNameMap,
+    pub types: NameMap,
+    pub tables: NameMap,
+    pub mems: NameMap,
+    pub globals: NameMap,
+    pub elem: NameMap,
+    pub data: NameMap,
+    pub fields: IndirectNameMap,
+    pub tags: NameMap,
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Default)]
+pub struct NameMap(pub Vec<NameAssoc>);
+
+#[derive(Debug, PartialEq)]
+pub struct NameAssoc {
+    pub idx: u32,
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Default)]
+pub struct IndirectNameMap(pub Vec<IndirectNameAssoc>);
+
+#[derive(Debug, PartialEq)]
+pub struct IndirectNameAssoc {
+    pub idx: u32,
+    pub names: NameMap,
+}
+
+impl NameMap {
+    pub fn get(&self, idx: u32) -> Option<&str> {
+        self.0.iter()
+            .find(|assoc| assoc.idx == idx)
+            .map(|assoc| &assoc.name[..])
+    }
+}
+
+impl IndirectNameMap {
+    pub fn get(&self, idx: u32) -> Option<&NameMap> {
+        self.0.iter()
+            .find(|assoc| assoc.idx == idx)
+            .map(|assoc| &assoc.names)
+    }
+}
+
+impl NameSection {
+    pub fn module_name(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    pub fn func_name(&self, idx::Func(func): &idx::Func) -> Option<&str> {
+        self.funcs.get(*func)
+    }
+
+    pub fn local_name(&self, idx::Func(func): &idx::Func, idx::Local(local): &idx::Local) -> Option<&str> {
+        self.locals.get(*func)?.get(*local)
+    }
+
+    /// Returns the name of a label of the function. Labels are numbered by the order in which
+    /// their blocks appear in the function body, unlike the relative depth of `idx::Label`.
+    pub fn label_name(&self, idx::Func(func): &idx::Func, label: u32) -> Option<&str> {
+        self.labels.get(*func)?.get(label)
+    }
+
+    pub fn type_name(&self, idx::Type(ty): &idx::Type) -> Option<&str> {
+        self.types.get(*ty)
+    }
+
+    pub fn table_name(&self, idx::Table(table): &idx::Table) -> Option<&str> {
+        self.tables.get(*table)
+    }
+
+    pub fn mem_name(&self, idx::Mem(mem): &idx::Mem) -> Option<&str> {
+        self.mems.get(*mem)
+    }
+
+    pub fn global_name(&self, idx::Global(global): &idx::Global) -> Option<&str> {
+        self.globals.get(*global)
+    }
+
+    pub fn elem_name(&self, idx::Elem(elem): &idx::Elem) -> Option<&str> {
+        self.elem.get(*elem)
+    }
+
+    pub fn data_name(&self, idx::Data(data): &idx::Data) -> Option<&str> {
+        self.data.get(*data)
+    }
+
+    pub fn tag_name(&self, idx::Tag(tag): &idx::Tag) -> Option<&str> {
+        self.tags.get(*tag)
+    }
+
+    pub fn field_name(&self, idx::Type(ty): &idx::Type, idx::Field(field): &idx::Field) -> Option<&str> {
+        self.fields.get(*ty)?.get(*field)
+    }
+}
\ No newline at end of file
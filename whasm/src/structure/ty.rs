@@ -1,25 +1,84 @@
+use crate::structure::idx;
+
 #[derive(Debug, PartialEq)]
 pub struct Limits {
-    pub min: u32,
-    pub max: Option<u32>,
+    pub min: u64,
+    pub max: Option<u64>,
 }
 
 #[derive(Copy, Clone)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Val {
     I32,
     I64,
     F32,
     F64,
+    V128,
+    Ref(Ref),
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Elem {
-    FuncRef,
+impl Val {
+    pub const FUNC_REF: Val = Val::Ref(Ref::FUNC);
+    pub const EXTERN_REF: Val = Val::Ref(Ref::EXTERN);
+    pub const EXN_REF: Val = Val::Ref(Ref::EXN);
+
+    pub fn is_ref(&self) -> bool {
+        matches!(self, Val::Ref(_))
+    }
+
+    /// Returns whether locals of this type can be initialized with a default value, which is
+    /// the case for all types except non-nullable references.
+    pub fn is_defaultable(&self) -> bool {
+        !matches!(self, Val::Ref(Ref { nullable: false, .. }))
+    }
+}
+
+// The type of a reference, which is stored in tables and element segments.
+#[derive(Copy, Clone)]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Ref {
+    pub nullable: bool,
+    pub heap: Heap,
+}
+
+impl Ref {
+    pub const FUNC: Ref = Ref { nullable: true, heap: Heap::Func };
+    pub const EXTERN: Ref = Ref { nullable: true, heap: Heap::Extern };
+    pub const EXN: Ref = Ref { nullable: true, heap: Heap::Exn };
+}
+
+impl From<Ref> for Val {
+    fn from(r: Ref) -> Val {
+        Val::Ref(r)
+    }
+}
+
+// The type of the objects pointed to by references: either one of the abstract heap types, or
+// a type defined in the type section.
+#[derive(Copy, Clone)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Heap {
+    Func,
+    NoFunc,
+    Extern,
+    NoExtern,
+    Any,
+    Eq,
+    I31,
+    Struct,
+    Array,
+    None,
+    Exn,
+    NoExn,
+    Type(idx::Type),
 }
 
 #[derive(Debug, PartialEq)]
-pub struct RetVal (pub Vec<Val>);
+pub enum RetVal {
+    Empty,
+    Val(Val),
+    Type(idx::Type),
+}
 
 #[derive(Debug, PartialEq)]
 pub struct Func {
@@ -27,22 +86,125 @@ pub struct Func {
     pub results: Vec<Val>,
 }
 
+// The type of a field of a struct or the elements of an array, which can be packed.
+#[derive(Copy, Clone)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Storage {
+    Val(Val),
+    I8,
+    I16,
+}
+
+impl Storage {
+    /// Returns the type of the operands used to read and write values of this storage type.
+    pub fn unpacked(&self) -> Val {
+        match self {
+            Storage::Val(val) => *val,
+            Storage::I8 | Storage::I16 => Val::I32,
+        }
+    }
+
+    pub fn is_packed(&self) -> bool {
+        !matches!(self, Storage::Val(_))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Field {
+    pub storage: Storage,
+    pub mt: Mut,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Struct {
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Array {
+    pub field: Field,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Composite {
+    Func(Func),
+    Struct(Struct),
+    Array(Array),
+}
+
+// A type defined in the type section, together with its declared supertypes. Types that are
+// final can not be extended by other types.
+#[derive(Debug, PartialEq)]
+pub struct Sub {
+    pub is_final: bool,
+    pub supers: Vec<idx::Type>,
+    pub composite: Composite,
+}
+
+// A group of types that can refer to each other recursively. Each type in the group gets its own
+// type index, in the order in which they are defined.
+#[derive(Debug, PartialEq)]
+pub struct Rec {
+    pub types: Vec<Sub>,
+}
+
+impl From<Func> for Rec {
+    fn from(func: Func) -> Rec {
+        Rec {
+            types: vec![Sub {
+                is_final: true,
+                supers: vec![],
+                composite: Composite::Func(func),
+            }],
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Table {
     pub lim: Limits,
-    pub elem: Elem,
+    pub elem: Ref,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Mem {
     pub lim: Limits,
+    pub shared: bool,
+    pub addr: Addr,
 }
 
+// The type of the addresses used to access a memory, which is `i64` for 64-bit memories.
+#[derive(Copy, Clone)]
 #[derive(Debug, PartialEq)]
+#[derive(PartialOrd, Ord, Eq)]
+pub enum Addr {
+    I32,
+    I64,
+}
+
+impl From<Addr> for Val {
+    fn from(addr: Addr) -> Val {
+        match addr {
+            Addr::I32 => Val::I32,
+            Addr::I64 => Val::I64,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Mut { Const, Var }
 
 #[derive(Debug, PartialEq)]
 pub struct Global {
     pub mt: Mut,
     pub val: Val,
+}
+
+
+// The type of an exception tag, given by a function type whose parameters are the values
+// carried by the exception.
+#[derive(Debug, PartialEq)]
+pub struct Tag {
+    pub ty: idx::Type,
 }
\ No newline at end of file
@@ -1,24 +1,36 @@
-use crate::validation::{Validate, Result, stacks};
-use crate::structure::{module, ty};
+use crate::validation::{Validate, Result, Features, stacks};
+use crate::structure::{module, instr, idx, ty};
+use std::collections::BTreeSet;
 
 #[derive(Default)]
+#[derive(Clone)]
 pub struct Context<'a> {
-    pub types: Vec<&'a ty::Func>,
+    pub features: Features,
+    pub types: Vec<&'a ty::Sub>,
     pub funcs: Vec<&'a ty::Func>,
+    // the type indices of the functions, which give `ref.func` its precise type
+    pub func_types: Vec<idx::Type>,
     pub tables: Vec<&'a ty::Table>,
     pub mems: Vec<&'a ty::Mem>,
     pub globals: Vec<&'a ty::Global>,
+    pub tags: Vec<&'a ty::Func>,
+    pub elems: Vec<&'a module::Elem>,
+    pub data_count: Option<u32>,
+    pub refs: BTreeSet<u32>,
     pub locals: Vec<&'a ty::Val>,
     pub labels: Vec<&'a ty::RetVal>,
     pub ret: Option<&'a [ty::Val]>,
     pub stacks: stacks::Stacks<'a>,
+    pub instrs: usize,
 }
 
 impl<'a> Context<'a> {
-    pub fn use_types(&mut self, types: &'a Vec<ty::Func>) {
-        for ty in types.iter() {
-            self.types.push(&ty);
+    pub fn use_types(&mut self, types: &'a [ty::Rec]) {
+        for ty in types.iter().flat_map(|rec| rec.types.iter()) {
+            self.types.push(ty);
         }
+        // operands are compared by subtyping, which depends on the defined types
+        self.stacks.types = self.types.clone();
     }
 
     pub fn use_imported_funcs(&mut self, imports: &'a Vec<module::Import>) -> Result<()> {
@@ -26,6 +38,7 @@ impl<'a> Context<'a> {
             if let module::desc::Import::Func(idx) = &import.desc {
                 let ty = idx.validate(self)?;
                 self.funcs.push(ty);
+                self.func_types.push(*idx);
             }
         }
         Ok(())
@@ -55,10 +68,21 @@ impl<'a> Context<'a> {
         }
     }
 
+    pub fn use_imported_tags(&mut self, imports: &'a [module::Import]) -> Result<()> {
+        for import in imports.iter() {
+            if let module::desc::Import::Tag(ty) = &import.desc {
+                let ty = ty.ty.validate(self)?;
+                self.tags.push(ty);
+            }
+        }
+        Ok(())
+    }
+
     pub fn use_funcs(&mut self, funcs: &'a Vec<module::Func>) -> Result<()> {
         for func in funcs.iter() {
             let ty = func.ty.validate(self)?;
             self.funcs.push(ty);
+            self.func_types.push(func.ty);
         }
         Ok(())
     }
@@ -81,6 +105,51 @@ impl<'a> Context<'a> {
         }
     }
 
+    pub fn use_tags(&mut self, tags: &'a [module::Tag]) -> Result<()> {
+        for tag in tags.iter() {
+            let ty = tag.ty.ty.validate(self)?;
+            self.tags.push(ty);
+        }
+        Ok(())
+    }
+
+    pub fn use_elems(&mut self, elems: &'a [module::Elem]) {
+        for elem in elems.iter() {
+            self.elems.push(elem);
+        }
+    }
+
+    pub fn use_data_count(&mut self, data_count: Option<u32>) {
+        self.data_count = data_count;
+    }
+
+    /// Declares the functions referenced outside of function bodies, which are the only ones
+    /// that can be referenced with `ref.func` inside function bodies.
+    pub fn use_refs(&mut self, mdl: &'a module::Module) {
+        for global in mdl.globals.iter() {
+            self.use_const_expr_refs(&global.init);
+        }
+        for elem in mdl.elem.iter() {
+            match &elem.init {
+                module::ElemInit::Funcs(funcs) => self.refs.extend(funcs.iter().map(|idx| idx.0)),
+                module::ElemInit::Exprs(exprs) => exprs.iter().for_each(|expr| self.use_const_expr_refs(expr)),
+            }
+        }
+        for export in mdl.exports.iter() {
+            if let module::desc::Export::Func(idx) = &export.desc {
+                self.refs.insert(idx.0);
+            }
+        }
+    }
+
+    fn use_const_expr_refs(&mut self, expr: &instr::ConstExpr) {
+        for instr in expr.0.iter() {
+            if let instr::Instr::RefFunc(idx) = instr {
+                self.refs.insert(idx.0);
+            }
+        }
+    }
+
     pub fn from(mdl: &'a module::Module) -> Result<Context<'a>> {
         let mut ctx = Context::default();
 
@@ -89,10 +158,15 @@ impl<'a> Context<'a> {
         ctx.use_imported_tables(&mdl.imports);
         ctx.use_imported_mems(&mdl.imports);
         ctx.use_imported_globals(&mdl.imports);
+        ctx.use_imported_tags(&mdl.imports)?;
         ctx.use_funcs(&mdl.funcs)?;
         ctx.use_tables(&mdl.tables);
         ctx.use_mems(&mdl.mems);
         ctx.use_globals(&mdl.globals);
+        ctx.use_tags(&mdl.tags)?;
+        ctx.use_elems(&mdl.elem);
+        ctx.use_data_count(mdl.data_count);
+        ctx.use_refs(mdl);
 
         Ok(ctx)
     }
@@ -1,9 +1,200 @@
 use err_derive::Error;
+use crate::structure::ty;
+use crate::binary;
+use crate::validation::stacks::Operand;
 
-// TODO: Add real meaningful errors here.
-
-#[derive(Debug, Error)]
+#[derive(Debug, Error, PartialEq)]
 pub enum Error {
-    #[error(display = "Unexpected end of file.")]
-    UnexpectedEndOfFile,
+    #[error(display = "Type mismatch: expected `{:?}`, found `{:?}`.", expected, found)]
+    TypeMismatch { expected: Operand, found: Operand },
+
+    #[error(display = "Expected an operand of type `{:?}`, but the operand stack is empty.", expected)]
+    EmptyOperandStack { expected: Operand },
+
+    #[error(display = "Expected {} values on the operand stack at the end of the block, found {}.", expected, found)]
+    UnbalancedOperandStack { expected: usize, found: usize },
+
+    #[error(display = "The control stack is empty.")]
+    EmptyControlStack,
+
+    #[error(display = "Expected an empty control stack at the end of the expression, found {} frames.", frames)]
+    UnbalancedControlStack { frames: usize },
+
+    #[error(display = "Label types do not match: expected `{:?}`, found `{:?}`.", expected, found)]
+    LabelTypeMismatch { expected: Vec<ty::Val>, found: Vec<ty::Val> },
+
+    #[error(display = "Instruction `return` found outside of a function body.")]
+    ReturnOutsideFunction,
+
+    #[error(display = "Unknown type `{}`.", idx)]
+    UnknownType { idx: u32 },
+
+    #[error(display = "Type `{}` is not a {} type.", idx, expected)]
+    UnexpectedCompositeType { idx: u32, expected: &'static str },
+
+    #[error(display = "Type `{}` declares {} supertypes, but at most 1 is allowed.", idx, count)]
+    TooManySuperTypes { idx: u32, count: usize },
+
+    #[error(display = "Type `{}` can not extend type `{}`.", idx, sup)]
+    InvalidSuperType { idx: u32, sup: u32 },
+
+    #[error(display = "Unknown field `{}` of type `{}`.", field, ty)]
+    UnknownField { ty: u32, field: u32 },
+
+    #[error(display = "Field `{}` of type `{}` is immutable and can not be set.", field, ty)]
+    ImmutableField { ty: u32, field: u32 },
+
+    #[error(display = "Field `{}` of type `{}` must be read with a sign extension if and only if it is packed.", field, ty)]
+    PackedFieldMismatch { ty: u32, field: u32 },
+
+    #[error(display = "Type `{}` has fields without a default value.", ty)]
+    NonDefaultableField { ty: u32 },
+
+    #[error(display = "Array type `{}` can not be initialized from data segment `{}`.", ty, data)]
+    InvalidArrayData { ty: u32, data: u32 },
+
+    #[error(display = "Cast target `{:?}` is not a subtype of `{:?}`.", to, from)]
+    InvalidCast { from: ty::Ref, to: ty::Ref },
+
+    #[error(display = "The `{}` feature is not enabled.", feature)]
+    FeatureDisabled { feature: &'static str },
+
+    #[error(display = "Unknown function `{}`.", idx)]
+    UnknownFunc { idx: u32 },
+
+    #[error(display = "Unknown table `{}`.", idx)]
+    UnknownTable { idx: u32 },
+
+    #[error(display = "Unknown memory `{}`.", idx)]
+    UnknownMem { idx: u32 },
+
+    #[error(display = "Unknown global `{}`.", idx)]
+    UnknownGlobal { idx: u32 },
+
+    #[error(display = "Unknown tag `{}`.", idx)]
+    UnknownTag { idx: u32 },
+
+    #[error(display = "Unknown local `{}`.", idx)]
+    UnknownLocal { idx: u32 },
+
+    #[error(display = "Function `{}` is referenced without being declared outside of function bodies.", idx)]
+    UndeclaredFuncRef { idx: u32 },
+
+    #[error(display = "Reference type mismatch: expected `{:?}`, found `{:?}`.", expected, found)]
+    ElemTypeMismatch { expected: ty::Ref, found: ty::Ref },
+
+    #[error(display = "Expected a reference operand, found `{:?}`.", found)]
+    ExpectedReference { found: Operand },
+
+    #[error(display = "Untyped `select` can not be used with operands of type `{:?}`.", found)]
+    InvalidSelectOperand { found: Operand },
+
+    #[error(display = "Typed `select` must have exactly 1 result type, found {}.", count)]
+    InvalidSelectArity { count: usize },
+
+    #[error(display = "Results of tail-called function do not match the current function: expected `{:?}`, found `{:?}`.", expected, found)]
+    TailCallResultMismatch { expected: Vec<ty::Val>, found: Vec<ty::Val> },
+
+    #[error(display = "Invalid alignment for atomic access: expected exactly {}, found {}.", natural, align)]
+    InvalidAtomicAlignment { align: u32, natural: u32 },
+
+    #[error(display = "Tag types must have no results, found `{:?}`.", results)]
+    InvalidTagType { results: Vec<ty::Val> },
+
+    #[error(display = "Label `{}` does not refer to a `catch` block.", idx)]
+    InvalidRethrowLabel { idx: u32 },
+
+    #[error(display = "Shared memories must declare a maximum size.")]
+    SharedMemoryWithoutMax,
+
+    #[error(display = "Lane index {} is out of range for {} lanes.", lane, lanes)]
+    InvalidLaneIndex { lane: u8, lanes: u8 },
+
+    #[error(display = "Unknown element segment `{}`.", idx)]
+    UnknownElem { idx: u32 },
+
+    #[error(display = "Unknown data segment `{}`.", idx)]
+    UnknownData { idx: u32 },
+
+    #[error(display = "Data segments can only be referenced when the data count section is present.")]
+    MissingDataCount,
+
+    #[error(display = "The data count section announces {} data segments, found {}.", count, found)]
+    DataCountMismatch { count: u32, found: usize },
+
+    #[error(display = "Unknown label `{}`.", idx)]
+    UnknownLabel { idx: u32 },
+
+    #[error(display = "Global `{}` is immutable and can not be set.", idx)]
+    ImmutableGlobal { idx: u32 },
+
+    #[error(display = "Alignment 2^{} is larger than the natural alignment 2^{}.", align, natural)]
+    InvalidAlignment { align: u32, natural: u32 },
+
+    #[error(display = "Offset `{}` is out of range for a 32-bit memory.", offset)]
+    OffsetOutOfRange { offset: u64 },
+
+    #[error(display = "Table `{}` does not have element type `funcref`.", idx)]
+    TableNotFuncRef { idx: u32 },
+
+    #[error(display = "Invalid limits: minimum `{}` is larger than maximum `{}`.", min, max)]
+    InvalidLimits { min: u64, max: u64 },
+
+    #[error(display = "Limits out of range: `{}` is larger than `{}`.", max, range)]
+    LimitsOutOfRange { max: u64, range: u64 },
+
+    #[error(display = "Duplicate export name `{}`.", name)]
+    DuplicateExportName { name: String },
+
+    #[error(display = "Start function must have type `[] -> []`, found `{:?} -> {:?}`.", params, results)]
+    InvalidStartFunction { params: Vec<ty::Val>, results: Vec<ty::Val> },
+
+    #[error(display = "Instruction `{}` is not allowed in a constant expression.", instr)]
+    NonConstantInstruction { instr: String },
+
+    #[error(display = "Global `{}` is mutable and can not be used in a constant expression.", idx)]
+    NonConstantGlobal { idx: u32 },
+
+    #[error(display = "Malformed function body. {}", error)]
+    MalformedBody { error: binary::Error },
+
+    /// Wraps an error found while validating a function body, together with its location.
+    #[error(display = "{} At {}.", error, location)]
+    InFunction { location: Location, error: Box<Error> },
+}
+
+impl Error {
+    /// Returns the location of the error within the module, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::InFunction { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+}
+
+/// The location of a validation error within a function body.
+/// 
+/// The `instr` field is the index of the failing instruction within the function body, in the
+/// order in which they appear in the binary encoding (including `else` and `end`).
+/// It is `None` when the error is not caused by an instruction, e.g., an unknown type index.
+#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy)]
+pub struct Location {
+    pub func: u32,
+    pub instr: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "function `{}`", self.func)?;
+        if let Some(instr) = self.instr {
+            write!(fmt, ", instruction `{}`", instr)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(fmt, ", byte offset `{:#x}`", offset)?;
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,721 @@
+//! This module defines the configuration of the WebAssembly proposals accepted by the parser and
+//! the validator.
+//! 
+//! Parsing with `WasmBinary::parse_with` or `SliceBinary::parse_slice_with` rejects the types and
+//! instructions of disabled proposals with a `binary::Error::FeatureDisabled` naming the proposal,
+//! while `WasmBinary::parse` accepts every supported proposal. The bodies of lazily parsed
+//! functions are only checked by the validator, which also rejects the uses of disabled proposals
+//! that depend on the rest of the module, such as multiple memories.
+//! 
+//! ```
+//! # use whasm::validation::{Features, ValidationEntry};
+//! # use whasm::structure::{module, instr, idx, ty};
+//! # use whasm::structure::instr::Instr;
+//! let module = module::Module {
+//!     types: vec![ty::Func { params: vec![ty::Val::I32], results: vec![ty::Val::I32] }.into()],
+//!     funcs: vec![module::Func {
+//!         ty: idx::Type(0),
+//!         locals: vec![],
+//!         body: instr::Expr(vec![Instr::LocalGet(idx::Local(0)), Instr::I32Extend8S]).into(),
+//!     }],
+//!     .. Default::default()
+//! };
+//! assert!(module.validate_with(Features::MVP).is_err());
+//! assert!(module.validate_with(Features { sign_extension: true, ..Features::MVP }).is_ok());
+//! ```
+
+use crate::validation::{Result, Error};
+use crate::structure::{instr::Instr, module, ty};
+
+/// The proposals that a module may use on top of the initial version of WebAssembly.
+/// 
+/// The default is `Features::ALL`.
+#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy)]
+pub struct Features {
+    /// Sign-extension operators.
+    pub sign_extension: bool,
+    /// Non-trapping float-to-int conversions.
+    pub saturating_float_to_int: bool,
+    /// Blocks with parameters and functions with multiple results.
+    pub multi_value: bool,
+    /// Import and export of mutable globals.
+    pub mutable_globals: bool,
+    /// Bulk memory operations and passive segments.
+    pub bulk_memory: bool,
+    /// Reference types, typed `select` and multiple tables.
+    pub reference_types: bool,
+    /// 128-bit vectors.
+    pub simd: bool,
+    /// Relaxed vector instructions, whose results may depend on the platform.
+    pub relaxed_simd: bool,
+    /// Tail calls.
+    pub tail_call: bool,
+    /// Shared memories and atomic memory accesses.
+    pub threads: bool,
+    /// Exception handling with tags, `throw` and `try_table`.
+    pub exceptions: bool,
+    /// The legacy `try`, `catch`, `catch_all`, `delegate` and `rethrow` instructions. Their tags
+    /// and `throw` also need `exceptions`.
+    pub legacy_exceptions: bool,
+    /// Memories with 64-bit addresses.
+    pub memory64: bool,
+    /// Multiple memories.
+    pub multi_memory: bool,
+    /// Integer arithmetic in constant expressions.
+    pub extended_const: bool,
+    /// Typed function references.
+    pub function_references: bool,
+    /// Garbage collection: composite types, subtyping and aggregate instructions.
+    pub gc: bool,
+}
+
+impl Features {
+    /// The initial version of WebAssembly, without any proposals.
+    pub const MVP: Features = Features {
+        sign_extension: false,
+        saturating_float_to_int: false,
+        multi_value: false,
+        mutable_globals: false,
+        bulk_memory: false,
+        reference_types: false,
+        simd: false,
+        relaxed_simd: false,
+        tail_call: false,
+        threads: false,
+        exceptions: false,
+        legacy_exceptions: false,
+        memory64: false,
+        multi_memory: false,
+        extended_const: false,
+        function_references: false,
+        gc: false,
+    };
+
+    /// WebAssembly 2.0, which includes the proposals standardized along with vectors.
+    pub const WASM2: Features = Features {
+        sign_extension: true,
+        saturating_float_to_int: true,
+        multi_value: true,
+        mutable_globals: true,
+        bulk_memory: true,
+        reference_types: true,
+        simd: true,
+        .. Features::MVP
+    };
+
+    /// Every proposal supported by this crate.
+    pub const ALL: Features = Features {
+        sign_extension: true,
+        saturating_float_to_int: true,
+        multi_value: true,
+        mutable_globals: true,
+        bulk_memory: true,
+        reference_types: true,
+        simd: true,
+        relaxed_simd: true,
+        tail_call: true,
+        threads: true,
+        exceptions: true,
+        legacy_exceptions: true,
+        memory64: true,
+        multi_memory: true,
+        extended_const: true,
+        function_references: true,
+        gc: true,
+    };
+
+    /// Checks that the proposal introducing the given value type is enabled.
+    pub(crate) fn check_val(&self, val: ty::Val) -> Result<()> {
+        match val {
+            ty::Val::V128 => require(self.simd, "simd"),
+            ty::Val::Ref(r) => match r.heap {
+                ty::Heap::Func | ty::Heap::Extern if r.nullable => require(self.reference_types, "reference-types"),
+                ty::Heap::Func | ty::Heap::Extern | ty::Heap::Type(_) => require(self.function_references, "function-references"),
+                ty::Heap::Exn | ty::Heap::NoExn => require(self.exceptions, "exceptions"),
+                _ => require(self.gc, "gc"),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the proposal introducing the given block type is enabled.
+    pub(crate) fn check_ret_val(&self, ret: &ty::RetVal) -> Result<()> {
+        match ret {
+            ty::RetVal::Empty => Ok(()),
+            ty::RetVal::Val(val) => self.check_val(*val),
+            ty::RetVal::Type(_) => require(self.multi_value, "multi-value"),
+        }
+    }
+
+    /// Checks that the proposals introducing the types of a recursive group are enabled.
+    pub(crate) fn check_rec(&self, rec: &ty::Rec) -> Result<()> {
+        if rec.types.len() != 1 {
+            require(self.gc, "gc")?;
+        }
+        for sub in rec.types.iter() {
+            if !sub.is_final || !sub.supers.is_empty() {
+                require(self.gc, "gc")?;
+            }
+            match &sub.composite {
+                ty::Composite::Func(func) => {
+                    for val in func.params.iter().chain(func.results.iter()) {
+                        self.check_val(*val)?;
+                    }
+                    if func.results.len() > 1 {
+                        require(self.multi_value, "multi-value")?;
+                    }
+                },
+                _ => require(self.gc, "gc")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the types and instructions that the parser read for the section with the given id,
+    /// which is the last one parsed into `module`.
+    /// 
+    /// The bodies of lazily parsed functions are only checked once they are validated.
+    pub(crate) fn check_section(&self, id: u8, module: &module::Module) -> Result<()> {
+        match id {
+            0x01 => for rec in module.types.iter() {
+                self.check_rec(rec)?;
+            },
+            0x02 => for import in module.imports.iter() {
+                match &import.desc {
+                    module::desc::Import::Table(ty) => self.check_elem(ty.elem)?,
+                    module::desc::Import::Mem(ty) => self.check_mem(ty)?,
+                    module::desc::Import::Global(ty) => self.check_val(ty.val)?,
+                    module::desc::Import::Tag(_) => require(self.exceptions, "exceptions")?,
+                    module::desc::Import::Func(_) => (),
+                }
+            },
+            0x04 => for table in module.tables.iter() {
+                self.check_elem(table.ty.elem)?;
+            },
+            0x05 => for mem in module.mems.iter() {
+                self.check_mem(&mem.ty)?;
+            },
+            0x06 => for global in module.globals.iter() {
+                self.check_val(global.ty.val)?;
+                self.check_expr(&global.init.0)?;
+            },
+            0x09 => for elem in module.elem.iter() {
+                self.check_elem(elem.ty)?;
+                if let module::ElemMode::Active { offset, .. } = &elem.mode {
+                    self.check_expr(&offset.0)?;
+                }
+                if let module::ElemInit::Exprs(exprs) = &elem.init {
+                    for expr in exprs.iter() {
+                        self.check_expr(&expr.0)?;
+                    }
+                }
+            },
+            0x0A => for func in module.funcs.iter() {
+                for local in func.locals.iter() {
+                    self.check_val(*local)?;
+                }
+                if let module::Body::Expr(expr) = &func.body {
+                    self.check_expr(&expr.0)?;
+                }
+            },
+            0x0B => for data in module.data.iter() {
+                if let module::DataMode::Active { offset, .. } = &data.mode {
+                    self.check_expr(&offset.0)?;
+                }
+            },
+            0x0C => require(self.bulk_memory, "bulk-memory")?,
+            0x0D => require(self.exceptions, "exceptions")?,
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Checks the element type of a table or segment, where `funcref` is always allowed.
+    fn check_elem(&self, elem: ty::Ref) -> Result<()> {
+        if elem != ty::Ref::FUNC {
+            self.check_val(elem.into())?;
+        }
+        Ok(())
+    }
+
+    fn check_mem(&self, mem: &ty::Mem) -> Result<()> {
+        if mem.addr == ty::Addr::I64 {
+            require(self.memory64, "memory64")?;
+        }
+        if mem.shared {
+            require(self.threads, "threads")?;
+        }
+        Ok(())
+    }
+
+    /// Checks the instructions of an expression, including the ones nested in blocks.
+    pub(crate) fn check_expr(&self, instrs: &[Instr]) -> Result<()> {
+        for instr in instrs.iter() {
+            self.check_instr(instr)?;
+            match instr {
+                Instr::Block(_, body) | Instr::Loop(_, body) | Instr::TryDelegate(_, body, _) | Instr::TryTable(_, _, body) => {
+                    self.check_expr(&body.0)?;
+                },
+                Instr::If(_, then, otherwise) => {
+                    self.check_expr(&then.0)?;
+                    self.check_expr(&otherwise.0)?;
+                },
+                Instr::Try(_, body, catches, catch_all) => {
+                    self.check_expr(&body.0)?;
+                    for (_, handler) in catches.iter() {
+                        self.check_expr(&handler.0)?;
+                    }
+                    if let Some(handler) = catch_all {
+                        self.check_expr(&handler.0)?;
+                    }
+                },
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the proposal introducing the given instruction is enabled.
+    pub(crate) fn check_instr(&self, instr: &Instr) -> Result<()> {
+        match instr {
+            Instr::Block(ret, _) | Instr::Loop(ret, _) | Instr::If(ret, _, _) => self.check_ret_val(ret),
+            Instr::TryTable(ret, ..) => {
+                self.check_ret_val(ret)?;
+                require(self.exceptions, "exceptions")
+            },
+            Instr::Try(ret, ..) | Instr::TryDelegate(ret, ..) => {
+                self.check_ret_val(ret)?;
+                require(self.legacy_exceptions, "legacy-exceptions")
+            },
+            // Sign extension
+            Instr::I32Extend8S
+            | Instr::I32Extend16S
+            | Instr::I64Extend8S
+            | Instr::I64Extend16S
+            | Instr::I64Extend32S => require(self.sign_extension, "sign-extension"),
+            // Saturating truncation
+            Instr::I32TruncSatF32S
+            | Instr::I32TruncSatF32U
+            | Instr::I32TruncSatF64S
+            | Instr::I32TruncSatF64U
+            | Instr::I64TruncSatF32S
+            | Instr::I64TruncSatF32U
+            | Instr::I64TruncSatF64S
+            | Instr::I64TruncSatF64U => require(self.saturating_float_to_int, "saturating-float-to-int"),
+            // Bulk memory
+            Instr::MemInit(..)
+            | Instr::DataDrop(..)
+            | Instr::MemCopy(..)
+            | Instr::MemFill(..)
+            | Instr::TableInit(..)
+            | Instr::ElemDrop(..)
+            | Instr::TableCopy(..) => require(self.bulk_memory, "bulk-memory"),
+            Instr::SelectTyped(vals) => {
+                vals.iter().try_for_each(|val| self.check_val(*val))?;
+                require(self.reference_types, "reference-types")
+            },
+            Instr::RefNull(heap) => {
+                self.check_val(ty::Val::Ref(ty::Ref { nullable: true, heap: *heap }))?;
+                require(self.reference_types, "reference-types")
+            },
+            // Reference types
+            Instr::TableGet(..)
+            | Instr::TableSet(..)
+            | Instr::RefIsNull
+            | Instr::RefFunc(..)
+            | Instr::TableGrow(..)
+            | Instr::TableSize(..)
+            | Instr::TableFill(..) => require(self.reference_types, "reference-types"),
+            // Tail calls
+            Instr::ReturnCall(..)
+            | Instr::ReturnCallIndirect(..)
+            | Instr::ReturnCallRef(..) => require(self.tail_call, "tail-call"),
+            // Exceptions
+            Instr::Throw(..)
+            | Instr::ThrowRef => require(self.exceptions, "exceptions"),
+            // Legacy exceptions
+            Instr::Catch(..)
+            | Instr::Rethrow(..)
+            | Instr::Delegate(..)
+            | Instr::CatchAll => require(self.legacy_exceptions, "legacy-exceptions"),
+            // Typed function references
+            Instr::CallRef(..)
+            | Instr::RefAsNonNull
+            | Instr::BrOnNull(..)
+            | Instr::BrOnNonNull(..) => require(self.function_references, "function-references"),
+            // Garbage collection
+            Instr::RefEq
+            | Instr::StructNew(..)
+            | Instr::StructNewDefault(..)
+            | Instr::StructGet(..)
+            | Instr::StructGetS(..)
+            | Instr::StructGetU(..)
+            | Instr::StructSet(..)
+            | Instr::ArrayNew(..)
+            | Instr::ArrayNewDefault(..)
+            | Instr::ArrayNewFixed(..)
+            | Instr::ArrayNewData(..)
+            | Instr::ArrayNewElem(..)
+            | Instr::ArrayGet(..)
+            | Instr::ArrayGetS(..)
+            | Instr::ArrayGetU(..)
+            | Instr::ArraySet(..)
+            | Instr::ArrayLen
+            | Instr::ArrayFill(..)
+            | Instr::ArrayCopy(..)
+            | Instr::ArrayInitData(..)
+            | Instr::ArrayInitElem(..)
+            | Instr::RefTest(..)
+            | Instr::RefCast(..)
+            | Instr::BrOnCast(..)
+            | Instr::BrOnCastFail(..)
+            | Instr::AnyConvertExtern
+            | Instr::ExternConvertAny
+            | Instr::RefI31
+            | Instr::I31GetS
+            | Instr::I31GetU => require(self.gc, "gc"),
+            // Vector
+            Instr::V128Load(..)
+            | Instr::V128Load8x8S(..)
+            | Instr::V128Load8x8U(..)
+            | Instr::V128Load16x4S(..)
+            | Instr::V128Load16x4U(..)
+            | Instr::V128Load32x2S(..)
+            | Instr::V128Load32x2U(..)
+            | Instr::V128Load8Splat(..)
+            | Instr::V128Load16Splat(..)
+            | Instr::V128Load32Splat(..)
+            | Instr::V128Load64Splat(..)
+            | Instr::V128Store(..)
+            | Instr::ConstV128(..)
+            | Instr::I8x16Shuffle(..)
+            | Instr::I8x16Swizzle
+            | Instr::I8x16Splat
+            | Instr::I16x8Splat
+            | Instr::I32x4Splat
+            | Instr::I64x2Splat
+            | Instr::F32x4Splat
+            | Instr::F64x2Splat
+            | Instr::I8x16ExtractLaneS(..)
+            | Instr::I8x16ExtractLaneU(..)
+            | Instr::I8x16ReplaceLane(..)
+            | Instr::I16x8ExtractLaneS(..)
+            | Instr::I16x8ExtractLaneU(..)
+            | Instr::I16x8ReplaceLane(..)
+            | Instr::I32x4ExtractLane(..)
+            | Instr::I32x4ReplaceLane(..)
+            | Instr::I64x2ExtractLane(..)
+            | Instr::I64x2ReplaceLane(..)
+            | Instr::F32x4ExtractLane(..)
+            | Instr::F32x4ReplaceLane(..)
+            | Instr::F64x2ExtractLane(..)
+            | Instr::F64x2ReplaceLane(..)
+            | Instr::I8x16Eq
+            | Instr::I8x16Ne
+            | Instr::I8x16LtS
+            | Instr::I8x16LtU
+            | Instr::I8x16GtS
+            | Instr::I8x16GtU
+            | Instr::I8x16LeS
+            | Instr::I8x16LeU
+            | Instr::I8x16GeS
+            | Instr::I8x16GeU
+            | Instr::I16x8Eq
+            | Instr::I16x8Ne
+            | Instr::I16x8LtS
+            | Instr::I16x8LtU
+            | Instr::I16x8GtS
+            | Instr::I16x8GtU
+            | Instr::I16x8LeS
+            | Instr::I16x8LeU
+            | Instr::I16x8GeS
+            | Instr::I16x8GeU
+            | Instr::I32x4Eq
+            | Instr::I32x4Ne
+            | Instr::I32x4LtS
+            | Instr::I32x4LtU
+            | Instr::I32x4GtS
+            | Instr::I32x4GtU
+            | Instr::I32x4LeS
+            | Instr::I32x4LeU
+            | Instr::I32x4GeS
+            | Instr::I32x4GeU
+            | Instr::F32x4Eq
+            | Instr::F32x4Ne
+            | Instr::F32x4Lt
+            | Instr::F32x4Gt
+            | Instr::F32x4Le
+            | Instr::F32x4Ge
+            | Instr::F64x2Eq
+            | Instr::F64x2Ne
+            | Instr::F64x2Lt
+            | Instr::F64x2Gt
+            | Instr::F64x2Le
+            | Instr::F64x2Ge
+            | Instr::V128Not
+            | Instr::V128And
+            | Instr::V128AndNot
+            | Instr::V128Or
+            | Instr::V128Xor
+            | Instr::V128Bitselect
+            | Instr::V128AnyTrue
+            | Instr::V128Load8Lane(..)
+            | Instr::V128Load16Lane(..)
+            | Instr::V128Load32Lane(..)
+            | Instr::V128Load64Lane(..)
+            | Instr::V128Store8Lane(..)
+            | Instr::V128Store16Lane(..)
+            | Instr::V128Store32Lane(..)
+            | Instr::V128Store64Lane(..)
+            | Instr::V128Load32Zero(..)
+            | Instr::V128Load64Zero(..)
+            | Instr::F32x4DemoteF64x2Zero
+            | Instr::F64x2PromoteLowF32x4
+            | Instr::I8x16Abs
+            | Instr::I8x16Neg
+            | Instr::I8x16Popcnt
+            | Instr::I8x16AllTrue
+            | Instr::I8x16Bitmask
+            | Instr::I8x16NarrowI16x8S
+            | Instr::I8x16NarrowI16x8U
+            | Instr::F32x4Ceil
+            | Instr::F32x4Floor
+            | Instr::F32x4Trunc
+            | Instr::F32x4Nearest
+            | Instr::I8x16Shl
+            | Instr::I8x16ShrS
+            | Instr::I8x16ShrU
+            | Instr::I8x16Add
+            | Instr::I8x16AddSatS
+            | Instr::I8x16AddSatU
+            | Instr::I8x16Sub
+            | Instr::I8x16SubSatS
+            | Instr::I8x16SubSatU
+            | Instr::F64x2Ceil
+            | Instr::F64x2Floor
+            | Instr::I8x16MinS
+            | Instr::I8x16MinU
+            | Instr::I8x16MaxS
+            | Instr::I8x16MaxU
+            | Instr::F64x2Trunc
+            | Instr::I8x16AvgrU
+            | Instr::I16x8ExtaddPairwiseI8x16S
+            | Instr::I16x8ExtaddPairwiseI8x16U
+            | Instr::I32x4ExtaddPairwiseI16x8S
+            | Instr::I32x4ExtaddPairwiseI16x8U
+            | Instr::I16x8Abs
+            | Instr::I16x8Neg
+            | Instr::I16x8Q15mulrSatS
+            | Instr::I16x8AllTrue
+            | Instr::I16x8Bitmask
+            | Instr::I16x8NarrowI32x4S
+            | Instr::I16x8NarrowI32x4U
+            | Instr::I16x8ExtendLowI8x16S
+            | Instr::I16x8ExtendHighI8x16S
+            | Instr::I16x8ExtendLowI8x16U
+            | Instr::I16x8ExtendHighI8x16U
+            | Instr::I16x8Shl
+            | Instr::I16x8ShrS
+            | Instr::I16x8ShrU
+            | Instr::I16x8Add
+            | Instr::I16x8AddSatS
+            | Instr::I16x8AddSatU
+            | Instr::I16x8Sub
+            | Instr::I16x8SubSatS
+            | Instr::I16x8SubSatU
+            | Instr::F64x2Nearest
+            | Instr::I16x8Mul
+            | Instr::I16x8MinS
+            | Instr::I16x8MinU
+            | Instr::I16x8MaxS
+            | Instr::I16x8MaxU
+            | Instr::I16x8AvgrU
+            | Instr::I16x8ExtmulLowI8x16S
+            | Instr::I16x8ExtmulHighI8x16S
+            | Instr::I16x8ExtmulLowI8x16U
+            | Instr::I16x8ExtmulHighI8x16U
+            | Instr::I32x4Abs
+            | Instr::I32x4Neg
+            | Instr::I32x4AllTrue
+            | Instr::I32x4Bitmask
+            | Instr::I32x4ExtendLowI16x8S
+            | Instr::I32x4ExtendHighI16x8S
+            | Instr::I32x4ExtendLowI16x8U
+            | Instr::I32x4ExtendHighI16x8U
+            | Instr::I32x4Shl
+            | Instr::I32x4ShrS
+            | Instr::I32x4ShrU
+            | Instr::I32x4Add
+            | Instr::I32x4Sub
+            | Instr::I32x4Mul
+            | Instr::I32x4MinS
+            | Instr::I32x4MinU
+            | Instr::I32x4MaxS
+            | Instr::I32x4MaxU
+            | Instr::I32x4DotI16x8S
+            | Instr::I32x4ExtmulLowI16x8S
+            | Instr::I32x4ExtmulHighI16x8S
+            | Instr::I32x4ExtmulLowI16x8U
+            | Instr::I32x4ExtmulHighI16x8U
+            | Instr::I64x2Abs
+            | Instr::I64x2Neg
+            | Instr::I64x2AllTrue
+            | Instr::I64x2Bitmask
+            | Instr::I64x2ExtendLowI32x4S
+            | Instr::I64x2ExtendHighI32x4S
+            | Instr::I64x2ExtendLowI32x4U
+            | Instr::I64x2ExtendHighI32x4U
+            | Instr::I64x2Shl
+            | Instr::I64x2ShrS
+            | Instr::I64x2ShrU
+            | Instr::I64x2Add
+            | Instr::I64x2Sub
+            | Instr::I64x2Mul
+            | Instr::I64x2Eq
+            | Instr::I64x2Ne
+            | Instr::I64x2LtS
+            | Instr::I64x2GtS
+            | Instr::I64x2LeS
+            | Instr::I64x2GeS
+            | Instr::I64x2ExtmulLowI32x4S
+            | Instr::I64x2ExtmulHighI32x4S
+            | Instr::I64x2ExtmulLowI32x4U
+            | Instr::I64x2ExtmulHighI32x4U
+            | Instr::F32x4Abs
+            | Instr::F32x4Neg
+            | Instr::F32x4Sqrt
+            | Instr::F32x4Add
+            | Instr::F32x4Sub
+            | Instr::F32x4Mul
+            | Instr::F32x4Div
+            | Instr::F32x4Min
+            | Instr::F32x4Max
+            | Instr::F32x4Pmin
+            | Instr::F32x4Pmax
+            | Instr::F64x2Abs
+            | Instr::F64x2Neg
+            | Instr::F64x2Sqrt
+            | Instr::F64x2Add
+            | Instr::F64x2Sub
+            | Instr::F64x2Mul
+            | Instr::F64x2Div
+            | Instr::F64x2Min
+            | Instr::F64x2Max
+            | Instr::F64x2Pmin
+            | Instr::F64x2Pmax
+            | Instr::I32x4TruncSatF32x4S
+            | Instr::I32x4TruncSatF32x4U
+            | Instr::F32x4ConvertI32x4S
+            | Instr::F32x4ConvertI32x4U
+            | Instr::I32x4TruncSatF64x2SZero
+            | Instr::I32x4TruncSatF64x2UZero
+            | Instr::F64x2ConvertLowI32x4S
+            | Instr::F64x2ConvertLowI32x4U => require(self.simd, "simd"),
+            // Relaxed vector
+            Instr::I8x16RelaxedSwizzle
+            | Instr::I32x4RelaxedTruncF32x4S
+            | Instr::I32x4RelaxedTruncF32x4U
+            | Instr::I32x4RelaxedTruncF64x2SZero
+            | Instr::I32x4RelaxedTruncF64x2UZero
+            | Instr::F32x4RelaxedMadd
+            | Instr::F32x4RelaxedNmadd
+            | Instr::F64x2RelaxedMadd
+            | Instr::F64x2RelaxedNmadd
+            | Instr::I8x16RelaxedLaneselect
+            | Instr::I16x8RelaxedLaneselect
+            | Instr::I32x4RelaxedLaneselect
+            | Instr::I64x2RelaxedLaneselect
+            | Instr::F32x4RelaxedMin
+            | Instr::F32x4RelaxedMax
+            | Instr::F64x2RelaxedMin
+            | Instr::F64x2RelaxedMax
+            | Instr::I16x8RelaxedQ15mulrS
+            | Instr::I16x8RelaxedDotI8x16I7x16S
+            | Instr::I32x4RelaxedDotI8x16I7x16AddS => require(self.relaxed_simd, "relaxed-simd"),
+            // Atomic memory
+            Instr::MemAtomicNotify(..)
+            | Instr::MemAtomicWait32(..)
+            | Instr::MemAtomicWait64(..)
+            | Instr::AtomicFence
+            | Instr::I32AtomicLoad(..)
+            | Instr::I64AtomicLoad(..)
+            | Instr::I32AtomicLoad8U(..)
+            | Instr::I32AtomicLoad16U(..)
+            | Instr::I64AtomicLoad8U(..)
+            | Instr::I64AtomicLoad16U(..)
+            | Instr::I64AtomicLoad32U(..)
+            | Instr::I32AtomicStore(..)
+            | Instr::I64AtomicStore(..)
+            | Instr::I32AtomicStore8(..)
+            | Instr::I32AtomicStore16(..)
+            | Instr::I64AtomicStore8(..)
+            | Instr::I64AtomicStore16(..)
+            | Instr::I64AtomicStore32(..)
+            | Instr::I32AtomicRmwAdd(..)
+            | Instr::I64AtomicRmwAdd(..)
+            | Instr::I32AtomicRmw8AddU(..)
+            | Instr::I32AtomicRmw16AddU(..)
+            | Instr::I64AtomicRmw8AddU(..)
+            | Instr::I64AtomicRmw16AddU(..)
+            | Instr::I64AtomicRmw32AddU(..)
+            | Instr::I32AtomicRmwSub(..)
+            | Instr::I64AtomicRmwSub(..)
+            | Instr::I32AtomicRmw8SubU(..)
+            | Instr::I32AtomicRmw16SubU(..)
+            | Instr::I64AtomicRmw8SubU(..)
+            | Instr::I64AtomicRmw16SubU(..)
+            | Instr::I64AtomicRmw32SubU(..)
+            | Instr::I32AtomicRmwAnd(..)
+            | Instr::I64AtomicRmwAnd(..)
+            | Instr::I32AtomicRmw8AndU(..)
+            | Instr::I32AtomicRmw16AndU(..)
+            | Instr::I64AtomicRmw8AndU(..)
+            | Instr::I64AtomicRmw16AndU(..)
+            | Instr::I64AtomicRmw32AndU(..)
+            | Instr::I32AtomicRmwOr(..)
+            | Instr::I64AtomicRmwOr(..)
+            | Instr::I32AtomicRmw8OrU(..)
+            | Instr::I32AtomicRmw16OrU(..)
+            | Instr::I64AtomicRmw8OrU(..)
+            | Instr::I64AtomicRmw16OrU(..)
+            | Instr::I64AtomicRmw32OrU(..)
+            | Instr::I32AtomicRmwXor(..)
+            | Instr::I64AtomicRmwXor(..)
+            | Instr::I32AtomicRmw8XorU(..)
+            | Instr::I32AtomicRmw16XorU(..)
+            | Instr::I64AtomicRmw8XorU(..)
+            | Instr::I64AtomicRmw16XorU(..)
+            | Instr::I64AtomicRmw32XorU(..)
+            | Instr::I32AtomicRmwXchg(..)
+            | Instr::I64AtomicRmwXchg(..)
+            | Instr::I32AtomicRmw8XchgU(..)
+            | Instr::I32AtomicRmw16XchgU(..)
+            | Instr::I64AtomicRmw8XchgU(..)
+            | Instr::I64AtomicRmw16XchgU(..)
+            | Instr::I64AtomicRmw32XchgU(..)
+            | Instr::I32AtomicRmwCmpxchg(..)
+            | Instr::I64AtomicRmwCmpxchg(..)
+            | Instr::I32AtomicRmw8CmpxchgU(..)
+            | Instr::I32AtomicRmw16CmpxchgU(..)
+            | Instr::I64AtomicRmw8CmpxchgU(..)
+            | Instr::I64AtomicRmw16CmpxchgU(..)
+            | Instr::I64AtomicRmw32CmpxchgU(..) => require(self.threads, "threads"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Features {
+    fn default() -> Features {
+        Features::ALL
+    }
+}
+
+/// Returns an error naming `feature` unless it is enabled.
+pub(crate) fn require(enabled: bool, feature: &'static str) -> Result<()> {
+    if !enabled {
+        return Err(Error::FeatureDisabled { feature });
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -1,14 +1,17 @@
 use crate::validation::{Validate, Context, Result, Error, stacks::Frame};
-use crate::structure::{idx, ty};
+use crate::structure::{idx, module, ty};
 
 impl<'a> Validate<'a> for idx::Type {
     type ValidationResult = &'a ty::Func;
     fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         let idx = self.0 as usize;
         if idx >= ctx.types.len() {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnknownType { idx: self.0 });
+        }
+        match &ctx.types[idx].composite {
+            ty::Composite::Func(func) => Ok(func),
+            _ => Err(Error::UnexpectedCompositeType { idx: self.0, expected: "function" }),
         }
-        Ok(ctx.types[idx])
     }
 }
 
@@ -17,7 +20,7 @@ impl<'a> Validate<'a> for idx::Func {
     fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         let idx = self.0 as usize;
         if idx >= ctx.funcs.len() {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnknownFunc { idx: self.0 });
         }
         Ok(ctx.funcs[idx])
     }
@@ -28,7 +31,7 @@ impl<'a> Validate<'a> for idx::Table {
     fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         let idx = self.0 as usize;
         if idx >= ctx.tables.len() {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnknownTable { idx: self.0 });
         }
         Ok(ctx.tables[idx])
     }
@@ -39,7 +42,7 @@ impl<'a> Validate<'a> for idx::Mem {
     fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         let idx = self.0 as usize;
         if idx >= ctx.mems.len() {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnknownMem { idx: self.0 });
         }
         Ok(ctx.mems[idx])
     }
@@ -50,18 +53,51 @@ impl<'a> Validate<'a> for idx::Global {
     fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         let idx = self.0 as usize;
         if idx >= ctx.globals.len() {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnknownGlobal { idx: self.0 });
         }
         Ok(ctx.globals[idx])
     }
 }
 
+impl<'a> Validate<'a> for idx::Elem {
+    type ValidationResult = &'a module::Elem;
+    fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        let idx = self.0 as usize;
+        if idx >= ctx.elems.len() {
+            return Err(Error::UnknownElem { idx: self.0 });
+        }
+        Ok(ctx.elems[idx])
+    }
+}
+
+impl<'a> Validate<'a> for idx::Data {
+    type ValidationResult = ();
+    fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        let count = ctx.data_count.ok_or(Error::MissingDataCount)?;
+        if self.0 >= count {
+            return Err(Error::UnknownData { idx: self.0 });
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Validate<'a> for idx::Tag {
+    type ValidationResult = &'a ty::Func;
+    fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        let idx = self.0 as usize;
+        if idx >= ctx.tags.len() {
+            return Err(Error::UnknownTag { idx: self.0 });
+        }
+        Ok(ctx.tags[idx])
+    }
+}
+
 impl<'a> Validate<'a> for idx::Local {
     type ValidationResult = &'a ty::Val;
     fn validate(&self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         let idx = self.0 as usize;
         if idx >= ctx.locals.len() {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnknownLocal { idx: self.0 });
         }
         Ok(ctx.locals[idx])
     }
@@ -75,7 +111,7 @@ impl<'a> Validate<'a> for idx::Label {
         // since the base frame can not be indexed with
         // a label.
         if !( idx + 1 < ctx.stacks.frames.len() ) {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnknownLabel { idx: self.0 });
         }
         let idx = ctx.stacks.frames.len() - idx - 1;
         Ok(ctx.stacks.frames[idx])
@@ -1,4 +1,4 @@
-use crate::validation::{Validate, Context, Result, Error, stacks};
+use crate::validation::{Validate, Context, Result, Error, stacks, features::require, ty::{is_subtype, top_heap}};
 use crate::structure::{idx, instr, ty};
 use crate::structure::instr::Instr;
 
@@ -23,14 +23,21 @@ impl<'a> Validate<'a> for instr::ConstExpr {
                 Instr::ConstI64(_) => (),
                 Instr::ConstF32(_) => (),
                 Instr::ConstF64(_) => (),
+                Instr::RefNull(_) => (),
+                Instr::RefFunc(_) => (),
+                Instr::StructNew(_) | Instr::StructNewDefault(_) => (),
+                Instr::ArrayNew(_) | Instr::ArrayNewDefault(_) | Instr::ArrayNewFixed(_, _) => (),
+                Instr::RefI31 | Instr::AnyConvertExtern | Instr::ExternConvertAny => (),
+                Instr::I32Add | Instr::I32Sub | Instr::I32Mul => require(ctx.features.extended_const, "extended-const")?,
+                Instr::I64Add | Instr::I64Sub | Instr::I64Mul => require(ctx.features.extended_const, "extended-const")?,
                 Instr::GlobalGet(idx) => {
                     let ty = idx.validate(ctx)?;
                     if ty.mt != ty::Mut::Const {
-                        return Err(Error::UnexpectedEndOfFile);
+                        return Err(Error::NonConstantGlobal { idx: idx.0 });
                     }
                 }
                 _ => {
-                    return Err(Error::UnexpectedEndOfFile);
+                    return Err(Error::NonConstantInstruction { instr: format!("{:?}", instr) });
                 }
             };
             instr.validate(ctx)?
@@ -42,6 +49,8 @@ impl<'a> Validate<'a> for instr::ConstExpr {
 impl<'a> Validate<'a> for instr::Instr {
     type ValidationResult = ();
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        ctx.instrs += 1;
+        ctx.features.check_instr(self)?;
         match self {
             // Control flow
             /* 0x00 */ Instr::Unreachable => {
@@ -49,34 +58,90 @@ impl<'a> Validate<'a> for instr::Instr {
             },
             /* 0x01 */ Instr::Nop => {},
             /* 0x02 */ Instr::Block(ret, expr) => {
-                let ret = ret.validate(ctx)?;
-                ctx.stacks.push_frame(ret, ret);
+                let (params, results) = ret.validate(ctx)?;
+                ctx.stacks.pop_operands(params)?;
+                ctx.stacks.push_frame(results, params, results);
                 expr.validate(ctx)?;
                 Instr::End.validate(ctx)?;
             },
             /* 0x03 */ Instr::Loop(ret, expr) => {
-                let ret = ret.validate(ctx)?;
-                ctx.stacks.push_frame(&[], ret);
+                let (params, results) = ret.validate(ctx)?;
+                ctx.stacks.pop_operands(params)?;
+                ctx.stacks.push_frame(params, params, results);
                 expr.validate(ctx)?;
                 Instr::End.validate(ctx)?;
             },
             /* 0x04 */ Instr::If(ret, expr1, expr2) => {
-                let ret = ret.validate(ctx)?;
+                let (params, results) = ret.validate(ctx)?;
                 ctx.stacks.pop_operands(&[ty::Val::I32])?;
-                ctx.stacks.push_frame(ret, ret);
+                ctx.stacks.pop_operands(params)?;
+                ctx.stacks.push_frame(results, params, results);
                 expr1.validate(ctx)?;
-                Instr::Else.validate(ctx)?;
-                expr2.validate(ctx)?;
+                if expr2.0.is_empty() {
+                    // the `else` opcode is not encoded when the else branch is empty
+                    let frame = ctx.stacks.pop_frame()?;
+                    ctx.stacks.push_frame(frame.label, frame.start, frame.out);
+                } else {
+                    Instr::Else.validate(ctx)?;
+                    expr2.validate(ctx)?;
+                }
                 Instr::End.validate(ctx)?;
             },
             /* 0x05 */ Instr::Else => {
-                let ret = ctx.stacks.pop_frame()?;
-                ctx.stacks.push_frame(ret, ret);
+                let frame = ctx.stacks.pop_frame()?;
+                ctx.stacks.push_frame(frame.label, frame.start, frame.out);
+            }
+            /* 0x06 */ Instr::Try(ret, body, catches, catch_all) => {
+                let (params, results) = ret.validate(ctx)?;
+                ctx.stacks.pop_operands(params)?;
+                ctx.stacks.push_frame(results, params, results);
+                body.validate(ctx)?;
+                for (tag, expr) in catches.iter() {
+                    // the `catch` opcode
+                    ctx.instrs += 1;
+                    let ty = tag.validate(ctx)?;
+                    validate_handler(ctx, &ty.params)?;
+                    expr.validate(ctx)?;
+                }
+                if let Some(expr) = catch_all {
+                    Instr::CatchAll.validate(ctx)?;
+                    expr.validate(ctx)?;
+                }
+                Instr::End.validate(ctx)?;
+            }
+            /* 0x06 */ Instr::TryDelegate(ret, body, label) => {
+                let (params, results) = ret.validate(ctx)?;
+                ctx.stacks.pop_operands(params)?;
+                ctx.stacks.push_frame(results, params, results);
+                body.validate(ctx)?;
+                // the `delegate` opcode
+                ctx.instrs += 1;
+                validate_delegate(ctx, label)?;
+            }
+            /* 0x07 */ Instr::Catch(tag) => {
+                let ty = tag.validate(ctx)?;
+                validate_handler(ctx, &ty.params)?;
+            }
+            /* 0x08 */ Instr::Throw(tag) => {
+                let ty = tag.validate(ctx)?;
+                ctx.stacks.pop_operands(&ty.params)?;
+                ctx.stacks.unreachable()?;
+            }
+            /* 0x09 */ Instr::Rethrow(label) => {
+                let frame = label.validate(ctx)?;
+                if !frame.catch {
+                    return Err(Error::InvalidRethrowLabel { idx: label.0 });
+                }
+                ctx.stacks.unreachable()?;
+            }
+            /* 0x0A */ Instr::ThrowRef => {
+                ctx.stacks.pop_operands(&[ty::Val::EXN_REF])?;
+                ctx.stacks.unreachable()?;
             }
             // ... reserved ...
             /* 0x0B */ Instr::End => {
-                let ret = ctx.stacks.pop_frame()?;
-                ctx.stacks.push_operands(&ret[..]);
+                let frame = ctx.stacks.pop_frame()?;
+                ctx.stacks.push_operands(frame.out);
             }
             /* 0x0C */ Instr::Br(label) => {
                 let frame = label.validate(ctx)?;
@@ -91,18 +156,20 @@ impl<'a> Validate<'a> for instr::Instr {
             }
             /* 0x0E */ Instr::BrTable(idxs, label) => {
                 let frame = label.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                // the targets only need the same arity, as long as the operands match each of them
                 for lbl in idxs.iter() {
                     let frm = lbl.validate(ctx)?;
-                    if frame.label != frm.label {
-                        return Err(Error::UnexpectedEndOfFile);
+                    if frame.label.len() != frm.label.len() {
+                        return Err(Error::LabelTypeMismatch { expected: frame.label.to_vec(), found: frm.label.to_vec() });
                     }
+                    ctx.stacks.check_operands(frm.label)?;
                 }
-                ctx.stacks.pop_operands(&[ty::Val::I32])?;
                 ctx.stacks.pop_operands(frame.label)?;
                 ctx.stacks.unreachable()?;
             }
             /* 0x0F */ Instr::Return => {
-                let ret = ctx.ret.ok_or(Error::UnexpectedEndOfFile)?;
+                let ret = ctx.ret.ok_or(Error::ReturnOutsideFunction)?;
                 ctx.stacks.pop_operands(&ret[..])?;
                 ctx.stacks.unreachable()?;
             }
@@ -111,17 +178,47 @@ impl<'a> Validate<'a> for instr::Instr {
                 ctx.stacks.pop_operands(&func.params[..])?;
                 ctx.stacks.push_operands(&func.results[..]);
             }
-            /* 0x11 */ Instr::CallIndirect(func,table) => {
-                let table = table.validate(ctx)?;
+            /* 0x11 */ Instr::CallIndirect(func, table_idx) => {
+                let table = table_idx.validate(ctx)?;
+                let func = func.validate(ctx)?;
+                if !is_subtype(&ctx.types, table.elem.into(), ty::Val::FUNC_REF) {
+                    return Err(Error::TableNotFuncRef { idx: table_idx.0 });
+                }
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.pop_operands(&func.params[..])?;
+                ctx.stacks.push_operands(&func.results[..]);
+            }
+            /* 0x12 */ Instr::ReturnCall(func) => {
+                let func = func.validate(ctx)?;
+                validate_tail_call(ctx, func)?;
+            }
+            /* 0x13 */ Instr::ReturnCallIndirect(func, table_idx) => {
+                let table = table_idx.validate(ctx)?;
                 let func = func.validate(ctx)?;
-                if table.elem != ty::Elem::FuncRef {
-                    return Err(Error::UnexpectedEndOfFile);
+                if !is_subtype(&ctx.types, table.elem.into(), ty::Val::FUNC_REF) {
+                    return Err(Error::TableNotFuncRef { idx: table_idx.0 });
                 }
                 ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                validate_tail_call(ctx, func)?;
+            }
+            /* 0x14 */ Instr::CallRef(ty_idx) => {
+                let func = ty_idx.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx))])?;
                 ctx.stacks.pop_operands(&func.params[..])?;
                 ctx.stacks.push_operands(&func.results[..]);
             }
+            /* 0x15 */ Instr::ReturnCallRef(ty_idx) => {
+                let func = ty_idx.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx))])?;
+                validate_tail_call(ctx, func)?;
+            }
             // ... reserved ...
+            /* 0x18 */ Instr::Delegate(label) => {
+                validate_delegate(ctx, label)?;
+            }
+            /* 0x19 */ Instr::CatchAll => {
+                validate_handler(ctx, &[])?;
+            }
             // Parametric
             /* 0x1A */ Instr::Drop => {
                 ctx.stacks.pop_operand(stacks::Operand::Unknown)?;
@@ -130,9 +227,35 @@ impl<'a> Validate<'a> for instr::Instr {
                 ctx.stacks.pop_operands(&[ty::Val::I32])?;
                 let t1 = ctx.stacks.pop_operand(stacks::Operand::Unknown)?;
                 let t2 = ctx.stacks.pop_operand(t1)?;
+                if let stacks::Operand::Val(val) = t2 {
+                    if val.is_ref() {
+                        return Err(Error::InvalidSelectOperand { found: t2 });
+                    }
+                }
                 ctx.stacks.push_operand(t2);
             }
+            /* 0x1C */ Instr::SelectTyped(vals) => {
+                if vals.len() != 1 {
+                    return Err(Error::InvalidSelectArity { count: vals.len() });
+                }
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.pop_operands(vals)?;
+                ctx.stacks.pop_operands(vals)?;
+                ctx.stacks.push_operands(vals);
+            }
             // ... reserved ...
+            // Exceptions
+            /* 0x1F */ Instr::TryTable(ret, catches, expr) => {
+                let (params, results) = ret.validate(ctx)?;
+                // the labels of the catch clauses are resolved outside of the block
+                for catch in catches.iter() {
+                    catch.validate(ctx)?;
+                }
+                ctx.stacks.pop_operands(params)?;
+                ctx.stacks.push_frame(results, params, results);
+                expr.validate(ctx)?;
+                Instr::End.validate(ctx)?;
+            }
             // Variable
             /* 0x20 */ Instr::LocalGet(idx) => {
                 let ty = idx.validate(ctx)?;
@@ -154,91 +277,101 @@ impl<'a> Validate<'a> for instr::Instr {
             /* 0x24 */ Instr::GlobalSet(idx) => {
                 let ty = idx.validate(ctx)?;
                 if ty.mt != ty::Mut::Var {
-                    return Err(Error::UnexpectedEndOfFile);
+                    return Err(Error::ImmutableGlobal { idx: idx.0 });
                 }
                 ctx.stacks.pop_operands(&[ty.val])?;
             }
+            // Table
+            /* 0x25 */ Instr::TableGet(idx) => {
+                let ty = idx.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty.elem.into()]);
+            }
+            /* 0x26 */ Instr::TableSet(idx) => {
+                let ty = idx.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ty::Val::I32, ty.elem.into()])?;
+            }
             // ... reserved ...
             // Memory load
-            /* 0x28 */ Instr::I32Load(_offset, align) => {
-                validate_load(ctx, *align, 32, ty::Val::I32)?;
+            /* 0x28 */ Instr::I32Load(memarg) => {
+                validate_load(ctx, memarg, 32, ty::Val::I32)?;
             }
-            /* 0x29 */ Instr::I64Load(_offset, align) => {
-                validate_load(ctx, *align, 64, ty::Val::I64)?;
+            /* 0x29 */ Instr::I64Load(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::I64)?;
             }
-            /* 0x2A */ Instr::F32Load(_offset, align) => {
-                validate_load(ctx, *align, 32, ty::Val::F32)?;
+            /* 0x2A */ Instr::F32Load(memarg) => {
+                validate_load(ctx, memarg, 32, ty::Val::F32)?;
             }
-            /* 0x2B */ Instr::F64Load(_offset, align) => {
-                validate_load(ctx, *align, 64, ty::Val::F64)?;
+            /* 0x2B */ Instr::F64Load(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::F64)?;
             }
-            /* 0x2C */ Instr::I32Load8S(_offset, align) => {
-                validate_load(ctx, *align, 8, ty::Val::I32)?;
+            /* 0x2C */ Instr::I32Load8S(memarg) => {
+                validate_load(ctx, memarg, 8, ty::Val::I32)?;
             }
-            /* 0x2D */ Instr::I32Load8U(_offset, align) => {
-                validate_load(ctx, *align, 8, ty::Val::I32)?;
+            /* 0x2D */ Instr::I32Load8U(memarg) => {
+                validate_load(ctx, memarg, 8, ty::Val::I32)?;
             }
-            /* 0x2E */ Instr::I32Load16S(_offset, align) => {
-                validate_load(ctx, *align, 16, ty::Val::I32)?;
+            /* 0x2E */ Instr::I32Load16S(memarg) => {
+                validate_load(ctx, memarg, 16, ty::Val::I32)?;
             }
-            /* 0x2F */ Instr::I32Load16U(_offset, align) => {
-                validate_load(ctx, *align, 16, ty::Val::I32)?;
+            /* 0x2F */ Instr::I32Load16U(memarg) => {
+                validate_load(ctx, memarg, 16, ty::Val::I32)?;
             }
-            /* 0x30 */ Instr::I64Load8S(_offset, align) => {
-                validate_load(ctx, *align, 8, ty::Val::I64)?;
+            /* 0x30 */ Instr::I64Load8S(memarg) => {
+                validate_load(ctx, memarg, 8, ty::Val::I64)?;
             }
-            /* 0x31 */ Instr::I64Load8U(_offset, align) => {
-                validate_load(ctx, *align, 8, ty::Val::I64)?;
+            /* 0x31 */ Instr::I64Load8U(memarg) => {
+                validate_load(ctx, memarg, 8, ty::Val::I64)?;
             }
-            /* 0x32 */ Instr::I64Load16S(_offset, align) => {
-                validate_load(ctx, *align, 16, ty::Val::I64)?;
+            /* 0x32 */ Instr::I64Load16S(memarg) => {
+                validate_load(ctx, memarg, 16, ty::Val::I64)?;
             }
-            /* 0x33 */ Instr::I64Load16U(_offset, align) => {
-                validate_load(ctx, *align, 16, ty::Val::I64)?;
+            /* 0x33 */ Instr::I64Load16U(memarg) => {
+                validate_load(ctx, memarg, 16, ty::Val::I64)?;
             }
-            /* 0x34 */ Instr::I64Load32S(_offset, align) => {
-                validate_load(ctx, *align, 32, ty::Val::I64)?;
+            /* 0x34 */ Instr::I64Load32S(memarg) => {
+                validate_load(ctx, memarg, 32, ty::Val::I64)?;
             }
-            /* 0x35 */ Instr::I64Load32U(_offset, align) => {
-                validate_load(ctx, *align, 32, ty::Val::I64)?;
+            /* 0x35 */ Instr::I64Load32U(memarg) => {
+                validate_load(ctx, memarg, 32, ty::Val::I64)?;
             }
             // Memory store
-            /* 0x36 */ Instr::I32Store(_offset, align) => {
-                validate_store(ctx, *align, 32, ty::Val::I32)?;
+            /* 0x36 */ Instr::I32Store(memarg) => {
+                validate_store(ctx, memarg, 32, ty::Val::I32)?;
             }
-            /* 0x37 */ Instr::I64Store(_offset, align) => {
-                validate_store(ctx, *align, 64, ty::Val::I64)?;
+            /* 0x37 */ Instr::I64Store(memarg) => {
+                validate_store(ctx, memarg, 64, ty::Val::I64)?;
             }
-            /* 0x38 */ Instr::F32Store(_offset, align) => {
-                validate_store(ctx, *align, 32, ty::Val::F32)?;
+            /* 0x38 */ Instr::F32Store(memarg) => {
+                validate_store(ctx, memarg, 32, ty::Val::F32)?;
             }
-            /* 0x39 */ Instr::F64Store(_offset, align) => {
-                validate_store(ctx, *align, 64, ty::Val::F64)?;
+            /* 0x39 */ Instr::F64Store(memarg) => {
+                validate_store(ctx, memarg, 64, ty::Val::F64)?;
             }
-            /* 0x3A */ Instr::I32Store8(_offset, align) => {
-                validate_store(ctx, *align, 8, ty::Val::I32)?;
+            /* 0x3A */ Instr::I32Store8(memarg) => {
+                validate_store(ctx, memarg, 8, ty::Val::I32)?;
             }
-            /* 0x3B */ Instr::I32Store16(_offset, align) => {
-                validate_store(ctx, *align, 16, ty::Val::I32)?;
+            /* 0x3B */ Instr::I32Store16(memarg) => {
+                validate_store(ctx, memarg, 16, ty::Val::I32)?;
             }
-            /* 0x3C */ Instr::I64Store8(_offset, align) => {
-                validate_store(ctx, *align, 8, ty::Val::I64)?;
+            /* 0x3C */ Instr::I64Store8(memarg) => {
+                validate_store(ctx, memarg, 8, ty::Val::I64)?;
             }
-            /* 0x3D */ Instr::I64Store16(_offset, align) => {
-                validate_store(ctx, *align, 16, ty::Val::I64)?;
+            /* 0x3D */ Instr::I64Store16(memarg) => {
+                validate_store(ctx, memarg, 16, ty::Val::I64)?;
             }
-            /* 0x3E */ Instr::I64Store32(_offset, align) => {
-                validate_store(ctx, *align, 32, ty::Val::I64)?;
+            /* 0x3E */ Instr::I64Store32(memarg) => {
+                validate_store(ctx, memarg, 32, ty::Val::I64)?;
             }
             // Memory management
             /* 0x3F */ Instr::MemSize(idx) => {
-                idx.validate(ctx)?;
-                ctx.stacks.push_operands(&[ty::Val::I32]);
+                let mem = idx.validate(ctx)?;
+                ctx.stacks.push_operands(&[mem.addr.into()]);
             }
             /* 0x40 */ Instr::MemGrow(idx) => {
-                idx.validate(ctx)?;
-                ctx.stacks.pop_operands(&[ty::Val::I32])?;
-                ctx.stacks.push_operands(&[ty::Val::I32]);
+                let mem = idx.validate(ctx)?;
+                ctx.stacks.pop_operands(&[mem.addr.into()])?;
+                ctx.stacks.push_operands(&[mem.addr.into()]);
             }
             // Numeric
             /* 0x41 */ Instr::ConstI32(_) => {
@@ -754,31 +887,2279 @@ impl<'a> Validate<'a> for instr::Instr {
                 ctx.stacks.pop_operands(&[ty::Val::I64])?;
                 ctx.stacks.push_operands(&[ty::Val::F64]);
             }
+            // Sign extension
+            /* 0xC0 */ Instr::I32Extend8S => {
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xC1 */ Instr::I32Extend16S => {
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xC2 */ Instr::I64Extend8S => {
+                ctx.stacks.pop_operands(&[ty::Val::I64])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
+            /* 0xC3 */ Instr::I64Extend16S => {
+                ctx.stacks.pop_operands(&[ty::Val::I64])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
+            /* 0xC4 */ Instr::I64Extend32S => {
+                ctx.stacks.pop_operands(&[ty::Val::I64])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
             // ... reserved ...
-        }
-        Ok(())
-    }
-}
-
-fn validate_load<'a>(ctx: &mut Context<'a>, _align: u32, _size: u32, val: ty::Val) -> Result<()> {
-    idx::Mem(0).validate(ctx)?;
-    /*
-    if !( 1<<align <= size/8 ) {
-        return Err(Error::UnexpectedEndOfFile);
-    }
-    */
-    ctx.stacks.pop_operands(&[ty::Val::I32])?;
-    ctx.stacks.push_operands(&[val]);
-    Ok(())
-}
-
-fn validate_store<'a>(ctx: &mut Context<'a>, _align: u32, _size: u32, val: ty::Val) -> Result<()> {
-    idx::Mem(0).validate(ctx)?;
-    /*
-    if !( 1<<align <= size/8 ) {
-        return Err(Error::UnexpectedEndOfFile);
-    }
-    */
-    ctx.stacks.pop_operands(&[ty::Val::I32, val])?;
-    Ok(())
+            // Reference
+            /* 0xD0 */ Instr::RefNull(heap) => {
+                heap.validate(ctx)?;
+                ctx.stacks.push_operands(&[ref_val(true, *heap)]);
+            }
+            /* 0xD1 */ Instr::RefIsNull => {
+                pop_ref(ctx)?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xD2 */ Instr::RefFunc(idx) => {
+                idx.validate(ctx)?;
+                if !ctx.refs.contains(&idx.0) {
+                    return Err(Error::UndeclaredFuncRef { idx: idx.0 });
+                }
+                let ty_idx = ctx.func_types[idx.0 as usize];
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(ty_idx))]);
+            }
+            /* 0xD3 */ Instr::RefEq => {
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Eq), ref_val(true, ty::Heap::Eq)])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xD4 */ Instr::RefAsNonNull => {
+                match pop_ref(ctx)? {
+                    Some(r) => ctx.stacks.push_operands(&[ref_val(false, r.heap)]),
+                    None => ctx.stacks.push_operand(stacks::Operand::Unknown),
+                }
+            }
+            /* 0xD5 */ Instr::BrOnNull(label) => {
+                let r = pop_ref(ctx)?;
+                let frame = label.validate(ctx)?;
+                ctx.stacks.pop_operands(frame.label)?;
+                ctx.stacks.push_operands(frame.label);
+                match r {
+                    Some(r) => ctx.stacks.push_operands(&[ref_val(false, r.heap)]),
+                    None => ctx.stacks.push_operand(stacks::Operand::Unknown),
+                }
+            }
+            /* 0xD6 */ Instr::BrOnNonNull(label) => {
+                let r = pop_ref(ctx)?;
+                validate_br_on(ctx, label, r.map(|r| ty::Ref { nullable: false, ..r }))?;
+            }
+            // ... reserved ...
+            // Aggregate
+            /* 0xFB 0x00 */ Instr::StructNew(ty_idx) => {
+                let st = struct_type(ctx, ty_idx)?;
+                let fields: Vec<ty::Val> = st.fields.iter().map(|field| field.storage.unpacked()).collect();
+                ctx.stacks.pop_operands(&fields)?;
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(*ty_idx))]);
+            }
+            /* 0xFB 0x01 */ Instr::StructNewDefault(ty_idx) => {
+                let st = struct_type(ctx, ty_idx)?;
+                if !st.fields.iter().all(|field| field.storage.unpacked().is_defaultable()) {
+                    return Err(Error::NonDefaultableField { ty: ty_idx.0 });
+                }
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(*ty_idx))]);
+            }
+            /* 0xFB 0x02 */ Instr::StructGet(ty_idx, field_idx) => {
+                validate_struct_get(ctx, ty_idx, field_idx, false)?;
+            }
+            /* 0xFB 0x03 */ Instr::StructGetS(ty_idx, field_idx) => {
+                validate_struct_get(ctx, ty_idx, field_idx, true)?;
+            }
+            /* 0xFB 0x04 */ Instr::StructGetU(ty_idx, field_idx) => {
+                validate_struct_get(ctx, ty_idx, field_idx, true)?;
+            }
+            /* 0xFB 0x05 */ Instr::StructSet(ty_idx, field_idx) => {
+                let field = struct_field(ctx, ty_idx, field_idx)?;
+                if field.mt != ty::Mut::Var {
+                    return Err(Error::ImmutableField { ty: ty_idx.0, field: field_idx.0 });
+                }
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx)), field.storage.unpacked()])?;
+            }
+            /* 0xFB 0x06 */ Instr::ArrayNew(ty_idx) => {
+                let array = array_type(ctx, ty_idx)?;
+                ctx.stacks.pop_operands(&[array.field.storage.unpacked(), ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(*ty_idx))]);
+            }
+            /* 0xFB 0x07 */ Instr::ArrayNewDefault(ty_idx) => {
+                let array = array_type(ctx, ty_idx)?;
+                if !array.field.storage.unpacked().is_defaultable() {
+                    return Err(Error::NonDefaultableField { ty: ty_idx.0 });
+                }
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(*ty_idx))]);
+            }
+            /* 0xFB 0x08 */ Instr::ArrayNewFixed(ty_idx, len) => {
+                let array = array_type(ctx, ty_idx)?;
+                for _ in 0..*len {
+                    ctx.stacks.pop_operands(&[array.field.storage.unpacked()])?;
+                }
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(*ty_idx))]);
+            }
+            /* 0xFB 0x09 */ Instr::ArrayNewData(ty_idx, data) => {
+                validate_array_data(ctx, ty_idx, data)?;
+                ctx.stacks.pop_operands(&[ty::Val::I32, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(*ty_idx))]);
+            }
+            /* 0xFB 0x0A */ Instr::ArrayNewElem(ty_idx, elem) => {
+                validate_array_elem(ctx, ty_idx, elem)?;
+                ctx.stacks.pop_operands(&[ty::Val::I32, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::Type(*ty_idx))]);
+            }
+            /* 0xFB 0x0B */ Instr::ArrayGet(ty_idx) => {
+                validate_array_get(ctx, ty_idx, false)?;
+            }
+            /* 0xFB 0x0C */ Instr::ArrayGetS(ty_idx) => {
+                validate_array_get(ctx, ty_idx, true)?;
+            }
+            /* 0xFB 0x0D */ Instr::ArrayGetU(ty_idx) => {
+                validate_array_get(ctx, ty_idx, true)?;
+            }
+            /* 0xFB 0x0E */ Instr::ArraySet(ty_idx) => {
+                let field = mutable_array_field(ctx, ty_idx)?;
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx)), ty::Val::I32, field.storage.unpacked()])?;
+            }
+            /* 0xFB 0x0F */ Instr::ArrayLen => {
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Array)])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFB 0x10 */ Instr::ArrayFill(ty_idx) => {
+                let field = mutable_array_field(ctx, ty_idx)?;
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx)), ty::Val::I32, field.storage.unpacked(), ty::Val::I32])?;
+            }
+            /* 0xFB 0x11 */ Instr::ArrayCopy(dst_idx, src_idx) => {
+                let dst = mutable_array_field(ctx, dst_idx)?;
+                let src = &array_type(ctx, src_idx)?.field;
+                let matches = match (src.storage, dst.storage) {
+                    (ty::Storage::Val(src), ty::Storage::Val(dst)) => is_subtype(&ctx.types, src, dst),
+                    (src, dst) => src == dst,
+                };
+                if !matches {
+                    return Err(Error::TypeMismatch {
+                        expected: dst.storage.unpacked().into(),
+                        found: src.storage.unpacked().into(),
+                    });
+                }
+                ctx.stacks.pop_operands(&[
+                    ref_val(true, ty::Heap::Type(*dst_idx)), ty::Val::I32,
+                    ref_val(true, ty::Heap::Type(*src_idx)), ty::Val::I32,
+                    ty::Val::I32,
+                ])?;
+            }
+            /* 0xFB 0x12 */ Instr::ArrayInitData(ty_idx, data) => {
+                mutable_array_field(ctx, ty_idx)?;
+                validate_array_data(ctx, ty_idx, data)?;
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx)), ty::Val::I32, ty::Val::I32, ty::Val::I32])?;
+            }
+            /* 0xFB 0x13 */ Instr::ArrayInitElem(ty_idx, elem) => {
+                mutable_array_field(ctx, ty_idx)?;
+                validate_array_elem(ctx, ty_idx, elem)?;
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx)), ty::Val::I32, ty::Val::I32, ty::Val::I32])?;
+            }
+            // Casts
+            /* 0xFB 0x14 */ Instr::RefTest(to) => {
+                to.heap.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ref_val(true, top_heap(&ctx.types, to.heap))])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFB 0x16 */ Instr::RefCast(to) => {
+                to.heap.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ref_val(true, top_heap(&ctx.types, to.heap))])?;
+                ctx.stacks.push_operands(&[(*to).into()]);
+            }
+            /* 0xFB 0x18 */ Instr::BrOnCast(label, from, to) => {
+                validate_cast(ctx, from, to)?;
+                ctx.stacks.pop_operands(&[(*from).into()])?;
+                validate_br_on(ctx, label, Some(*to))?;
+                // the operand falls through when the cast fails
+                ctx.stacks.push_operands(&[ref_val(from.nullable && !to.nullable, from.heap)]);
+            }
+            /* 0xFB 0x19 */ Instr::BrOnCastFail(label, from, to) => {
+                validate_cast(ctx, from, to)?;
+                ctx.stacks.pop_operands(&[(*from).into()])?;
+                let failed = ty::Ref { nullable: from.nullable && !to.nullable, heap: from.heap };
+                validate_br_on(ctx, label, Some(failed))?;
+                ctx.stacks.push_operands(&[(*to).into()]);
+            }
+            /* 0xFB 0x1A */ Instr::AnyConvertExtern => {
+                let op = ctx.stacks.pop_operand(ref_val(true, ty::Heap::Extern).into())?;
+                ctx.stacks.push_operands(&[ref_val(is_nullable(op), ty::Heap::Any)]);
+            }
+            /* 0xFB 0x1B */ Instr::ExternConvertAny => {
+                let op = ctx.stacks.pop_operand(ref_val(true, ty::Heap::Any).into())?;
+                ctx.stacks.push_operands(&[ref_val(is_nullable(op), ty::Heap::Extern)]);
+            }
+            // Scalar references
+            /* 0xFB 0x1C */ Instr::RefI31 => {
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ref_val(false, ty::Heap::I31)]);
+            }
+            /* 0xFB 0x1D */ Instr::I31GetS => {
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::I31)])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFB 0x1E */ Instr::I31GetU => {
+                ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::I31)])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            // Saturating truncation
+            /* 0xFC 0x00 */ Instr::I32TruncSatF32S => {
+                ctx.stacks.pop_operands(&[ty::Val::F32])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFC 0x01 */ Instr::I32TruncSatF32U => {
+                ctx.stacks.pop_operands(&[ty::Val::F32])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFC 0x02 */ Instr::I32TruncSatF64S => {
+                ctx.stacks.pop_operands(&[ty::Val::F64])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFC 0x03 */ Instr::I32TruncSatF64U => {
+                ctx.stacks.pop_operands(&[ty::Val::F64])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFC 0x04 */ Instr::I64TruncSatF32S => {
+                ctx.stacks.pop_operands(&[ty::Val::F32])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
+            /* 0xFC 0x05 */ Instr::I64TruncSatF32U => {
+                ctx.stacks.pop_operands(&[ty::Val::F32])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
+            /* 0xFC 0x06 */ Instr::I64TruncSatF64S => {
+                ctx.stacks.pop_operands(&[ty::Val::F64])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
+            /* 0xFC 0x07 */ Instr::I64TruncSatF64U => {
+                ctx.stacks.pop_operands(&[ty::Val::F64])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
+            // Bulk memory
+            /* 0xFC 0x08 */ Instr::MemInit(data, mem) => {
+                let mem = mem.validate(ctx)?;
+                data.validate(ctx)?;
+                ctx.stacks.pop_operands(&[mem.addr.into(), ty::Val::I32, ty::Val::I32])?;
+            }
+            /* 0xFC 0x09 */ Instr::DataDrop(data) => {
+                data.validate(ctx)?;
+            }
+            /* 0xFC 0x0A */ Instr::MemCopy(dst, src) => {
+                let dst = dst.validate(ctx)?;
+                let src = src.validate(ctx)?;
+                // the length must fit in both memories
+                let len = dst.addr.min(src.addr);
+                ctx.stacks.pop_operands(&[dst.addr.into(), src.addr.into(), len.into()])?;
+            }
+            /* 0xFC 0x0B */ Instr::MemFill(mem) => {
+                let mem = mem.validate(ctx)?;
+                ctx.stacks.pop_operands(&[mem.addr.into(), ty::Val::I32, mem.addr.into()])?;
+            }
+            /* 0xFC 0x0C */ Instr::TableInit(elem, table) => {
+                let table = table.validate(ctx)?;
+                let elem = elem.validate(ctx)?;
+                if !is_subtype(&ctx.types, elem.ty.into(), table.elem.into()) {
+                    return Err(Error::ElemTypeMismatch { expected: table.elem, found: elem.ty });
+                }
+                ctx.stacks.pop_operands(&[ty::Val::I32, ty::Val::I32, ty::Val::I32])?;
+            }
+            /* 0xFC 0x0D */ Instr::ElemDrop(elem) => {
+                elem.validate(ctx)?;
+            }
+            /* 0xFC 0x0E */ Instr::TableCopy(dst, src) => {
+                let dst = dst.validate(ctx)?;
+                let src = src.validate(ctx)?;
+                if !is_subtype(&ctx.types, src.elem.into(), dst.elem.into()) {
+                    return Err(Error::ElemTypeMismatch { expected: dst.elem, found: src.elem });
+                }
+                ctx.stacks.pop_operands(&[ty::Val::I32, ty::Val::I32, ty::Val::I32])?;
+            }
+            // Table management
+            /* 0xFC 0x0F */ Instr::TableGrow(idx) => {
+                let ty = idx.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ty.elem.into(), ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFC 0x10 */ Instr::TableSize(idx) => {
+                idx.validate(ctx)?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFC 0x11 */ Instr::TableFill(idx) => {
+                let ty = idx.validate(ctx)?;
+                ctx.stacks.pop_operands(&[ty::Val::I32, ty.elem.into(), ty::Val::I32])?;
+            }
+            // Vector
+            /* 0xFD 0x00 */ Instr::V128Load(memarg) => {
+                validate_load(ctx, memarg, 128, ty::Val::V128)?;
+            }
+            /* 0xFD 0x01 */ Instr::V128Load8x8S(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x02 */ Instr::V128Load8x8U(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x03 */ Instr::V128Load16x4S(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x04 */ Instr::V128Load16x4U(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x05 */ Instr::V128Load32x2S(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x06 */ Instr::V128Load32x2U(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x07 */ Instr::V128Load8Splat(memarg) => {
+                validate_load(ctx, memarg, 8, ty::Val::V128)?;
+            }
+            /* 0xFD 0x08 */ Instr::V128Load16Splat(memarg) => {
+                validate_load(ctx, memarg, 16, ty::Val::V128)?;
+            }
+            /* 0xFD 0x09 */ Instr::V128Load32Splat(memarg) => {
+                validate_load(ctx, memarg, 32, ty::Val::V128)?;
+            }
+            /* 0xFD 0x0A */ Instr::V128Load64Splat(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x0B */ Instr::V128Store(memarg) => {
+                validate_store(ctx, memarg, 128, ty::Val::V128)?;
+            }
+            /* 0xFD 0x0C */ Instr::ConstV128(_) => {
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x0D */ Instr::I8x16Shuffle(lanes) => {
+                for lane in lanes.iter() {
+                    validate_lane(*lane, 32)?;
+                }
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x0E */ Instr::I8x16Swizzle => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x0F */ Instr::I8x16Splat => {
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x10 */ Instr::I16x8Splat => {
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x11 */ Instr::I32x4Splat => {
+                ctx.stacks.pop_operands(&[ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x12 */ Instr::I64x2Splat => {
+                ctx.stacks.pop_operands(&[ty::Val::I64])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x13 */ Instr::F32x4Splat => {
+                ctx.stacks.pop_operands(&[ty::Val::F32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x14 */ Instr::F64x2Splat => {
+                ctx.stacks.pop_operands(&[ty::Val::F64])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x15 */ Instr::I8x16ExtractLaneS(lane) => {
+                validate_lane(*lane, 16)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x16 */ Instr::I8x16ExtractLaneU(lane) => {
+                validate_lane(*lane, 16)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x17 */ Instr::I8x16ReplaceLane(lane) => {
+                validate_lane(*lane, 16)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x18 */ Instr::I16x8ExtractLaneS(lane) => {
+                validate_lane(*lane, 8)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x19 */ Instr::I16x8ExtractLaneU(lane) => {
+                validate_lane(*lane, 8)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x1A */ Instr::I16x8ReplaceLane(lane) => {
+                validate_lane(*lane, 8)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x1B */ Instr::I32x4ExtractLane(lane) => {
+                validate_lane(*lane, 4)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x1C */ Instr::I32x4ReplaceLane(lane) => {
+                validate_lane(*lane, 4)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x1D */ Instr::I64x2ExtractLane(lane) => {
+                validate_lane(*lane, 2)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I64]);
+            }
+            /* 0xFD 0x1E */ Instr::I64x2ReplaceLane(lane) => {
+                validate_lane(*lane, 2)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I64])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x1F */ Instr::F32x4ExtractLane(lane) => {
+                validate_lane(*lane, 4)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::F32]);
+            }
+            /* 0xFD 0x20 */ Instr::F32x4ReplaceLane(lane) => {
+                validate_lane(*lane, 4)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::F32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x21 */ Instr::F64x2ExtractLane(lane) => {
+                validate_lane(*lane, 2)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::F64]);
+            }
+            /* 0xFD 0x22 */ Instr::F64x2ReplaceLane(lane) => {
+                validate_lane(*lane, 2)?;
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::F64])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x23 */ Instr::I8x16Eq => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x24 */ Instr::I8x16Ne => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x25 */ Instr::I8x16LtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x26 */ Instr::I8x16LtU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x27 */ Instr::I8x16GtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x28 */ Instr::I8x16GtU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x29 */ Instr::I8x16LeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x2A */ Instr::I8x16LeU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x2B */ Instr::I8x16GeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x2C */ Instr::I8x16GeU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x2D */ Instr::I16x8Eq => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x2E */ Instr::I16x8Ne => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x2F */ Instr::I16x8LtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x30 */ Instr::I16x8LtU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x31 */ Instr::I16x8GtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x32 */ Instr::I16x8GtU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x33 */ Instr::I16x8LeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x34 */ Instr::I16x8LeU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x35 */ Instr::I16x8GeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x36 */ Instr::I16x8GeU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x37 */ Instr::I32x4Eq => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x38 */ Instr::I32x4Ne => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x39 */ Instr::I32x4LtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x3A */ Instr::I32x4LtU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x3B */ Instr::I32x4GtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x3C */ Instr::I32x4GtU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x3D */ Instr::I32x4LeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x3E */ Instr::I32x4LeU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x3F */ Instr::I32x4GeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x40 */ Instr::I32x4GeU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x41 */ Instr::F32x4Eq => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x42 */ Instr::F32x4Ne => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x43 */ Instr::F32x4Lt => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x44 */ Instr::F32x4Gt => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x45 */ Instr::F32x4Le => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x46 */ Instr::F32x4Ge => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x47 */ Instr::F64x2Eq => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x48 */ Instr::F64x2Ne => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x49 */ Instr::F64x2Lt => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x4A */ Instr::F64x2Gt => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x4B */ Instr::F64x2Le => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x4C */ Instr::F64x2Ge => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x4D */ Instr::V128Not => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x4E */ Instr::V128And => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x4F */ Instr::V128AndNot => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x50 */ Instr::V128Or => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x51 */ Instr::V128Xor => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x52 */ Instr::V128Bitselect => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x53 */ Instr::V128AnyTrue => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x54 */ Instr::V128Load8Lane(memarg, lane) => {
+                validate_load_lane(ctx, memarg, 8, *lane)?;
+            }
+            /* 0xFD 0x55 */ Instr::V128Load16Lane(memarg, lane) => {
+                validate_load_lane(ctx, memarg, 16, *lane)?;
+            }
+            /* 0xFD 0x56 */ Instr::V128Load32Lane(memarg, lane) => {
+                validate_load_lane(ctx, memarg, 32, *lane)?;
+            }
+            /* 0xFD 0x57 */ Instr::V128Load64Lane(memarg, lane) => {
+                validate_load_lane(ctx, memarg, 64, *lane)?;
+            }
+            /* 0xFD 0x58 */ Instr::V128Store8Lane(memarg, lane) => {
+                validate_store_lane(ctx, memarg, 8, *lane)?;
+            }
+            /* 0xFD 0x59 */ Instr::V128Store16Lane(memarg, lane) => {
+                validate_store_lane(ctx, memarg, 16, *lane)?;
+            }
+            /* 0xFD 0x5A */ Instr::V128Store32Lane(memarg, lane) => {
+                validate_store_lane(ctx, memarg, 32, *lane)?;
+            }
+            /* 0xFD 0x5B */ Instr::V128Store64Lane(memarg, lane) => {
+                validate_store_lane(ctx, memarg, 64, *lane)?;
+            }
+            /* 0xFD 0x5C */ Instr::V128Load32Zero(memarg) => {
+                validate_load(ctx, memarg, 32, ty::Val::V128)?;
+            }
+            /* 0xFD 0x5D */ Instr::V128Load64Zero(memarg) => {
+                validate_load(ctx, memarg, 64, ty::Val::V128)?;
+            }
+            /* 0xFD 0x5E */ Instr::F32x4DemoteF64x2Zero => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x5F */ Instr::F64x2PromoteLowF32x4 => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x60 */ Instr::I8x16Abs => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x61 */ Instr::I8x16Neg => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x62 */ Instr::I8x16Popcnt => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x63 */ Instr::I8x16AllTrue => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x64 */ Instr::I8x16Bitmask => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x65 */ Instr::I8x16NarrowI16x8S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x66 */ Instr::I8x16NarrowI16x8U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x67 */ Instr::F32x4Ceil => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x68 */ Instr::F32x4Floor => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x69 */ Instr::F32x4Trunc => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x6A */ Instr::F32x4Nearest => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x6B */ Instr::I8x16Shl => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x6C */ Instr::I8x16ShrS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x6D */ Instr::I8x16ShrU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x6E */ Instr::I8x16Add => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x6F */ Instr::I8x16AddSatS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x70 */ Instr::I8x16AddSatU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x71 */ Instr::I8x16Sub => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x72 */ Instr::I8x16SubSatS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x73 */ Instr::I8x16SubSatU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x74 */ Instr::F64x2Ceil => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x75 */ Instr::F64x2Floor => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x76 */ Instr::I8x16MinS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x77 */ Instr::I8x16MinU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x78 */ Instr::I8x16MaxS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x79 */ Instr::I8x16MaxU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x7A */ Instr::F64x2Trunc => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x7B */ Instr::I8x16AvgrU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x7C */ Instr::I16x8ExtaddPairwiseI8x16S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x7D */ Instr::I16x8ExtaddPairwiseI8x16U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x7E */ Instr::I32x4ExtaddPairwiseI16x8S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x7F */ Instr::I32x4ExtaddPairwiseI16x8U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x80 */ Instr::I16x8Abs => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x81 */ Instr::I16x8Neg => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x82 */ Instr::I16x8Q15mulrSatS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x83 */ Instr::I16x8AllTrue => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x84 */ Instr::I16x8Bitmask => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0x85 */ Instr::I16x8NarrowI32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x86 */ Instr::I16x8NarrowI32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x87 */ Instr::I16x8ExtendLowI8x16S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x88 */ Instr::I16x8ExtendHighI8x16S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x89 */ Instr::I16x8ExtendLowI8x16U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x8A */ Instr::I16x8ExtendHighI8x16U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x8B */ Instr::I16x8Shl => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x8C */ Instr::I16x8ShrS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x8D */ Instr::I16x8ShrU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x8E */ Instr::I16x8Add => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x8F */ Instr::I16x8AddSatS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x90 */ Instr::I16x8AddSatU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x91 */ Instr::I16x8Sub => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x92 */ Instr::I16x8SubSatS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x93 */ Instr::I16x8SubSatU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x94 */ Instr::F64x2Nearest => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x95 */ Instr::I16x8Mul => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x96 */ Instr::I16x8MinS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x97 */ Instr::I16x8MinU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x98 */ Instr::I16x8MaxS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x99 */ Instr::I16x8MaxU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0x9B */ Instr::I16x8AvgrU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x9C */ Instr::I16x8ExtmulLowI8x16S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x9D */ Instr::I16x8ExtmulHighI8x16S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x9E */ Instr::I16x8ExtmulLowI8x16U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x9F */ Instr::I16x8ExtmulHighI8x16U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xA0 */ Instr::I32x4Abs => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xA1 */ Instr::I32x4Neg => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xA3 */ Instr::I32x4AllTrue => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0xA4 */ Instr::I32x4Bitmask => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xA7 */ Instr::I32x4ExtendLowI16x8S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xA8 */ Instr::I32x4ExtendHighI16x8S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xA9 */ Instr::I32x4ExtendLowI16x8U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xAA */ Instr::I32x4ExtendHighI16x8U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xAB */ Instr::I32x4Shl => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xAC */ Instr::I32x4ShrS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xAD */ Instr::I32x4ShrU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xAE */ Instr::I32x4Add => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xB1 */ Instr::I32x4Sub => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xB5 */ Instr::I32x4Mul => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xB6 */ Instr::I32x4MinS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xB7 */ Instr::I32x4MinU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xB8 */ Instr::I32x4MaxS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xB9 */ Instr::I32x4MaxU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xBA */ Instr::I32x4DotI16x8S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xBC */ Instr::I32x4ExtmulLowI16x8S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xBD */ Instr::I32x4ExtmulHighI16x8S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xBE */ Instr::I32x4ExtmulLowI16x8U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xBF */ Instr::I32x4ExtmulHighI16x8U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xC0 */ Instr::I64x2Abs => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xC1 */ Instr::I64x2Neg => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xC3 */ Instr::I64x2AllTrue => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+            /* 0xFD 0xC4 */ Instr::I64x2Bitmask => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::I32]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xC7 */ Instr::I64x2ExtendLowI32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xC8 */ Instr::I64x2ExtendHighI32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xC9 */ Instr::I64x2ExtendLowI32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xCA */ Instr::I64x2ExtendHighI32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xCB */ Instr::I64x2Shl => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xCC */ Instr::I64x2ShrS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xCD */ Instr::I64x2ShrU => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::I32])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xCE */ Instr::I64x2Add => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xD1 */ Instr::I64x2Sub => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xD5 */ Instr::I64x2Mul => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xD6 */ Instr::I64x2Eq => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xD7 */ Instr::I64x2Ne => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xD8 */ Instr::I64x2LtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xD9 */ Instr::I64x2GtS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xDA */ Instr::I64x2LeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xDB */ Instr::I64x2GeS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xDC */ Instr::I64x2ExtmulLowI32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xDD */ Instr::I64x2ExtmulHighI32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xDE */ Instr::I64x2ExtmulLowI32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xDF */ Instr::I64x2ExtmulHighI32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE0 */ Instr::F32x4Abs => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE1 */ Instr::F32x4Neg => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xE3 */ Instr::F32x4Sqrt => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE4 */ Instr::F32x4Add => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE5 */ Instr::F32x4Sub => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE6 */ Instr::F32x4Mul => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE7 */ Instr::F32x4Div => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE8 */ Instr::F32x4Min => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xE9 */ Instr::F32x4Max => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xEA */ Instr::F32x4Pmin => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xEB */ Instr::F32x4Pmax => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xEC */ Instr::F64x2Abs => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xED */ Instr::F64x2Neg => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+                // ... reserved ...
+            /* 0xFD 0xEF */ Instr::F64x2Sqrt => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF0 */ Instr::F64x2Add => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF1 */ Instr::F64x2Sub => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF2 */ Instr::F64x2Mul => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF3 */ Instr::F64x2Div => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF4 */ Instr::F64x2Min => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF5 */ Instr::F64x2Max => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF6 */ Instr::F64x2Pmin => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF7 */ Instr::F64x2Pmax => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF8 */ Instr::I32x4TruncSatF32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xF9 */ Instr::I32x4TruncSatF32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xFA */ Instr::F32x4ConvertI32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xFB */ Instr::F32x4ConvertI32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xFC */ Instr::I32x4TruncSatF64x2SZero => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xFD */ Instr::I32x4TruncSatF64x2UZero => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xFE */ Instr::F64x2ConvertLowI32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0xFF */ Instr::F64x2ConvertLowI32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            // Relaxed vector
+            /* 0xFD 0x100 */ Instr::I8x16RelaxedSwizzle => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x101 */ Instr::I32x4RelaxedTruncF32x4S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x102 */ Instr::I32x4RelaxedTruncF32x4U => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x103 */ Instr::I32x4RelaxedTruncF64x2SZero => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x104 */ Instr::I32x4RelaxedTruncF64x2UZero => {
+                ctx.stacks.pop_operands(&[ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x105 */ Instr::F32x4RelaxedMadd => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x106 */ Instr::F32x4RelaxedNmadd => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x107 */ Instr::F64x2RelaxedMadd => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x108 */ Instr::F64x2RelaxedNmadd => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x109 */ Instr::I8x16RelaxedLaneselect => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x10A */ Instr::I16x8RelaxedLaneselect => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x10B */ Instr::I32x4RelaxedLaneselect => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x10C */ Instr::I64x2RelaxedLaneselect => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x10D */ Instr::F32x4RelaxedMin => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x10E */ Instr::F32x4RelaxedMax => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x10F */ Instr::F64x2RelaxedMin => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x110 */ Instr::F64x2RelaxedMax => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x111 */ Instr::I16x8RelaxedQ15mulrS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x112 */ Instr::I16x8RelaxedDotI8x16I7x16S => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            /* 0xFD 0x113 */ Instr::I32x4RelaxedDotI8x16I7x16AddS => {
+                ctx.stacks.pop_operands(&[ty::Val::V128, ty::Val::V128, ty::Val::V128])?;
+                ctx.stacks.push_operands(&[ty::Val::V128]);
+            }
+            // Atomic memory
+            /* 0xFE 0x00 */ Instr::MemAtomicNotify(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x01 */ Instr::MemAtomicWait32(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32, ty::Val::I64], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x02 */ Instr::MemAtomicWait64(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64, ty::Val::I64], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x03 */ Instr::AtomicFence => {}
+            // ... reserved ...
+            /* 0xFE 0x10 */ Instr::I32AtomicLoad(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x11 */ Instr::I64AtomicLoad(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x12 */ Instr::I32AtomicLoad8U(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x13 */ Instr::I32AtomicLoad16U(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x14 */ Instr::I64AtomicLoad8U(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x15 */ Instr::I64AtomicLoad16U(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x16 */ Instr::I64AtomicLoad32U(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x17 */ Instr::I32AtomicStore(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[])?;
+            }
+            /* 0xFE 0x18 */ Instr::I64AtomicStore(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64], &[])?;
+            }
+            /* 0xFE 0x19 */ Instr::I32AtomicStore8(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32], &[])?;
+            }
+            /* 0xFE 0x1A */ Instr::I32AtomicStore16(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32], &[])?;
+            }
+            /* 0xFE 0x1B */ Instr::I64AtomicStore8(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64], &[])?;
+            }
+            /* 0xFE 0x1C */ Instr::I64AtomicStore16(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64], &[])?;
+            }
+            /* 0xFE 0x1D */ Instr::I64AtomicStore32(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64], &[])?;
+            }
+            /* 0xFE 0x1E */ Instr::I32AtomicRmwAdd(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x1F */ Instr::I64AtomicRmwAdd(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x20 */ Instr::I32AtomicRmw8AddU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x21 */ Instr::I32AtomicRmw16AddU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x22 */ Instr::I64AtomicRmw8AddU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x23 */ Instr::I64AtomicRmw16AddU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x24 */ Instr::I64AtomicRmw32AddU(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x25 */ Instr::I32AtomicRmwSub(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x26 */ Instr::I64AtomicRmwSub(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x27 */ Instr::I32AtomicRmw8SubU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x28 */ Instr::I32AtomicRmw16SubU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x29 */ Instr::I64AtomicRmw8SubU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x2A */ Instr::I64AtomicRmw16SubU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x2B */ Instr::I64AtomicRmw32SubU(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x2C */ Instr::I32AtomicRmwAnd(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x2D */ Instr::I64AtomicRmwAnd(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x2E */ Instr::I32AtomicRmw8AndU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x2F */ Instr::I32AtomicRmw16AndU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x30 */ Instr::I64AtomicRmw8AndU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x31 */ Instr::I64AtomicRmw16AndU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x32 */ Instr::I64AtomicRmw32AndU(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x33 */ Instr::I32AtomicRmwOr(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x34 */ Instr::I64AtomicRmwOr(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x35 */ Instr::I32AtomicRmw8OrU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x36 */ Instr::I32AtomicRmw16OrU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x37 */ Instr::I64AtomicRmw8OrU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x38 */ Instr::I64AtomicRmw16OrU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x39 */ Instr::I64AtomicRmw32OrU(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x3A */ Instr::I32AtomicRmwXor(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x3B */ Instr::I64AtomicRmwXor(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x3C */ Instr::I32AtomicRmw8XorU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x3D */ Instr::I32AtomicRmw16XorU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x3E */ Instr::I64AtomicRmw8XorU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x3F */ Instr::I64AtomicRmw16XorU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x40 */ Instr::I64AtomicRmw32XorU(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x41 */ Instr::I32AtomicRmwXchg(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x42 */ Instr::I64AtomicRmwXchg(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x43 */ Instr::I32AtomicRmw8XchgU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x44 */ Instr::I32AtomicRmw16XchgU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x45 */ Instr::I64AtomicRmw8XchgU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x46 */ Instr::I64AtomicRmw16XchgU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x47 */ Instr::I64AtomicRmw32XchgU(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x48 */ Instr::I32AtomicRmwCmpxchg(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I32, ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x49 */ Instr::I64AtomicRmwCmpxchg(memarg) => {
+                validate_atomic(ctx, memarg, 64, &[ty::Val::I64, ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x4A */ Instr::I32AtomicRmw8CmpxchgU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I32, ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x4B */ Instr::I32AtomicRmw16CmpxchgU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I32, ty::Val::I32], &[ty::Val::I32])?;
+            }
+            /* 0xFE 0x4C */ Instr::I64AtomicRmw8CmpxchgU(memarg) => {
+                validate_atomic(ctx, memarg, 8, &[ty::Val::I64, ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x4D */ Instr::I64AtomicRmw16CmpxchgU(memarg) => {
+                validate_atomic(ctx, memarg, 16, &[ty::Val::I64, ty::Val::I64], &[ty::Val::I64])?;
+            }
+            /* 0xFE 0x4E */ Instr::I64AtomicRmw32CmpxchgU(memarg) => {
+                validate_atomic(ctx, memarg, 32, &[ty::Val::I64, ty::Val::I64], &[ty::Val::I64])?;
+            }
+            // ... reserved ...
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Validate<'a> for instr::Catch {
+    type ValidationResult = ();
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        let (tag, label, exn) = match self {
+            instr::Catch::Catch(tag, label) => (Some(tag), label, false),
+            instr::Catch::CatchRef(tag, label) => (Some(tag), label, true),
+            instr::Catch::CatchAll(label) => (None, label, false),
+            instr::Catch::CatchAllRef(label) => (None, label, true),
+        };
+        // the catch clause branches to the label with the values carried by the exception
+        let mut expected = match tag {
+            Some(tag) => tag.validate(ctx)?.params.clone(),
+            None => vec![],
+        };
+        if exn {
+            expected.push(ty::Val::EXN_REF);
+        }
+        let frame = label.validate(ctx)?;
+        let matches = frame.label.len() == expected.len()
+            && expected.iter().zip(frame.label.iter()).all(|(found, label)| is_subtype(&ctx.types, *found, *label));
+        if !matches {
+            return Err(Error::LabelTypeMismatch { expected, found: frame.label.to_vec() });
+        }
+        Ok(())
+    }
+}
+
+/// Ends the current block of a legacy `try` and starts a handler receiving `params`.
+fn validate_handler<'a>(ctx: &mut Context<'a>, params: &'a [ty::Val]) -> Result<()> {
+    let frame = ctx.stacks.pop_frame()?;
+    ctx.stacks.push_frame(frame.label, params, frame.out);
+    ctx.stacks.frames.last_mut().unwrap().catch = true;
+    Ok(())
+}
+
+/// Ends a legacy `try` block, whose exceptions are rethrown to the block with the given label.
+fn validate_delegate<'a>(ctx: &mut Context<'a>, label: &idx::Label) -> Result<()> {
+    let frame = ctx.stacks.pop_frame()?;
+    // unlike branches, `delegate` can target the function frame
+    if label.0 as usize >= ctx.stacks.frames.len() {
+        return Err(Error::UnknownLabel { idx: label.0 });
+    }
+    ctx.stacks.push_operands(frame.out);
+    Ok(())
+}
+
+fn validate_load<'a>(ctx: &mut Context<'a>, memarg: &'a instr::MemArg, size: u32, val: ty::Val) -> Result<()> {
+    let addr = validate_memarg(ctx, memarg)?;
+    validate_align(memarg.align, size)?;
+    ctx.stacks.pop_operands(&[addr])?;
+    ctx.stacks.push_operands(&[val]);
+    Ok(())
+}
+
+fn validate_store<'a>(ctx: &mut Context<'a>, memarg: &'a instr::MemArg, size: u32, val: ty::Val) -> Result<()> {
+    let addr = validate_memarg(ctx, memarg)?;
+    validate_align(memarg.align, size)?;
+    ctx.stacks.pop_operands(&[addr, val])?;
+    Ok(())
+}
+
+fn validate_tail_call<'a>(ctx: &mut Context<'a>, func: &ty::Func) -> Result<()> {
+    // the callee returns directly to the caller of the current function
+    let ret = ctx.ret.ok_or(Error::ReturnOutsideFunction)?;
+    let matches = func.results.len() == ret.len()
+        && func.results.iter().zip(ret.iter()).all(|(found, expected)| is_subtype(&ctx.types, *found, *expected));
+    if !matches {
+        return Err(Error::TailCallResultMismatch { expected: ret.to_vec(), found: func.results.clone() });
+    }
+    ctx.stacks.pop_operands(&func.params[..])?;
+    ctx.stacks.unreachable()?;
+    Ok(())
+}
+
+/// Returns the type of references to the given heap type.
+fn ref_val(nullable: bool, heap: ty::Heap) -> ty::Val {
+    ty::Val::Ref(ty::Ref { nullable, heap })
+}
+
+/// Pops a reference of any type, which is unknown in unreachable code.
+fn pop_ref(ctx: &mut Context) -> Result<Option<ty::Ref>> {
+    let op = ctx.stacks.pop_operand(stacks::Operand::Unknown)?;
+    match op {
+        stacks::Operand::Val(ty::Val::Ref(r)) => Ok(Some(r)),
+        stacks::Operand::Val(_) => Err(Error::ExpectedReference { found: op }),
+        stacks::Operand::Unknown => Ok(None),
+    }
+}
+
+fn is_nullable(op: stacks::Operand) -> bool {
+    !matches!(op, stacks::Operand::Val(ty::Val::Ref(ty::Ref { nullable: false, .. })))
+}
+
+/// Validates a conditional branch that passes a reference of type `branch` as the last value of
+/// the label, keeping the other values of the label on the stack when it falls through.
+fn validate_br_on<'a>(ctx: &mut Context<'a>, label: &'a idx::Label, branch: Option<ty::Ref>) -> Result<()> {
+    let frame = label.validate(ctx)?;
+    let matches = match (frame.label.last(), branch) {
+        (Some(last), Some(branch)) => is_subtype(&ctx.types, branch.into(), *last),
+        (Some(last), None) => last.is_ref(),
+        (None, _) => false,
+    };
+    let (_, rest) = match frame.label.split_last() {
+        Some(split) if matches => split,
+        _ => {
+            let mut found = frame.label.to_vec();
+            found.pop();
+            found.extend(branch.map(ty::Val::Ref));
+            return Err(Error::LabelTypeMismatch { expected: frame.label.to_vec(), found });
+        },
+    };
+    ctx.stacks.pop_operands(rest)?;
+    ctx.stacks.push_operands(rest);
+    Ok(())
+}
+
+/// Validates the types of a cast, whose target must be a subtype of its source.
+fn validate_cast<'a>(ctx: &mut Context<'a>, from: &'a ty::Ref, to: &'a ty::Ref) -> Result<()> {
+    from.heap.validate(ctx)?;
+    to.heap.validate(ctx)?;
+    if !is_subtype(&ctx.types, (*to).into(), (*from).into()) {
+        return Err(Error::InvalidCast { from: *from, to: *to });
+    }
+    Ok(())
+}
+
+fn struct_type<'a>(ctx: &Context<'a>, ty_idx: &idx::Type) -> Result<&'a ty::Struct> {
+    match ctx.types.get(ty_idx.0 as usize).copied().map(|ty| &ty.composite) {
+        Some(ty::Composite::Struct(st)) => Ok(st),
+        Some(_) => Err(Error::UnexpectedCompositeType { idx: ty_idx.0, expected: "struct" }),
+        None => Err(Error::UnknownType { idx: ty_idx.0 }),
+    }
+}
+
+fn array_type<'a>(ctx: &Context<'a>, ty_idx: &idx::Type) -> Result<&'a ty::Array> {
+    match ctx.types.get(ty_idx.0 as usize).copied().map(|ty| &ty.composite) {
+        Some(ty::Composite::Array(array)) => Ok(array),
+        Some(_) => Err(Error::UnexpectedCompositeType { idx: ty_idx.0, expected: "array" }),
+        None => Err(Error::UnknownType { idx: ty_idx.0 }),
+    }
+}
+
+fn struct_field<'a>(ctx: &Context<'a>, ty_idx: &idx::Type, field_idx: &idx::Field) -> Result<&'a ty::Field> {
+    let st = struct_type(ctx, ty_idx)?;
+    st.fields.get(field_idx.0 as usize).ok_or(Error::UnknownField { ty: ty_idx.0, field: field_idx.0 })
+}
+
+/// Returns the element type of an array that is written to, which must be mutable. The elements
+/// of an array are reported as its field `0`.
+fn mutable_array_field<'a>(ctx: &Context<'a>, ty_idx: &idx::Type) -> Result<&'a ty::Field> {
+    let field = &array_type(ctx, ty_idx)?.field;
+    if field.mt != ty::Mut::Var {
+        return Err(Error::ImmutableField { ty: ty_idx.0, field: 0 });
+    }
+    Ok(field)
+}
+
+/// Validates a read of a struct field, which must use a sign extension if and only if the field
+/// is `packed`.
+fn validate_struct_get(ctx: &mut Context, ty_idx: &idx::Type, field_idx: &idx::Field, packed: bool) -> Result<()> {
+    let field = struct_field(ctx, ty_idx, field_idx)?;
+    if field.storage.is_packed() != packed {
+        return Err(Error::PackedFieldMismatch { ty: ty_idx.0, field: field_idx.0 });
+    }
+    ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx))])?;
+    ctx.stacks.push_operands(&[field.storage.unpacked()]);
+    Ok(())
+}
+
+/// Validates a read of an array element, which must use a sign extension if and only if the
+/// elements are `packed`.
+fn validate_array_get(ctx: &mut Context, ty_idx: &idx::Type, packed: bool) -> Result<()> {
+    let field = &array_type(ctx, ty_idx)?.field;
+    if field.storage.is_packed() != packed {
+        return Err(Error::PackedFieldMismatch { ty: ty_idx.0, field: 0 });
+    }
+    ctx.stacks.pop_operands(&[ref_val(true, ty::Heap::Type(*ty_idx)), ty::Val::I32])?;
+    ctx.stacks.push_operands(&[field.storage.unpacked()]);
+    Ok(())
+}
+
+/// Validates that an array can be initialized from a data segment, which requires numeric or
+/// vector elements.
+fn validate_array_data<'a>(ctx: &mut Context<'a>, ty_idx: &idx::Type, data: &'a idx::Data) -> Result<()> {
+    let field = &array_type(ctx, ty_idx)?.field;
+    if field.storage.unpacked().is_ref() {
+        return Err(Error::InvalidArrayData { ty: ty_idx.0, data: data.0 });
+    }
+    data.validate(ctx)
+}
+
+/// Validates that an array can be initialized from an element segment, whose references must
+/// be subtypes of the elements of the array.
+fn validate_array_elem<'a>(ctx: &mut Context<'a>, ty_idx: &idx::Type, elem: &'a idx::Elem) -> Result<()> {
+    let field = &array_type(ctx, ty_idx)?.field;
+    let elem = elem.validate(ctx)?;
+    let expected = field.storage.unpacked();
+    if !is_subtype(&ctx.types, elem.ty.into(), expected) {
+        return Err(Error::TypeMismatch { expected: expected.into(), found: ty::Val::from(elem.ty).into() });
+    }
+    Ok(())
+}
+
+/// Validates an atomic access, which pops the address followed by `pops` and pushes `pushes`.
+fn validate_atomic<'a>(ctx: &mut Context<'a>, memarg: &'a instr::MemArg, size: u32, pops: &[ty::Val], pushes: &[ty::Val]) -> Result<()> {
+    let addr = validate_memarg(ctx, memarg)?;
+    // atomic accesses must be naturally aligned
+    let natural = (size / 8).trailing_zeros();
+    if memarg.align != natural {
+        return Err(Error::InvalidAtomicAlignment { align: memarg.align, natural });
+    }
+    ctx.stacks.pop_operands(pops)?;
+    ctx.stacks.pop_operands(&[addr])?;
+    ctx.stacks.push_operands(pushes);
+    Ok(())
+}
+
+fn validate_load_lane<'a>(ctx: &mut Context<'a>, memarg: &'a instr::MemArg, size: u32, lane: u8) -> Result<()> {
+    let addr = validate_memarg(ctx, memarg)?;
+    validate_align(memarg.align, size)?;
+    validate_lane(lane, (128 / size) as u8)?;
+    ctx.stacks.pop_operands(&[addr, ty::Val::V128])?;
+    ctx.stacks.push_operands(&[ty::Val::V128]);
+    Ok(())
+}
+
+fn validate_store_lane<'a>(ctx: &mut Context<'a>, memarg: &'a instr::MemArg, size: u32, lane: u8) -> Result<()> {
+    let addr = validate_memarg(ctx, memarg)?;
+    validate_align(memarg.align, size)?;
+    validate_lane(lane, (128 / size) as u8)?;
+    ctx.stacks.pop_operands(&[addr, ty::Val::V128])?;
+    Ok(())
+}
+
+/// Validates the memory accessed by a load or store, returning the type of its addresses.
+fn validate_memarg<'a>(ctx: &mut Context<'a>, memarg: &'a instr::MemArg) -> Result<ty::Val> {
+    let mem = memarg.mem.validate(ctx)?;
+    if mem.addr == ty::Addr::I32 && memarg.offset > u64::from(u32::MAX) {
+        return Err(Error::OffsetOutOfRange { offset: memarg.offset });
+    }
+    Ok(mem.addr.into())
+}
+
+fn validate_lane(lane: u8, lanes: u8) -> Result<()> {
+    if lane >= lanes {
+        return Err(Error::InvalidLaneIndex { lane, lanes });
+    }
+    Ok(())
+}
+
+fn validate_align(align: u32, size: u32) -> Result<()> {
+    let natural = (size / 8).trailing_zeros();
+    if align > natural {
+        return Err(Error::InvalidAlignment { align, natural });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::structure::{instr, idx, ty};
+    use crate::structure::instr::Instr;
+    use crate::validation::{ValidationEntry, Features, Error, stacks::Operand};
+    use crate::validation::module::test::{ModuleBuilder, module_with_body, memarg, func_error};
+
+    #[test]
+    fn can_validate_sign_extension_instructions() {
+        let i32s = || (vec![ty::Val::I32], vec![ty::Val::I32]);
+        let i64s = || (vec![ty::Val::I64], vec![ty::Val::I64]);
+        for (instr, (params, results)) in [
+            (Instr::I32Extend8S, i32s()),
+            (Instr::I32Extend16S, i32s()),
+            (Instr::I64Extend8S, i64s()),
+            (Instr::I64Extend16S, i64s()),
+            (Instr::I64Extend32S, i64s()),
+        ] {
+            let module = module_with_body(params, results, vec![Instr::LocalGet(idx::Local(0)), instr]);
+            assert_eq!(func_error(&module), None);
+        }
+
+        let module = module_with_body(vec![ty::Val::I32], vec![ty::Val::I64], vec![Instr::LocalGet(idx::Local(0)), Instr::I64Extend32S]);
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(ty::Val::I64), found: Operand::Val(ty::Val::I32) }));
+    }
+
+    #[test]
+    fn can_validate_saturating_truncation_instructions() {
+        use ty::Val::*;
+        for (instr, param, result) in [
+            (Instr::I32TruncSatF32S, F32, I32),
+            (Instr::I32TruncSatF32U, F32, I32),
+            (Instr::I32TruncSatF64S, F64, I32),
+            (Instr::I32TruncSatF64U, F64, I32),
+            (Instr::I64TruncSatF32S, F32, I64),
+            (Instr::I64TruncSatF32U, F32, I64),
+            (Instr::I64TruncSatF64S, F64, I64),
+            (Instr::I64TruncSatF64U, F64, I64),
+        ] {
+            let module = module_with_body(vec![param], vec![result], vec![Instr::LocalGet(idx::Local(0)), instr]);
+            assert_eq!(func_error(&module), None);
+        }
+
+        let module = module_with_body(vec![F32], vec![I32], vec![Instr::LocalGet(idx::Local(0)), Instr::I32TruncSatF64S]);
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(F64), found: Operand::Val(F32) }));
+    }
+
+    #[test]
+    fn can_validate_multi_value_blocks() {
+        use ty::Val::*;
+        // type 1 is the block type, which is the same as the type of the function
+        let swap = |body| ModuleBuilder::new()
+            .types(vec![ty::Func { params: vec![I32, I64], results: vec![I64, I32] }.into()])
+            .body(vec![I32, I64], vec![I64, I32], body)
+            .build();
+        let with_args = |instrs: Vec<Instr>| {
+            vec![Instr::LocalGet(idx::Local(0)), Instr::LocalGet(idx::Local(1))].into_iter().chain(instrs).collect()
+        };
+        let body = || instr::Expr(vec![Instr::Drop, Instr::Drop, Instr::ConstI64(0), Instr::ConstI32(0)]);
+
+        let block = Instr::Block(ty::RetVal::Type(idx::Type(1)), body());
+        assert_eq!(func_error(&swap(with_args(vec![block]))), None);
+
+        let if_else = Instr::If(ty::RetVal::Type(idx::Type(1)), body(), body());
+        assert_eq!(func_error(&swap(with_args(vec![Instr::ConstI32(1), if_else]))), None);
+
+        // the label of a loop is given by its params
+        let looped = Instr::Loop(ty::RetVal::Type(idx::Type(1)), instr::Expr(vec![Instr::Br(idx::Label(0))]));
+        assert_eq!(func_error(&swap(with_args(vec![looped]))), None);
+
+        // block params are taken from the enclosing frame
+        let block = Instr::Block(ty::RetVal::Type(idx::Type(1)), body());
+        assert_eq!(func_error(&swap(vec![block])), Some(Error::EmptyOperandStack { expected: Operand::Val(I64) }));
+
+        // an if without else passes its params through as results
+        let if_only = Instr::If(ty::RetVal::Type(idx::Type(1)), instr::Expr(vec![Instr::Drop, Instr::ConstI64(0)]), instr::Expr(vec![]));
+        let module = ModuleBuilder::new()
+            .types(vec![ty::Func { params: vec![I32], results: vec![I64] }.into()])
+            .body(vec![I32], vec![I64], vec![Instr::LocalGet(idx::Local(0)), Instr::ConstI32(1), if_only])
+            .build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(I64), found: Operand::Val(I32) }));
+
+        let module = module_with_body(vec![], vec![I32, I64, F32], vec![Instr::ConstI32(0), Instr::ConstI64(0), Instr::ConstF32(0.0)]);
+        assert_eq!(func_error(&module), None);
+    }
+
+    #[test]
+    fn can_validate_bulk_memory_instructions() {
+        let module = module_with_body(vec![], vec![], vec![
+            Instr::ConstI32(0), Instr::ConstI32(8), Instr::ConstI32(4), Instr::MemCopy(idx::Mem(0), idx::Mem(0)),
+            Instr::ConstI32(0), Instr::ConstI32(0), Instr::ConstI32(4), Instr::MemFill(idx::Mem(0)),
+        ]);
+        assert_eq!(func_error(&module), None);
+
+        let module = module_with_body(vec![], vec![], vec![
+            Instr::ConstI32(0), Instr::ConstI64(0), Instr::ConstI32(4), Instr::MemFill(idx::Mem(0)),
+        ]);
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(ty::Val::I32), found: Operand::Val(ty::Val::I64) }));
+
+        let module = module_with_body(vec![], vec![], vec![Instr::ElemDrop(idx::Elem(0))]);
+        assert_eq!(func_error(&module), Some(Error::UnknownElem { idx: 0 }));
+    }
+
+    #[test]
+    fn can_validate_reference_instructions() {
+        let module = ModuleBuilder::new().table(ty::Ref::FUNC).table(ty::Ref::EXTERN).body(vec![ty::Val::EXTERN_REF], vec![ty::Val::I32], vec![
+            Instr::ConstI32(0), Instr::LocalGet(idx::Local(0)), Instr::TableSet(idx::Table(1)),
+            Instr::ConstI32(0), Instr::TableGet(idx::Table(1)),
+            Instr::RefNull(ty::Heap::Extern), Instr::ConstI32(1), Instr::SelectTyped(vec![ty::Val::EXTERN_REF]),
+            Instr::RefIsNull,
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().table(ty::Ref::FUNC).table(ty::Ref::EXTERN).body(vec![ty::Val::EXTERN_REF], vec![ty::Val::I32], vec![
+            Instr::ConstI32(0), Instr::RefNull(ty::Heap::Func), Instr::TableSet(idx::Table(1)), Instr::ConstI32(0),
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch {
+            expected: Operand::Val(ty::Val::EXTERN_REF),
+            found: Operand::Val(ty::Val::FUNC_REF),
+        }));
+
+        let module = ModuleBuilder::new().table(ty::Ref::FUNC).table(ty::Ref::EXTERN).body(vec![ty::Val::EXTERN_REF], vec![ty::Val::I32], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::LocalGet(idx::Local(0)), Instr::ConstI32(1), Instr::Select, Instr::RefIsNull,
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::InvalidSelectOperand { found: Operand::Val(ty::Val::EXTERN_REF) }));
+
+        let module = ModuleBuilder::new().table(ty::Ref::FUNC).table(ty::Ref::EXTERN).body(vec![ty::Val::EXTERN_REF], vec![ty::Val::I32], vec![Instr::ConstI32(0), Instr::RefIsNull]).build();
+        assert_eq!(func_error(&module), Some(Error::ExpectedReference { found: Operand::Val(ty::Val::I32) }));
+
+        let module = ModuleBuilder::new().table(ty::Ref::FUNC).table(ty::Ref::EXTERN).body(vec![ty::Val::EXTERN_REF], vec![ty::Val::I32], vec![
+            Instr::ConstI32(0), Instr::ConstI32(0), Instr::ConstI32(0), Instr::TableCopy(idx::Table(0), idx::Table(1)), Instr::ConstI32(0),
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::ElemTypeMismatch { expected: ty::Ref::FUNC, found: ty::Ref::EXTERN }));
+    }
+
+    #[test]
+    fn can_validate_vector_instructions() {
+        let v128 = ty::Val::V128;
+        let module = module_with_body(vec![v128], vec![ty::Val::F32], vec![
+            Instr::ConstI32(0), Instr::V128Load(memarg(4, 0)),
+            Instr::LocalGet(idx::Local(0)),
+            Instr::I8x16Shuffle([0, 1, 2, 3, 4, 5, 6, 7, 16, 17, 18, 19, 20, 21, 22, 23]),
+            Instr::ConstI32(1), Instr::I32x4Shl,
+            Instr::ConstF32(1.0), Instr::F32x4Splat, Instr::F32x4Add,
+            Instr::V128AnyTrue, Instr::LocalGet(idx::Local(0)), Instr::V128Load32Lane(memarg(2, 0), 3),
+            Instr::F32x4ExtractLane(3),
+        ]);
+        assert_eq!(func_error(&module), None);
+
+        let module = module_with_body(vec![v128], vec![ty::Val::I64], vec![Instr::LocalGet(idx::Local(0)), Instr::I64x2ExtractLane(2)]);
+        assert_eq!(func_error(&module), Some(Error::InvalidLaneIndex { lane: 2, lanes: 2 }));
+
+        let module = module_with_body(vec![v128], vec![v128], vec![Instr::LocalGet(idx::Local(0)), Instr::LocalGet(idx::Local(0)), Instr::I8x16Shuffle([32; 16])]);
+        assert_eq!(func_error(&module), Some(Error::InvalidLaneIndex { lane: 32, lanes: 32 }));
+
+        let module = module_with_body(vec![], vec![ty::Val::V128], vec![Instr::ConstI32(0), Instr::V128Load64Splat(memarg(4, 0))]);
+        assert_eq!(func_error(&module), Some(Error::InvalidAlignment { align: 4, natural: 3 }));
+
+        let module = module_with_body(vec![v128], vec![ty::Val::I32], vec![Instr::LocalGet(idx::Local(0)), Instr::ConstI64(1), Instr::I64x2Shl, Instr::V128AnyTrue]);
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(ty::Val::I32), found: Operand::Val(ty::Val::I64) }));
+    }
+
+    #[test]
+    fn can_validate_atomic_instructions() {
+        let module = module_with_body(vec![], vec![ty::Val::I64], vec![
+            Instr::ConstI32(0), Instr::ConstI64(1), Instr::ConstI64(2), Instr::I64AtomicRmw16CmpxchgU(memarg(1, 0)),
+            Instr::AtomicFence,
+        ]);
+        assert_eq!(func_error(&module), None);
+
+        let module = module_with_body(vec![], vec![ty::Val::I32], vec![Instr::ConstI32(0), Instr::I32AtomicLoad(memarg(1, 0))]);
+        assert_eq!(func_error(&module), Some(Error::InvalidAtomicAlignment { align: 1, natural: 2 }));
+
+        let module = module_with_body(vec![], vec![ty::Val::I32], vec![
+            Instr::ConstI32(0), Instr::ConstI32(0), Instr::ConstI32(0), Instr::MemAtomicWait64(memarg(3, 0)),
+        ]);
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(ty::Val::I64), found: Operand::Val(ty::Val::I32) }));
+    }
+
+    #[test]
+    fn can_validate_tail_calls() {
+        let module = module_with_body(vec![ty::Val::I32], vec![ty::Val::I32], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::ReturnCall(idx::Func(0)), Instr::Drop,
+        ]);
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new()
+            .types(vec![ty::Func { params: vec![], results: vec![ty::Val::I64] }.into()])
+            .table(ty::Ref::FUNC)
+            .body(vec![], vec![ty::Val::I32], vec![Instr::ConstI32(0), Instr::ReturnCallIndirect(idx::Type(1), idx::Table(0))])
+            .build();
+        assert_eq!(func_error(&module), Some(Error::TailCallResultMismatch { expected: vec![ty::Val::I32], found: vec![ty::Val::I64] }));
+    }
+
+    #[test]
+    fn can_validate_exception_instructions() {
+        use ty::Val::*;
+        let block = |results, body| Instr::Block(ty::RetVal::Val(results), instr::Expr(body));
+        let try_table = |catches, body| Instr::TryTable(ty::RetVal::Empty, catches, instr::Expr(body));
+
+        let module = ModuleBuilder::new().tag(vec![I32]).body(vec![], vec![], vec![
+            block(I32, vec![try_table(vec![instr::Catch::Catch(idx::Tag(0), idx::Label(0))], vec![
+                Instr::ConstI32(1), Instr::Throw(idx::Tag(0)),
+            ]), Instr::ConstI32(0)]),
+            Instr::Drop,
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().tag(vec![I32]).body(vec![], vec![], vec![
+            block(ty::Val::EXN_REF, vec![try_table(vec![instr::Catch::CatchAllRef(idx::Label(0))], vec![]), Instr::RefNull(ty::Heap::Exn)]),
+            Instr::ThrowRef,
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        // the label of a catch clause must match the values carried by the exception
+        let module = ModuleBuilder::new().tag(vec![I32]).body(vec![], vec![], vec![
+            block(I32, vec![try_table(vec![instr::Catch::CatchRef(idx::Tag(0), idx::Label(0))], vec![]), Instr::ConstI32(0)]),
+            Instr::Drop,
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::LabelTypeMismatch { expected: vec![I32, ty::Val::EXN_REF], found: vec![I32] }));
+
+        let module = ModuleBuilder::new().tag(vec![I32]).body(vec![], vec![], vec![Instr::Throw(idx::Tag(1))]).build();
+        assert_eq!(func_error(&module), Some(Error::UnknownTag { idx: 1 }));
+
+        let module = ModuleBuilder::new().tag(vec![I32]).body(vec![], vec![], vec![Instr::ConstI64(0), Instr::Throw(idx::Tag(0))]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(I32), found: Operand::Val(I64) }));
+    }
+
+    #[test]
+    fn can_validate_legacy_exception_instructions() {
+        let module = ModuleBuilder::new().tag(vec![ty::Val::I32]).body(vec![], vec![], vec![
+            Instr::Try(ty::RetVal::Empty, instr::Expr(vec![
+                Instr::TryDelegate(ty::RetVal::Empty, instr::Expr(vec![]), idx::Label(1)),
+            ]), vec![
+                (idx::Tag(0), instr::Expr(vec![Instr::Drop])),
+            ], Some(instr::Expr(vec![Instr::Rethrow(idx::Label(0))]))),
+        ]).build();
+        assert_eq!(func_error(&module), None);
+        let features = Features { legacy_exceptions: false, .. Features::ALL };
+        assert!(matches!(
+            module.validate_with(features),
+            Err(Error::InFunction { error, .. }) if *error == Error::FeatureDisabled { feature: "legacy-exceptions" },
+        ));
+
+        let module = module_with_body(vec![], vec![], vec![
+            Instr::Try(ty::RetVal::Empty, instr::Expr(vec![Instr::Rethrow(idx::Label(0))]), vec![], None),
+        ]);
+        assert_eq!(func_error(&module), Some(Error::InvalidRethrowLabel { idx: 0 }));
+    }
+
+    #[test]
+    fn can_validate_64_bit_memories() {
+        use ty::Val::*;
+        let module = ModuleBuilder::new().mem(ty::Addr::I64).body(vec![], vec![I32], vec![Instr::ConstI64(0), Instr::I32Load(memarg(2, 1 << 32))]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I64).body(vec![], vec![I64], vec![Instr::ConstI64(1), Instr::MemGrow(idx::Mem(0))]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I64).body(vec![], vec![], vec![
+            Instr::ConstI64(0), Instr::ConstI32(0), Instr::ConstI64(8), Instr::MemFill(idx::Mem(0)),
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I64).body(vec![], vec![I32], vec![Instr::ConstI32(0), Instr::I32Load(memarg(2, 0))]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(I64), found: Operand::Val(I32) }));
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I64).body(vec![], vec![I32], vec![Instr::MemSize(idx::Mem(0))]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(I32), found: Operand::Val(I64) }));
+
+        // offsets into 32-bit memories must fit in 32 bits
+        let module = module_with_body(vec![], vec![I32], vec![Instr::ConstI32(0), Instr::I32Load(memarg(2, 1 << 32))]);
+        assert_eq!(func_error(&module), Some(Error::OffsetOutOfRange { offset: 1 << 32 }));
+    }
+
+    #[test]
+    fn can_validate_multiple_memories() {
+        use ty::Val::*;
+        let load = |mem| Instr::I32Load(instr::MemArg { align: 2, offset: 0, mem: idx::Mem(mem) });
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).mem(ty::Addr::I64).body(vec![], vec![I32], vec![Instr::ConstI64(0), load(1)]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).mem(ty::Addr::I64).body(vec![], vec![I32], vec![Instr::ConstI64(0), load(0)]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(I32), found: Operand::Val(I64) }));
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).mem(ty::Addr::I64).body(vec![], vec![I32], vec![Instr::ConstI32(0), load(2)]).build();
+        assert_eq!(func_error(&module), Some(Error::UnknownMem { idx: 2 }));
+
+        // the length of a copy between memories takes the smallest address type
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).mem(ty::Addr::I64).body(vec![], vec![], vec![
+            Instr::ConstI64(0), Instr::ConstI32(0), Instr::ConstI32(8), Instr::MemCopy(idx::Mem(1), idx::Mem(0)),
+            Instr::MemSize(idx::Mem(1)), Instr::Drop,
+        ]).build();
+        assert_eq!(func_error(&module), None);
+    }
+
+    fn ref_to(nullable: bool, ty: u32) -> ty::Val {
+        ty::Val::Ref(ty::Ref { nullable, heap: ty::Heap::Type(idx::Type(ty)) })
+    }
+
+    #[test]
+    fn can_validate_gc_instructions() {
+        let get = || vec![Instr::LocalGet(idx::Local(0)), Instr::StructGetU(idx::Type(1), idx::Field(0))];
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(true, 1)], vec![ty::Val::I32], get()).build();
+        assert_eq!(func_error(&module), None);
+
+        // references to subtypes can be used where their supertypes are expected
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(false, 2)], vec![ty::Val::I32], get()).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(true, 1)], vec![ty::Val::I32], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::StructGet(idx::Type(2), idx::Field(1)),
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch {
+            expected: Operand::Val(ref_to(true, 2)),
+            found: Operand::Val(ref_to(true, 1)),
+        }));
+
+        let module = ModuleBuilder::new().gc_types().body(vec![], vec![ty::Val::I32], vec![
+            Instr::ConstI32(1), Instr::ConstI32(2), Instr::StructNew(idx::Type(2)),
+            Instr::StructGetS(idx::Type(1), idx::Field(0)),
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(true, 1)], vec![ty::Val::I32], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::StructGet(idx::Type(1), idx::Field(0)),
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::PackedFieldMismatch { ty: 1, field: 0 }));
+
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(true, 2)], vec![], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::ConstI32(0), Instr::StructSet(idx::Type(2), idx::Field(1)),
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::ImmutableField { ty: 2, field: 1 }));
+
+        let module = ModuleBuilder::new().gc_types().body(vec![], vec![ty::Val::I32], vec![
+            Instr::ConstI32(7), Instr::ConstI32(3), Instr::ArrayNew(idx::Type(3)), Instr::ArrayLen,
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().gc_types().body(vec![], vec![ty::Val::I32], vec![
+            Instr::RefNull(ty::Heap::Type(idx::Type(4))), Instr::CallRef(idx::Type(4)),
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().gc_types().body(vec![], vec![ty::Val::I32], vec![
+            Instr::ConstI32(7), Instr::RefI31, Instr::I31GetS,
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().gc_types().body(vec![], vec![], vec![Instr::StructNew(idx::Type(4)), Instr::Drop]).build();
+        assert_eq!(func_error(&module), Some(Error::UnexpectedCompositeType { idx: 4, expected: "struct" }));
+
+        // casts only apply to references of the same hierarchy as their target
+        let any_ref = ty::Val::Ref(ty::Ref { nullable: true, heap: ty::Heap::Any });
+        let struct_ref = ty::Ref { nullable: false, heap: ty::Heap::Type(idx::Type(1)) };
+        let module = ModuleBuilder::new().gc_types().body(vec![any_ref], vec![ty::Val::I32], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::RefTest(struct_ref),
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().gc_types().body(vec![ty::Val::FUNC_REF], vec![], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::RefCast(struct_ref), Instr::Drop,
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch {
+            expected: Operand::Val(any_ref),
+            found: Operand::Val(ty::Val::FUNC_REF),
+        }));
+
+        let module = ModuleBuilder::new().gc_types().body(vec![any_ref], vec![ty::Val::I32], vec![
+            Instr::LocalGet(idx::Local(0)), Instr::RefTest(ty::Ref { nullable: true, heap: ty::Heap::Extern }),
+        ]).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch {
+            expected: Operand::Val(ty::Val::EXTERN_REF),
+            found: Operand::Val(any_ref),
+        }));
+    }
+
+    #[test]
+    fn catch_clauses_can_branch_to_supertypes() {
+        let any_ref = ty::Val::Ref(ty::Ref { nullable: true, heap: ty::Heap::Any });
+        let catch = |param, label| {
+            let try_table = Instr::TryTable(ty::RetVal::Empty, vec![instr::Catch::Catch(idx::Tag(0), idx::Label(0))], instr::Expr(vec![]));
+            ModuleBuilder::new().gc_types().tag(vec![param]).body(vec![], vec![], vec![
+                Instr::Block(ty::RetVal::Val(label), instr::Expr(vec![try_table, Instr::RefNull(ty::Heap::None)])),
+                Instr::Drop,
+            ]).build()
+        };
+        assert_eq!(func_error(&catch(ref_to(false, 2), any_ref)), None);
+        assert_eq!(func_error(&catch(ref_to(false, 2), ref_to(true, 1))), None);
+        assert_eq!(func_error(&catch(any_ref, ref_to(true, 2))), Some(Error::LabelTypeMismatch { expected: vec![any_ref], found: vec![ref_to(true, 2)] }));
+    }
+
+    #[test]
+    fn can_validate_gc_branches() {
+        use crate::structure::instr::Expr;
+
+        // the reference is known to be non-null when `br_on_null` falls through
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(true, 1)], vec![ty::Val::I32], vec![
+            Instr::Block(ty::RetVal::Empty, Expr(vec![
+                Instr::LocalGet(idx::Local(0)), Instr::BrOnNull(idx::Label(0)), Instr::LocalSet(idx::Local(0)),
+            ])),
+            Instr::ConstI32(0),
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let cast = |label| Instr::Block(ty::RetVal::Val(label), Expr(vec![
+            Instr::LocalGet(idx::Local(0)),
+            Instr::BrOnCast(idx::Label(0), ty::Ref { nullable: true, heap: ty::Heap::Type(idx::Type(1)) }, ty::Ref { nullable: false, heap: ty::Heap::Type(idx::Type(2)) }),
+            Instr::Unreachable,
+        ]));
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(true, 1)], vec![ty::Val::I32], vec![
+            cast(ref_to(false, 2)), Instr::StructGet(idx::Type(2), idx::Field(1)),
+        ]).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().gc_types().body(vec![ref_to(true, 1)], vec![], vec![cast(ref_to(false, 3)), Instr::Drop]).build();
+        assert_eq!(func_error(&module), Some(Error::LabelTypeMismatch { expected: vec![ref_to(false, 3)], found: vec![ref_to(false, 2)] }));
+
+        // the targets of `br_table` can have different types that accept the operand
+        let table = |param| ModuleBuilder::new().gc_types().body(vec![param, ty::Val::I32], vec![], vec![
+            Instr::Block(ty::RetVal::Val(ref_to(true, 1)), Expr(vec![
+                Instr::Block(ty::RetVal::Val(ref_to(false, 2)), Expr(vec![
+                    Instr::LocalGet(idx::Local(0)), Instr::LocalGet(idx::Local(1)),
+                    Instr::BrTable(vec![idx::Label(0)], idx::Label(1)),
+                ])),
+            ])),
+            Instr::Drop,
+        ]).build();
+        assert_eq!(func_error(&table(ref_to(false, 2))), None);
+        assert_eq!(func_error(&table(ref_to(true, 1))), Some(Error::TypeMismatch {
+            expected: Operand::Val(ref_to(false, 2)),
+            found: Operand::Val(ref_to(true, 1)),
+        }));
+    }
+
+
+    #[test]
+    fn can_validate_relaxed_vector_instructions() {
+        use ty::Val::*;
+        let get = |n| (0..n).map(|i| Instr::LocalGet(idx::Local(i))).collect::<Vec<_>>();
+        for (instr, arity) in [
+            (Instr::I32x4RelaxedTruncF64x2UZero, 1),
+            (Instr::F64x2RelaxedMin, 2),
+            (Instr::I16x8RelaxedQ15mulrS, 2),
+            (Instr::F32x4RelaxedNmadd, 3),
+            (Instr::I8x16RelaxedLaneselect, 3),
+            (Instr::I32x4RelaxedDotI8x16I7x16AddS, 3),
+        ] {
+            let mut body = get(arity);
+            body.push(instr);
+            assert_eq!(func_error(&module_with_body(vec![V128; arity as usize], vec![V128], body)), None);
+        }
+
+        let mut body = get(2);
+        body.push(Instr::F32x4RelaxedMadd);
+        let module = module_with_body(vec![V128, V128], vec![V128], body);
+        assert_eq!(func_error(&module), Some(Error::EmptyOperandStack { expected: Operand::Val(V128) }));
+    }
+
+    #[test]
+    fn relaxed_vector_instructions_can_be_disabled() {
+        use ty::Val::*;
+        let body = vec![Instr::LocalGet(idx::Local(0)), Instr::LocalGet(idx::Local(1)), Instr::I8x16RelaxedSwizzle];
+        let module = module_with_body(vec![V128, V128], vec![V128], body);
+        assert!(module.validate_with(Features::ALL).is_ok());
+        let features = Features { relaxed_simd: false, .. Features::ALL };
+        assert!(matches!(
+            module.validate_with(features),
+            Err(Error::InFunction { error, .. }) if *error == Error::FeatureDisabled { feature: "relaxed-simd" },
+        ));
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 pub mod context;
 pub mod error;
+pub mod features;
 pub mod stacks;
 
 pub mod idx;
@@ -7,15 +8,24 @@ pub mod instr;
 pub mod module;
 pub mod ty;
 
+#[cfg(feature = "parallel")]
+pub mod parallel;
+
 pub use self::context::Context;
 pub use self::error::Error;
+pub use self::features::Features;
+#[cfg(feature = "parallel")]
+pub use self::parallel::ParallelValidationEntry;
 
 pub type Result<T> = std::result::Result<T, error::Error>;
 
 pub trait ValidationEntry<'a>
 where Self: Sized {
     type ValidationResult;
-    fn validate(&'a self) -> Result<Self::ValidationResult>;
+    fn validate_with(&'a self, features: Features) -> Result<Self::ValidationResult>;
+    fn validate(&'a self) -> Result<Self::ValidationResult> {
+        self.validate_with(Features::default())
+    }
 }
 
 pub trait Validate<'a>
@@ -1,5 +1,6 @@
-use crate::validation::{Validate, ValidationEntry, Context, Result, Error};
-use crate::structure::module;
+use crate::validation::{Validate, ValidationEntry, Context, Result, Error, Features, error::Location, features::require, ty::{is_subtype, validate_sub}};
+use crate::structure::{module, instr};
+use crate::binary::offsets::{Offsets, WithOffsets};
 use crate::structure::ty;
 
 pub mod result {
@@ -15,96 +16,176 @@ pub mod result {
         Table ( &'a ty::Table ),
         Mem ( &'a ty::Mem ),
         Global ( &'a ty::Global ),
+        Tag ( &'a ty::Func ),
     }
 }
 
 impl<'a> ValidationEntry<'a> for module::Module {
     type ValidationResult = <module::Module as Validate<'a>>::ValidationResult;
-    fn validate(&'a self) -> Result<Self::ValidationResult> {
-        let mut ctx = Context::default();
+    fn validate_with(&'a self, features: Features) -> Result<Self::ValidationResult> {
+        let mut ctx = Context { features, .. Context::default() };
         <module::Module as Validate<'a>>::validate(self, &mut ctx)
     }
 }
 
+impl<'a> ValidationEntry<'a> for WithOffsets<module::Module> {
+    type ValidationResult = <module::Module as Validate<'a>>::ValidationResult;
+    fn validate_with(&'a self, features: Features) -> Result<Self::ValidationResult> {
+        let WithOffsets(module, offsets) = self;
+        module.validate_with(features).map_err(|error| locate(error, module, offsets))
+    }
+}
+
+/// Fills the byte offset of the location of an error found in a function body.
+pub(crate) fn locate(error: Error, module: &module::Module, offsets: &Offsets) -> Error {
+    match error {
+        Error::InFunction { mut location, error } => {
+            let imported_funcs = module.imports.iter()
+                .filter(|import| matches!(import.desc, module::desc::Import::Func(_)))
+                .count();
+            let func = (location.func as usize).checked_sub(imported_funcs);
+            location.offset = func.zip(location.instr)
+                .and_then(|(func, instr)| offsets.instr_offset(func, instr));
+            Error::InFunction { location, error }
+        },
+        error => error,
+    }
+}
+
 impl<'a> Validate<'a> for module::Module {
     type ValidationResult = result::Module<'a>;
-    fn validate(&'a self, _ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
-        let mut ctx = Context::from(&self)?;
-        let mut ctx_globals = Context::default();
-        ctx_globals.use_globals(&self.globals);
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        validate_module(self, ctx.features, validate_funcs)
+    }
+}
 
-        for ty in self.types.iter() {
-            ty.validate(&mut ctx)?;
-        }
-        for func in self.funcs.iter() {
-            func.validate(&mut ctx)?;
-        }
-        for table in self.tables.iter() {
-            table.validate(&mut ctx)?;
-        }
-        for mem in self.mems.iter() {
-            mem.validate(&mut ctx)?;
-        }
-        for global in self.globals.iter() {
-            global.validate(&mut ctx_globals)?;
-        }
-        for elem in self.elem.iter() {
-            elem.validate(&mut ctx)?;
-        }
-        for data in self.data.iter() {
-            data.validate(&mut ctx)?;
-        }
-        if let Some(ref start) = self.start {
-            start.validate(&mut ctx)?;
-        }
-        let mut imports = Vec::with_capacity(self.imports.len());
-        for import in self.imports.iter() {
-            let ty = import.validate(&mut ctx)?;
-            imports.push(ty);
-        }
-        let mut exports = Vec::with_capacity(self.exports.len());
-        for export in self.exports.iter() {
-            let ty = export.validate(&mut ctx)?;
-            exports.push(ty);
-        }
+fn validate_funcs<'a>(module: &'a module::Module, ctx: &mut Context<'a>) -> Result<()> {
+    let imported_funcs = ctx.funcs.len() - module.funcs.len();
+    for (i, func) in module.funcs.iter().enumerate() {
+        validate_func(func, imported_funcs + i, ctx)?;
+    }
+    Ok(())
+}
 
-        if !(self.tables.len() <= 1) {
-            return Err(Error::UnexpectedEndOfFile);
-        }
-        if !(self.mems.len() <= 1) {
-            return Err(Error::UnexpectedEndOfFile);
-        }
+/// Validates the function with the given index, attaching its location to any error found.
+pub(crate) fn validate_func<'a>(func: &'a module::Func, idx: usize, ctx: &mut Context<'a>) -> Result<()> {
+    func.validate(ctx).map_err(|error| Error::InFunction {
+        location: Location {
+            func: idx as u32,
+            instr: ctx.instrs.checked_sub(1),
+            offset: None,
+        },
+        error: Box::new(error),
+    })?;
+    Ok(())
+}
 
-        let mut export_names = self.exports.iter()
-            .map(|e| &e.name)
-            .collect::<Vec<_>>();
-        export_names.sort();
-        export_names.dedup();
+/// Validates a module that may only use the given features, using `validate_funcs` to validate
+/// the bodies of its functions.
+pub(crate) fn validate_module<'a, F>(module: &'a module::Module, features: Features, validate_funcs: F) -> Result<result::Module<'a>>
+where F: FnOnce(&'a module::Module, &mut Context<'a>) -> Result<()> {
+    let mut ctx = Context::from(module)?;
+    ctx.features = features;
+    let mut ctx_globals = Context { features, .. Context::default() };
+    ctx_globals.use_types(&module.types);
+    ctx_globals.use_imported_funcs(&module.imports)?;
+    ctx_globals.use_funcs(&module.funcs)?;
+    // initializers can only refer to the imported globals and to the globals defined before them
+    ctx_globals.use_imported_globals(&module.imports);
+    ctx_globals.use_refs(module);
 
-        if export_names.len() != self.exports.len() {
-            return Err(Error::UnexpectedEndOfFile);
+    if module.types.iter().any(|rec| rec.types.len() != 1) {
+        require(features.gc, "gc")?;
+    }
+    for (idx, ty) in module.types.iter().flat_map(|rec| rec.types.iter()).enumerate() {
+        validate_sub(ty, idx as u32, &mut ctx)?;
+    }
+    if ctx.tables.len() > 1 {
+        require(features.reference_types, "reference-types")?;
+    }
+    if ctx.mems.len() > 1 {
+        require(features.multi_memory, "multi-memory")?;
+    }
+    validate_funcs(module, &mut ctx)?;
+    for table in module.tables.iter() {
+        table.validate(&mut ctx)?;
+    }
+    for mem in module.mems.iter() {
+        mem.validate(&mut ctx)?;
+    }
+    for tag in module.tags.iter() {
+        tag.validate(&mut ctx)?;
+    }
+    for global in module.globals.iter() {
+        let ty = global.validate(&mut ctx_globals)?;
+        ctx_globals.globals.push(ty);
+    }
+    for elem in module.elem.iter() {
+        elem.validate(&mut ctx)?;
+    }
+    for data in module.data.iter() {
+        data.validate(&mut ctx)?;
+    }
+    if let Some(count) = module.data_count {
+        require(features.bulk_memory, "bulk-memory")?;
+        if count as usize != module.data.len() {
+            return Err(Error::DataCountMismatch { count, found: module.data.len() });
         }
+    }
+    if let Some(ref start) = module.start {
+        start.validate(&mut ctx)?;
+    }
+    let mut imports = Vec::with_capacity(module.imports.len());
+    for import in module.imports.iter() {
+        let ty = import.validate(&mut ctx)?;
+        imports.push(ty);
+    }
+    let mut exports = Vec::with_capacity(module.exports.len());
+    for export in module.exports.iter() {
+        let ty = export.validate(&mut ctx)?;
+        exports.push(ty);
+    }
 
-        Ok(Self::ValidationResult {
-            imports,
-            exports,
-        })
+    let mut export_names = module.exports.iter()
+        .map(|e| &e.name)
+        .collect::<Vec<_>>();
+    export_names.sort();
+
+    if let Some(name) = export_names.windows(2).find(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateExportName { name: name[0].clone() });
     }
+
+    Ok(result::Module {
+        imports,
+        exports,
+    })
 }
 
 impl<'a> Validate<'a> for module::Func {
     type ValidationResult = &'a ty::Func;
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        ctx.instrs = 0;
+        ctx.stacks.operands.clear();
+        ctx.stacks.frames.clear();
         let ty = self.ty.validate(ctx)?;
         let ret = &ty.results[..];
 
         ctx.ret = Some(ret);
+        for local in self.locals.iter() {
+            local.validate(ctx)?;
+        }
         ctx.locals = ty.params.iter().chain(self.locals.iter()).collect();
-        ctx.stacks.push_frame(ret, ret);
-        self.body.validate(ctx)?;
+        ctx.stacks.push_frame(ret, &[], ret);
+        let body = self.body.expr().map_err(|error| Error::MalformedBody { error })?;
+        body.validate(ctx)?;
+        // the final `end` of the function body
+        ctx.instrs += 1;
         ctx.stacks.pop_frame()?;
-        if !ctx.stacks.frames.is_empty() || !ctx.stacks.operands.is_empty() {
-            return Err(Error::UnexpectedEndOfFile);
+        if !ctx.stacks.frames.is_empty() {
+            return Err(Error::UnbalancedControlStack { frames: ctx.stacks.frames.len() });
+        }
+        if !ctx.stacks.operands.is_empty() {
+            return Err(Error::UnbalancedOperandStack { expected: 0, found: ctx.stacks.operands.len() });
         }
         ctx.locals.clear();
         ctx.ret = None;
@@ -129,19 +210,18 @@ impl<'a> Validate<'a> for module::Mem {
     }
 }
 
+impl<'a> Validate<'a> for module::Tag {
+    type ValidationResult = &'a ty::Func;
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        self.ty.validate(ctx)
+    }
+}
+
 impl<'a> Validate<'a> for module::Global {
     type ValidationResult = &'a ty::Global;
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         self.ty.validate(ctx)?;
-        let ret = std::slice::from_ref(&self.ty.val);
-
-        ctx.stacks.push_frame(ret, ret);
-        self.init.validate(ctx)?;
-        ctx.stacks.pop_frame()?;
-        if !ctx.stacks.frames.is_empty() || !ctx.stacks.operands.is_empty() {
-            return Err(Error::UnexpectedEndOfFile);
-        }
-
+        validate_const_expr(&self.init, std::slice::from_ref(&self.ty.val), ctx)?;
         Ok(&self.ty)
     }
 }
@@ -149,21 +229,33 @@ impl<'a> Validate<'a> for module::Global {
 impl<'a> Validate<'a> for module::Elem {
     type ValidationResult = ();
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
-        let ty = self.table.validate(ctx)?;
-        if ty.elem != ty::Elem::FuncRef {
-            return Err(Error::UnexpectedEndOfFile);
+        match &self.mode {
+            module::ElemMode::Passive => require(ctx.features.bulk_memory, "bulk-memory")?,
+            module::ElemMode::Declarative => require(ctx.features.reference_types, "reference-types")?,
+            module::ElemMode::Active { .. } => (),
         }
-
-        let ret = &[ty::Val::I32];
-        ctx.stacks.push_frame(ret, ret);
-        self.offset.validate(ctx)?;
-        ctx.stacks.pop_frame()?;
-        if !ctx.stacks.frames.is_empty() || !ctx.stacks.operands.is_empty() {
-            return Err(Error::UnexpectedEndOfFile);
+        if self.ty != ty::Ref::FUNC {
+            ctx.features.check_val(self.ty.into())?;
+        }
+        match &self.init {
+            module::ElemInit::Funcs(funcs) => {
+                for func in funcs.iter() {
+                    func.validate(ctx)?;
+                }
+            },
+            module::ElemInit::Exprs(exprs) => {
+                for expr in exprs.iter() {
+                    validate_const_expr(expr, &[ty::Val::Ref(self.ty)], ctx)?;
+                }
+            },
         }
 
-        for fcn in self.init.iter() {
-            fcn.validate(ctx)?;
+        if let module::ElemMode::Active { table, offset } = &self.mode {
+            let table = table.validate(ctx)?;
+            if !is_subtype(&ctx.types, self.ty.into(), table.elem.into()) {
+                return Err(Error::ElemTypeMismatch { expected: table.elem, found: self.ty });
+            }
+            validate_const_expr(offset, &[ty::Val::I32], ctx)?;
         }
         Ok(())
     }
@@ -172,26 +264,47 @@ impl<'a> Validate<'a> for module::Elem {
 impl<'a> Validate<'a> for module::Data {
     type ValidationResult = ();
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
-        self.mem.validate(ctx)?;
-
-        let ret = &[ty::Val::I32];
-        ctx.stacks.push_frame(ret, ret);
-        self.offset.validate(ctx)?;
-        ctx.stacks.pop_frame()?;
-        if !ctx.stacks.frames.is_empty() || !ctx.stacks.operands.is_empty() {
-            return Err(Error::UnexpectedEndOfFile);
+        if let module::DataMode::Passive = self.mode {
+            require(ctx.features.bulk_memory, "bulk-memory")?;
+        }
+        if let module::DataMode::Active { mem, offset } = &self.mode {
+            let ret = match mem.validate(ctx)?.addr {
+                ty::Addr::I32 => &[ty::Val::I32],
+                ty::Addr::I64 => &[ty::Val::I64],
+            };
+            validate_const_expr(offset, ret, ctx)?;
         }
-
         Ok(())
     }
 }
 
+/// Validates a constant expression that must produce values of the types in `ret`.
+fn validate_const_expr<'a>(expr: &'a instr::ConstExpr, ret: &[ty::Val], ctx: &mut Context<'a>) -> Result<()> {
+    ctx.stacks.push_frame(&[], &[], &[]);
+    expr.validate(ctx)?;
+    // the results are checked against the operand stack, so that the frame does not need to
+    // borrow them for the lifetime of the context
+    let found = ctx.stacks.operands.len();
+    ctx.stacks.pop_operands(ret)?;
+    if !ctx.stacks.operands.is_empty() {
+        return Err(Error::UnbalancedOperandStack { expected: ret.len(), found });
+    }
+    ctx.stacks.pop_frame()?;
+    if !ctx.stacks.frames.is_empty() {
+        return Err(Error::UnbalancedControlStack { frames: ctx.stacks.frames.len() });
+    }
+    if !ctx.stacks.operands.is_empty() {
+        return Err(Error::UnbalancedOperandStack { expected: 0, found: ctx.stacks.operands.len() });
+    }
+    Ok(())
+}
+
 impl<'a> Validate<'a> for module::Start {
     type ValidationResult = ();
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         let ty = self.func.validate(ctx)?;
         if ty.params != [] || ty.results != [] {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::InvalidStartFunction { params: ty.params.clone(), results: ty.results.clone() });
         }
         Ok(())
     }
@@ -215,8 +328,15 @@ impl<'a> Validate<'a> for module::desc::Import {
             },
             Self::Global(ty) => {
                 ty.validate(ctx)?;
+                if ty.mt == ty::Mut::Var {
+                    require(ctx.features.mutable_globals, "mutable-globals")?;
+                }
                 Ok(Self::ValidationResult::Global( ty ))
             },
+            Self::Tag(ty) => {
+                let ty = ty.validate(ctx)?;
+                Ok(Self::ValidationResult::Tag( ty ))
+            },
         }
     }
 }
@@ -239,8 +359,15 @@ impl<'a> Validate<'a> for module::desc::Export {
             },
             Self::Global(idx) => {
                 let ty = idx.validate(ctx)?;
+                if ty.mt == ty::Mut::Var {
+                    require(ctx.features.mutable_globals, "mutable-globals")?;
+                }
                 Ok(Self::ValidationResult::Global( ty ))
             },
+            Self::Tag(idx) => {
+                let ty = idx.validate(ctx)?;
+                Ok(Self::ValidationResult::Tag( ty ))
+            },
         }
     }
 }
@@ -257,4 +384,408 @@ impl<'a> Validate<'a> for module::Export {
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
         self.desc.validate(ctx)
     }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::structure::{module, instr, idx, ty};
+    use crate::structure::instr::Instr;
+    use crate::validation::{ValidationEntry, Features, Error, error::Location, stacks::Operand};
+    use crate::binary::{self, WasmBinary, WasmBinaryEncode, offsets::WithOffsets, module::Lazy};
+
+    /// Builds the modules of the validation tests, which start with an `i32` global and a function
+    /// of type 0 with an empty body.
+    pub(crate) struct ModuleBuilder {
+        module: module::Module,
+    }
+
+    impl ModuleBuilder {
+        pub(crate) fn new() -> Self {
+            let module = module::Module {
+                types: vec![ty::Func { params: vec![], results: vec![] }.into()],
+                funcs: vec![module::Func { ty: idx::Type(0), locals: vec![], body: instr::Expr(vec![]).into() }],
+                globals: vec![module::Global {
+                    ty: ty::Global { mt: ty::Mut::Const, val: ty::Val::I32 },
+                    init: instr::ConstExpr(vec![Instr::ConstI32(0)]),
+                }],
+                .. Default::default()
+            };
+            Self { module }
+        }
+
+        /// Appends types after the type of the function.
+        pub(crate) fn types(mut self, types: Vec<ty::Rec>) -> Self {
+            self.module.types.extend(types);
+            self
+        }
+
+        /// Appends the types of the GC tests: types 1 and 2 are a struct and its subtype, type 3
+        /// is an array of `i32` and type 4 is a function that returns an `i32`.
+        pub(crate) fn gc_types(self) -> Self {
+            let field = |storage, mt| ty::Field { storage, mt };
+            let sub = |is_final, supers, composite| ty::Sub { is_final, supers, composite };
+            self.types(vec![
+                ty::Rec { types: vec![
+                    sub(false, vec![], ty::Composite::Struct(ty::Struct { fields: vec![field(ty::Storage::I8, ty::Mut::Var)] })),
+                    sub(true, vec![idx::Type(1)], ty::Composite::Struct(ty::Struct { fields: vec![
+                        field(ty::Storage::I8, ty::Mut::Var),
+                        field(ty::Storage::Val(ty::Val::I32), ty::Mut::Const),
+                    ] })),
+                ] },
+                ty::Rec { types: vec![
+                    sub(true, vec![], ty::Composite::Array(ty::Array { field: field(ty::Storage::Val(ty::Val::I32), ty::Mut::Var) })),
+                ] },
+                ty::Func { params: vec![], results: vec![ty::Val::I32] }.into(),
+            ])
+        }
+
+        /// Appends a memory of one page with the given address type.
+        pub(crate) fn mem(mut self, addr: ty::Addr) -> Self {
+            self.module.mems.push(module::Mem { ty: ty::Mem { lim: ty::Limits { min: 1, max: None }, shared: false, addr } });
+            self
+        }
+
+        pub(crate) fn table(mut self, elem: ty::Ref) -> Self {
+            self.module.tables.push(module::Table { ty: ty::Table { lim: ty::Limits { min: 1, max: None }, elem } });
+            self
+        }
+
+        /// Appends a tag, whose type is a new function type that takes `params`.
+        pub(crate) fn tag(mut self, params: Vec<ty::Val>) -> Self {
+            let ty = self.module.types.iter().map(|rec| rec.types.len() as u32).sum();
+            self.module.types.push(ty::Func { params, results: vec![] }.into());
+            self.module.tags.push(module::Tag { ty: ty::Tag { ty: idx::Type(ty) } });
+            self
+        }
+
+        pub(crate) fn data(mut self, data: module::Data) -> Self {
+            self.module.data.push(data);
+            self
+        }
+
+        /// Sets the type and the body of the function.
+        pub(crate) fn body(mut self, params: Vec<ty::Val>, results: Vec<ty::Val>, body: Vec<Instr>) -> Self {
+            self.module.types[0] = ty::Func { params, results }.into();
+            self.module.funcs[0].body = instr::Expr(body).into();
+            self
+        }
+
+        pub(crate) fn build(self) -> module::Module {
+            self.module
+        }
+    }
+
+    /// Returns a module with a memory, an `i32` global and a single function of type 0, which
+    /// takes `params` and returns `results`.
+    pub(crate) fn module_with_body(params: Vec<ty::Val>, results: Vec<ty::Val>, body: Vec<Instr>) -> module::Module {
+        ModuleBuilder::new().mem(ty::Addr::I32).body(params, results, body).build()
+    }
+
+    pub(crate) fn memarg(align: u32, offset: u64) -> instr::MemArg {
+        instr::MemArg { align, offset, mem: idx::Mem(0) }
+    }
+
+    /// Validates the module, unwrapping the error of an invalid function body.
+    pub(crate) fn func_error(module: &module::Module) -> Option<Error> {
+        match module.validate().err()? {
+            Error::InFunction { error, .. } => Some(*error),
+            error => Some(error),
+        }
+    }
+
+    #[test]
+    fn reports_type_mismatch() {
+        let module = module_with_body(vec![], vec![ty::Val::I32], vec![Instr::ConstI64(0)]);
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch {
+            expected: Operand::Val(ty::Val::I32),
+            found: Operand::Val(ty::Val::I64),
+        }));
+    }
+
+    #[test]
+    fn reports_unknown_indices() {
+        let module = module_with_body(vec![], vec![], vec![Instr::Call(idx::Func(7))]);
+        assert_eq!(func_error(&module), Some(Error::UnknownFunc { idx: 7 }));
+
+        let module = module_with_body(vec![], vec![], vec![Instr::LocalGet(idx::Local(3))]);
+        assert_eq!(func_error(&module), Some(Error::UnknownLocal { idx: 3 }));
+    }
+
+    #[test]
+    fn reports_immutable_global() {
+        let module = module_with_body(vec![], vec![], vec![Instr::ConstI32(1), Instr::GlobalSet(idx::Global(0))]);
+        assert_eq!(func_error(&module), Some(Error::ImmutableGlobal { idx: 0 }));
+    }
+
+    #[test]
+    fn reports_invalid_alignment() {
+        let module = module_with_body(vec![ty::Val::I32], vec![ty::Val::I32], vec![
+            Instr::LocalGet(idx::Local(0)),
+            Instr::I32Load(memarg(3, 0)),
+        ]);
+        assert_eq!(func_error(&module), Some(Error::InvalidAlignment { align: 3, natural: 2 }));
+    }
+
+    #[test]
+    fn reports_error_location() {
+        let mut module = module_with_body(vec![], vec![ty::Val::I32], vec![
+            Instr::Block(ty::RetVal::Empty, instr::Expr(vec![
+                Instr::Nop,
+                Instr::Call(idx::Func(3)),
+            ])),
+            Instr::ConstI32(0),
+        ]);
+        module.imports.push(module::Import {
+            module: "env".into(),
+            name: "f".into(),
+            desc: module::desc::Import::Func(idx::Type(0)),
+        });
+        let error = module.validate().err().unwrap();
+        assert_eq!(error.location(), Some(&Location { func: 1, instr: Some(2), offset: None }));
+        assert_eq!(format!("{}", error), "Unknown function `3`. At function `1`, instruction `2`.");
+
+        let module = module_with_body(vec![], vec![ty::Val::I32], vec![Instr::Nop]);
+        let error = module.validate().err().unwrap();
+        assert_eq!(error.location(), Some(&Location { func: 0, instr: Some(1), offset: None }));
+    }
+
+    #[test]
+    fn reports_error_offset() {
+        let module = module_with_body(vec![], vec![], vec![
+            Instr::Block(ty::RetVal::Empty, instr::Expr(vec![
+                Instr::LocalGet(idx::Local(0)),
+            ])),
+        ]);
+        let mut bin = vec![];
+        module.encode(&mut bin).unwrap();
+        let module: WithOffsets<module::Module> = bin.iter().copied().parse().unwrap();
+
+        let error = module.validate().err().unwrap();
+        let offset = module.1.instr_offset(0, 1);
+        assert_eq!(bin[offset.unwrap()], 0x20);
+        assert_eq!(error.location(), Some(&Location { func: 0, instr: Some(1), offset }));
+    }
+
+    #[test]
+    fn validates_lazy_module() {
+        let module = module_with_body(vec![], vec![ty::Val::I32], vec![Instr::ConstI64(0)]);
+        let mut bin = vec![];
+        module.encode(&mut bin).unwrap();
+        let Lazy(module): Lazy<module::Module> = bin.iter().copied().parse().unwrap();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch {
+            expected: Operand::Val(ty::Val::I32),
+            found: Operand::Val(ty::Val::I64),
+        }));
+
+        let mut module = module_with_body(vec![], vec![], vec![]);
+        module.funcs[0].body = module::Body::Raw { bytes: vec![0xFF, 0x0B], expr: Default::default() };
+        assert_eq!(func_error(&module), Some(Error::MalformedBody {
+            error: binary::Error::InvalidVariantId { id: 0xFF, ty: "instr::Instr" },
+        }));
+    }
+
+    #[test]
+    fn reports_duplicate_export_name() {
+        let mut module = module_with_body(vec![], vec![], vec![]);
+        for _ in 0..2 {
+            module.exports.push(module::Export { name: "f".into(), desc: module::desc::Export::Func(idx::Func(0)) });
+        }
+        assert_eq!(module.validate().err(), Some(Error::DuplicateExportName { name: "f".into() }));
+    }
+
+    #[test]
+    fn reports_invalid_start_function() {
+        let mut module = module_with_body(vec![ty::Val::I32], vec![], vec![]);
+        module.start = Some(module::Start { func: idx::Func(0) });
+        assert_eq!(module.validate().err(), Some(Error::InvalidStartFunction {
+            params: vec![ty::Val::I32],
+            results: vec![],
+        }));
+    }
+
+    #[test]
+    fn data_segments_are_validated_against_data_count() {
+        let body = || vec![Instr::ConstI32(0), Instr::ConstI32(0), Instr::ConstI32(4), Instr::MemInit(idx::Data(0), idx::Mem(0))];
+        let passive = || module::Data { mode: module::DataMode::Passive, init: vec![0x2A] };
+
+        let module = module::Module { data: vec![passive()], data_count: Some(1), .. module_with_body(vec![], vec![], body()) };
+        assert_eq!(func_error(&module), None);
+
+        let module = module::Module { data: vec![passive()], .. module_with_body(vec![], vec![], body()) };
+        assert_eq!(func_error(&module), Some(Error::MissingDataCount));
+
+        let module = module::Module { data: vec![], data_count: Some(0), .. module_with_body(vec![], vec![], vec![Instr::DataDrop(idx::Data(0))]) };
+        assert_eq!(func_error(&module), Some(Error::UnknownData { idx: 0 }));
+
+        let module = module::Module { data: vec![passive()], data_count: Some(2), .. module_with_body(vec![], vec![], vec![]) };
+        assert_eq!(func_error(&module), Some(Error::DataCountMismatch { count: 2, found: 1 }));
+    }
+
+    #[test]
+    fn functions_must_be_declared_to_be_referenced() {
+        let body = || vec![Instr::RefFunc(idx::Func(0)), Instr::RefIsNull];
+        let module = module_with_body(vec![], vec![ty::Val::I32], body());
+        assert_eq!(func_error(&module), Some(Error::UndeclaredFuncRef { idx: 0 }));
+
+        let module = module::Module {
+            elem: vec![module::Elem {
+                ty: ty::Ref::FUNC,
+                mode: module::ElemMode::Declarative,
+                init: module::ElemInit::Funcs(vec![idx::Func(0)]),
+            }],
+            .. module_with_body(vec![], vec![ty::Val::I32], body())
+        };
+        assert_eq!(func_error(&module), None);
+
+        let module = module::Module {
+            globals: vec![module::Global {
+                ty: ty::Global { mt: ty::Mut::Const, val: ty::Val::FUNC_REF },
+                init: instr::ConstExpr(vec![Instr::RefFunc(idx::Func(0))]),
+            }],
+            .. module_with_body(vec![], vec![ty::Val::I32], body())
+        };
+        assert_eq!(func_error(&module), None);
+    }
+
+    #[test]
+    fn active_element_segments_must_match_table_type() {
+        let module = module::Module {
+            tables: vec![module::Table { ty: ty::Table { lim: ty::Limits { min: 1, max: None }, elem: ty::Ref::EXTERN } }],
+            elem: vec![module::Elem {
+                ty: ty::Ref::FUNC,
+                mode: module::ElemMode::Active { table: idx::Table(0), offset: instr::ConstExpr(vec![Instr::ConstI32(0)]) },
+                init: module::ElemInit::Funcs(vec![idx::Func(0)]),
+            }],
+            .. module_with_body(vec![], vec![], vec![])
+        };
+        assert_eq!(func_error(&module), Some(Error::ElemTypeMismatch { expected: ty::Ref::EXTERN, found: ty::Ref::FUNC }));
+    }
+
+    #[test]
+    fn shared_memories_must_have_a_maximum() {
+        let mem = |max| module::Mem { ty: ty::Mem { lim: ty::Limits { min: 1, max }, shared: true, addr: ty::Addr::I32 } };
+        let module = module::Module { mems: vec![mem(Some(2))], .. module_with_body(vec![], vec![], vec![]) };
+        assert_eq!(func_error(&module), None);
+
+        let module = module::Module { mems: vec![mem(None)], .. module_with_body(vec![], vec![], vec![]) };
+        assert_eq!(func_error(&module), Some(Error::SharedMemoryWithoutMax));
+    }
+
+    #[test]
+    fn tag_types_must_have_no_results() {
+        let module = module::Module {
+            tags: vec![module::Tag { ty: ty::Tag { ty: idx::Type(0) } }],
+            .. module_with_body(vec![], vec![ty::Val::I32], vec![Instr::ConstI32(0)])
+        };
+        assert_eq!(func_error(&module), Some(Error::InvalidTagType { results: vec![ty::Val::I32] }));
+    }
+
+    #[test]
+    fn memory_limits_must_fit_the_address_type() {
+        let module = module::Module {
+            mems: vec![module::Mem { ty: ty::Mem { lim: ty::Limits { min: 1 << 17, max: None }, shared: false, addr: ty::Addr::I32 } }],
+            .. module_with_body(vec![], vec![], vec![])
+        };
+        assert_eq!(func_error(&module), Some(Error::LimitsOutOfRange { max: 1 << 17, range: 1 << 16 }));
+    }
+
+    #[test]
+    fn global_initializers_refer_to_imported_and_preceding_globals() {
+        let global = |val, init| module::Global { ty: ty::Global { mt: ty::Mut::Const, val }, init: instr::ConstExpr(init) };
+        let mut module = module_with_body(vec![], vec![], vec![]);
+        module.imports.push(module::Import {
+            module: "env".into(),
+            name: "g".into(),
+            desc: module::desc::Import::Global(ty::Global { mt: ty::Mut::Const, val: ty::Val::I32 }),
+        });
+        module.globals = vec![global(ty::Val::I64, vec![Instr::GlobalGet(idx::Global(0))])];
+        assert_eq!(module.validate().err(), Some(Error::TypeMismatch { expected: Operand::Val(ty::Val::I64), found: Operand::Val(ty::Val::I32) }));
+
+        module.globals = vec![global(ty::Val::I32, vec![Instr::GlobalGet(idx::Global(1))])];
+        assert_eq!(module.validate().err(), Some(Error::UnknownGlobal { idx: 1 }));
+
+        module.globals = vec![
+            global(ty::Val::I32, vec![Instr::GlobalGet(idx::Global(0))]),
+            global(ty::Val::I32, vec![Instr::GlobalGet(idx::Global(1))]),
+        ];
+        assert_eq!(module.validate().err(), None);
+    }
+
+    /// Returns an empty data segment that is active at the given offset of memory 0.
+    fn active_data(offset: Vec<Instr>) -> module::Data {
+        module::Data {
+            mode: module::DataMode::Active { mem: idx::Mem(0), offset: instr::ConstExpr(offset) },
+            init: vec![],
+        }
+    }
+
+    #[test]
+    fn can_validate_extended_constant_expressions() {
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).data(active_data(vec![Instr::GlobalGet(idx::Global(0)), Instr::ConstI32(16), Instr::I32Add])).build();
+        assert_eq!(func_error(&module), None);
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).data(active_data(vec![Instr::ConstI64(0), Instr::ConstI64(16), Instr::I64Mul])).build();
+        assert_eq!(func_error(&module), Some(Error::TypeMismatch { expected: Operand::Val(ty::Val::I32), found: Operand::Val(ty::Val::I64) }));
+
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).data(active_data(vec![Instr::ConstI32(0), Instr::ConstI32(16), Instr::I32DivU])).build();
+        assert_eq!(func_error(&module), Some(Error::NonConstantInstruction { instr: "I32DivU".into() }));
+    }
+
+    #[test]
+    fn extended_constant_expressions_can_be_disabled() {
+        let module = ModuleBuilder::new().mem(ty::Addr::I32).data(active_data(vec![Instr::GlobalGet(idx::Global(0)), Instr::ConstI32(16), Instr::I32Add])).build();
+        let features = Features { extended_const: false, .. Features::ALL };
+        assert_eq!(module.validate_with(features).err(), Some(Error::FeatureDisabled { feature: "extended-const" }));
+    }
+
+    #[test]
+    fn can_validate_subtypes() {
+        let mut module = ModuleBuilder::new().gc_types().build();
+        assert_eq!(module.validate().err(), None);
+
+        // final types can not be extended
+        module.types[2].types[0].supers = vec![idx::Type(2)];
+        assert_eq!(module.validate().err(), Some(Error::InvalidSuperType { idx: 3, sup: 2 }));
+
+        // subtypes must keep the fields of their supertypes
+        let mut module = ModuleBuilder::new().gc_types().build();
+        module.types[1].types[0].composite = ty::Composite::Struct(ty::Struct { fields: vec![] });
+        module.types[1].types[1].supers = vec![idx::Type(1)];
+        assert_eq!(module.validate().err(), None);
+        module.types[1].types.swap(0, 1);
+        module.types[1].types[0].is_final = false;
+        module.types[1].types[0].supers = vec![];
+        module.types[1].types[1].supers = vec![idx::Type(1)];
+        assert_eq!(module.validate().err(), Some(Error::InvalidSuperType { idx: 2, sup: 1 }));
+    }
+
+    #[test]
+    fn disabled_features_are_rejected() {
+        let module = module_with_body(vec![ty::Val::I32], vec![ty::Val::I32], vec![Instr::LocalGet(idx::Local(0)), Instr::I32Extend8S]);
+        assert_eq!(module.validate_with(Features::WASM2).err(), None);
+        let error = module.validate_with(Features::MVP).err().map(|error| match error {
+            Error::InFunction { error, .. } => *error,
+            error => error,
+        });
+        assert_eq!(error, Some(Error::FeatureDisabled { feature: "sign-extension" }));
+
+        // a sandbox that only accepts a few proposals on top of the MVP
+        let sandbox = Features { sign_extension: true, bulk_memory: true, .. Features::MVP };
+        assert_eq!(module.validate_with(sandbox).err(), None);
+        let module = module::Module {
+            data: vec![module::Data { mode: module::DataMode::Passive, init: vec![] }],
+            data_count: Some(1),
+            .. module_with_body(vec![], vec![], vec![])
+        };
+        assert_eq!(module.validate_with(sandbox).err(), None);
+        assert_eq!(module.validate_with(Features::MVP).err(), Some(Error::FeatureDisabled { feature: "bulk-memory" }));
+
+        let module = module_with_body(vec![ty::Val::V128], vec![], vec![]);
+        assert_eq!(module.validate_with(sandbox).err(), Some(Error::FeatureDisabled { feature: "simd" }));
+        let module = module_with_body(vec![], vec![ty::Val::I32, ty::Val::I32], vec![Instr::ConstI32(0), Instr::ConstI32(1)]);
+        assert_eq!(module.validate_with(sandbox).err(), Some(Error::FeatureDisabled { feature: "multi-value" }));
+        let module = ModuleBuilder::new().gc_types().build();
+        assert_eq!(module.validate_with(Features::WASM2).err(), Some(Error::FeatureDisabled { feature: "gc" }));
+        assert_eq!(module.validate_with(Features::ALL).err(), None);
+    }
 }
\ No newline at end of file
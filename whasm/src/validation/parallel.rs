@@ -0,0 +1,80 @@
+//! This module defines the parallel validation of modules, enabled with the `parallel` feature.
+//! 
+//! Function bodies are independent from each other once the types, imports, functions and
+//! globals of the module are known. The `ParallelValidationEntry` trait validates them on the
+//! rayon thread pool, where each worker uses its own copy of the validation context (and hence
+//! its own operand and control stacks).
+//! 
+//! The result is the same as the one of `ValidationEntry`. If several functions are invalid,
+//! the error of the function with the lowest index is reported.
+
+use crate::validation::{Context, Result, Features, module::{self as validation, result}};
+use crate::structure::module;
+use crate::binary::offsets::WithOffsets;
+use rayon::prelude::*;
+
+pub trait ParallelValidationEntry<'a>
+where Self: Sized {
+    type ValidationResult;
+    fn par_validate_with(&'a self, features: Features) -> Result<Self::ValidationResult>;
+    fn par_validate(&'a self) -> Result<Self::ValidationResult> {
+        self.par_validate_with(Features::default())
+    }
+}
+
+impl<'a> ParallelValidationEntry<'a> for module::Module {
+    type ValidationResult = result::Module<'a>;
+    fn par_validate_with(&'a self, features: Features) -> Result<Self::ValidationResult> {
+        validation::validate_module(self, features, validate_funcs)
+    }
+}
+
+impl<'a> ParallelValidationEntry<'a> for WithOffsets<module::Module> {
+    type ValidationResult = result::Module<'a>;
+    fn par_validate_with(&'a self, features: Features) -> Result<Self::ValidationResult> {
+        let WithOffsets(module, offsets) = self;
+        module.par_validate_with(features).map_err(|error| validation::locate(error, module, offsets))
+    }
+}
+
+fn validate_funcs<'a>(module: &'a module::Module, ctx: &mut Context<'a>) -> Result<()> {
+    let ctx: &Context<'a> = ctx;
+    let imported_funcs = ctx.funcs.len() - module.funcs.len();
+    let error = module.funcs.par_iter()
+        .enumerate()
+        .map_init(|| ctx.clone(), |ctx, (i, func)| validation::validate_func(func, imported_funcs + i, ctx))
+        .find_first(|result| result.is_err());
+    error.unwrap_or(Ok(()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::structure::{module, instr, idx, ty};
+    use crate::structure::instr::Instr;
+    use crate::validation::{ValidationEntry, ParallelValidationEntry};
+
+    fn func(ty: u32, body: Vec<Instr>) -> module::Func {
+        module::Func { ty: idx::Type(ty), locals: vec![], body: instr::Expr(body).into() }
+    }
+
+    #[test]
+    fn reports_same_error_as_sequential_validation() {
+        let mut module = module::Module {
+            types: vec![
+                ty::Func { params: vec![], results: vec![] }.into(),
+                ty::Func { params: vec![], results: vec![ty::Val::I32] }.into(),
+            ],
+            .. Default::default()
+        };
+        for i in 0..64 {
+            module.funcs.push(func(1, vec![Instr::ConstI32(i)]));
+        }
+        assert!(module.par_validate().is_ok());
+
+        module.funcs[40] = func(1, vec![Instr::ConstI64(0)]);
+        module.funcs[50] = func(0, vec![Instr::Call(idx::Func(99))]);
+        let expected = module.validate().err();
+        assert!(expected.is_some());
+        assert_eq!(module.par_validate().err(), expected);
+    }
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::validation::{Result, Error};
+use crate::validation::{Result, Error, ty::is_subtype};
 use crate::structure::ty;
 
 #[derive(Debug, PartialEq)]
@@ -18,15 +18,20 @@ impl From<ty::Val> for Operand {
 #[derive(Clone, Copy)]
 pub struct Frame<'a> {
     pub label: &'a [ty::Val],
+    pub start: &'a [ty::Val],
     pub out: &'a [ty::Val],
     pub height: usize,
     pub unreachable: bool,
+    // whether the frame is the handler of a legacy `try` block, which can be targeted by `rethrow`
+    pub catch: bool,
 }
 
 #[derive(Default)]
+#[derive(Clone)]
 pub struct Stacks<'a> {
     pub operands: Vec<Operand>,
     pub frames: Vec<Frame<'a>>,
+    pub types: Vec<&'a ty::Sub>,
 }
 
 impl<'a> Stacks<'a> {
@@ -35,19 +40,19 @@ impl<'a> Stacks<'a> {
     }
 
     pub fn pop_operand(&mut self, op: Operand) -> Result<Operand> {
-        let frame = self.frames.last().ok_or(Error::UnexpectedEndOfFile)?;
+        let frame = self.frames.last().ok_or(Error::EmptyControlStack)?;
         if self.operands.len() == frame.height && frame.unreachable {
             return Ok(op);
         }
         if self.operands.len() == frame.height {
-            return Err(Error::UnexpectedEndOfFile)
+            return Err(Error::EmptyOperandStack { expected: op })
         }
         let val = self.operands.pop().unwrap();
         match (val, op) {
             (Operand::Unknown, _) => Ok(op),
             (_, Operand::Unknown) => Ok(val),
-            (_, _) if val == op => Ok(val),
-            (_, _) => Err(Error::UnexpectedEndOfFile),
+            (Operand::Val(found), Operand::Val(expected)) if is_subtype(&self.types, found, expected) => Ok(val),
+            (_, _) => Err(Error::TypeMismatch { expected: op, found: val }),
         }
     }
 
@@ -64,29 +69,48 @@ impl<'a> Stacks<'a> {
         Ok(())
     }
 
-    pub fn push_frame(&mut self, label: &'a [ty::Val], out: &'a [ty::Val]) {
+    /// Checks that the operands on top of the stack match the given types, leaving the operands
+    /// as they were.
+    pub fn check_operands(&mut self, ops: &[ty::Val]) -> Result<()> {
+        let mut popped = Vec::with_capacity(ops.len());
+        for op in ops.iter().rev() {
+            let frame = self.frames.last().ok_or(Error::EmptyControlStack)?;
+            let found = match self.operands.len() == frame.height {
+                true => Operand::Unknown,
+                false => *self.operands.last().unwrap(),
+            };
+            self.pop_operand(Operand::Val(*op))?;
+            popped.push(found);
+        }
+        self.operands.extend(popped.into_iter().rev());
+        Ok(())
+    }
+
+    pub fn push_frame(&mut self, label: &'a [ty::Val], start: &'a [ty::Val], out: &'a [ty::Val]) {
         self.frames.push(Frame {
             label,
+            start,
             out,
             height: self.operands.len(),
             unreachable: false,
-        })
+            catch: false,
+        });
+        self.push_operands(start);
     }
 
-    pub fn pop_frame(&mut self) -> Result<&'a [ty::Val]> {
-        let frame = self.frames.last().ok_or(Error::UnexpectedEndOfFile)?;
+    pub fn pop_frame(&mut self) -> Result<Frame<'a>> {
+        let frame = self.frames.last().ok_or(Error::EmptyControlStack)?;
         let out = frame.out;
         let height = frame.height;
         self.pop_operands(&out[..])?;
         if self.operands.len() != height {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::UnbalancedOperandStack { expected: out.len(), found: self.operands.len() - height + out.len() });
         }
-        self.frames.pop().ok_or(Error::UnexpectedEndOfFile)?;
-        Ok(out)
+        self.frames.pop().ok_or(Error::EmptyControlStack)
     }
 
     pub fn unreachable(&mut self) -> Result<()> {
-        let frame = self.frames.last().ok_or(Error::UnexpectedEndOfFile)?;
+        let frame = self.frames.last().ok_or(Error::EmptyControlStack)?;
         let height = frame.height;
         self.operands.truncate(height);
         self.frames.last_mut().unwrap().unreachable = true;
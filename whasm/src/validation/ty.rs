@@ -1,34 +1,212 @@
-use crate::validation::{Validate, Context, Result, Error};
-use crate::structure::ty;
+use crate::validation::{Validate, Context, Result, Error, features::require};
+use crate::structure::{idx, ty};
 
 impl<'a> Validate<'a> for ty::Limits {
-    type ValidationResult = u32;
+    type ValidationResult = u64;
     fn validate(&self, _ctx: &mut Context) -> Result<Self::ValidationResult> {
         let min = self.min;
         let max = self.max.unwrap_or(self.min);
         if !(min <= max) {
-            return Err(Error::UnexpectedEndOfFile);
+            return Err(Error::InvalidLimits { min, max });
         }
         Ok(max)
     }
 }
 
+impl<'a> Validate<'a> for ty::Val {
+    type ValidationResult = ();
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        ctx.features.check_val(*self)?;
+        match self {
+            ty::Val::Ref(r) => r.heap.validate(ctx),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a> Validate<'a> for ty::Heap {
+    type ValidationResult = ();
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        match self {
+            ty::Heap::Type(idx) if idx.0 as usize >= ctx.types.len() => {
+                Err(Error::UnknownType { idx: idx.0 })
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
 impl<'a> Validate<'a> for ty::Func {
     type ValidationResult = ();
-    fn validate(&self, _ctx: &mut Context) -> Result<Self::ValidationResult> {
-        if !(self.results.len() <= 1) {
-            return Err(Error::UnexpectedEndOfFile);
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        for val in self.params.iter().chain(self.results.iter()) {
+            val.validate(ctx)?;
+        }
+        if self.results.len() > 1 {
+            require(ctx.features.multi_value, "multi-value")?;
         }
         Ok(())
     }
 }
 
+impl<'a> Validate<'a> for ty::Field {
+    type ValidationResult = ();
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        match &self.storage {
+            ty::Storage::Val(val) => val.validate(ctx),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a> Validate<'a> for ty::Composite {
+    type ValidationResult = ();
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        match self {
+            ty::Composite::Func(func) => func.validate(ctx),
+            ty::Composite::Struct(st) => {
+                for field in st.fields.iter() {
+                    field.validate(ctx)?;
+                }
+                Ok(())
+            },
+            ty::Composite::Array(array) => array.field.validate(ctx),
+        }
+    }
+}
+
+/// Validates the type with the given index, which can only extend a single type that is defined
+/// before it and that is not final.
+pub(crate) fn validate_sub<'a>(sub: &'a ty::Sub, idx: u32, ctx: &mut Context<'a>) -> Result<()> {
+    sub.composite.validate(ctx)?;
+    // only final function types without supertypes predate the GC proposal
+    if !sub.is_final || !sub.supers.is_empty() || !matches!(sub.composite, ty::Composite::Func(_)) {
+        require(ctx.features.gc, "gc")?;
+    }
+    if sub.supers.len() > 1 {
+        return Err(Error::TooManySuperTypes { idx, count: sub.supers.len() });
+    }
+    for sup in sub.supers.iter() {
+        if sup.0 >= idx {
+            return Err(Error::InvalidSuperType { idx, sup: sup.0 });
+        }
+        let sup_ty = ctx.types[sup.0 as usize];
+        if sup_ty.is_final || !is_composite_subtype(&ctx.types, &sub.composite, &sup_ty.composite) {
+            return Err(Error::InvalidSuperType { idx, sup: sup.0 });
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether values of type `sub` can be used where values of type `sup` are expected,
+/// given the types defined in the module.
+pub fn is_subtype(types: &[&ty::Sub], sub: ty::Val, sup: ty::Val) -> bool {
+    match (sub, sup) {
+        (ty::Val::Ref(sub), ty::Val::Ref(sup)) => {
+            (sup.nullable || !sub.nullable) && is_heap_subtype(types, sub.heap, sup.heap)
+        },
+        (sub, sup) => sub == sup,
+    }
+}
+
+/// Returns whether the heap type `sub` is a subtype of `sup`. Abstract heap types form three
+/// hierarchies, topped by `any`, `func` and `extern` (and `exn`), each with its own bottom type.
+/// Defined types are subtypes of their declared supertypes and of the abstract type of their kind.
+pub fn is_heap_subtype(types: &[&ty::Sub], sub: ty::Heap, sup: ty::Heap) -> bool {
+    use ty::Heap;
+    if sub == sup {
+        return true;
+    }
+    match (sub, sup) {
+        (Heap::Type(sub), Heap::Type(sup)) => {
+            // declared supertypes always have smaller indices, so the chain is finite
+            let mut idx = sub;
+            while let Some(&next) = types.get(idx.0 as usize).and_then(|ty| ty.supers.first()) {
+                if next == sup {
+                    return true;
+                }
+                if next.0 >= idx.0 {
+                    return false;
+                }
+                idx = next;
+            }
+            false
+        },
+        (Heap::Type(idx), _) => match composite_kind(types, idx) {
+            Some(kind) => is_heap_subtype(types, kind, sup),
+            None => false,
+        },
+        (Heap::NoFunc, Heap::Type(idx)) => composite_kind(types, idx) == Some(Heap::Func),
+        (Heap::None, Heap::Type(idx)) => matches!(composite_kind(types, idx), Some(Heap::Struct | Heap::Array)),
+        (Heap::None, Heap::Any | Heap::Eq | Heap::I31 | Heap::Struct | Heap::Array) => true,
+        (Heap::Eq | Heap::I31 | Heap::Struct | Heap::Array, Heap::Any) => true,
+        (Heap::I31 | Heap::Struct | Heap::Array, Heap::Eq) => true,
+        (Heap::NoFunc, Heap::Func) | (Heap::NoExtern, Heap::Extern) | (Heap::NoExn, Heap::Exn) => true,
+        _ => false,
+    }
+}
+
+/// Returns the top of the hierarchy the heap type belongs to, which is `func`, `extern`, `exn` or
+/// `any`.
+pub(crate) fn top_heap(types: &[&ty::Sub], heap: ty::Heap) -> ty::Heap {
+    use ty::Heap;
+    match heap {
+        Heap::Func | Heap::NoFunc => Heap::Func,
+        Heap::Extern | Heap::NoExtern => Heap::Extern,
+        Heap::Exn | Heap::NoExn => Heap::Exn,
+        Heap::Type(idx) if composite_kind(types, idx) == Some(Heap::Func) => Heap::Func,
+        _ => Heap::Any,
+    }
+}
+
+/// Returns the abstract heap type of the kind of the given defined type.
+fn composite_kind(types: &[&ty::Sub], idx: idx::Type) -> Option<ty::Heap> {
+    types.get(idx.0 as usize).map(|ty| match ty.composite {
+        ty::Composite::Func(_) => ty::Heap::Func,
+        ty::Composite::Struct(_) => ty::Heap::Struct,
+        ty::Composite::Array(_) => ty::Heap::Array,
+    })
+}
+
+/// Returns whether a composite type can extend another one. Functions are contravariant in their
+/// parameters and covariant in their results, while structs can append new fields. Only immutable
+/// fields can be refined to subtypes.
+fn is_composite_subtype(types: &[&ty::Sub], sub: &ty::Composite, sup: &ty::Composite) -> bool {
+    match (sub, sup) {
+        (ty::Composite::Func(sub), ty::Composite::Func(sup)) => {
+            sub.params.len() == sup.params.len()
+                && sub.results.len() == sup.results.len()
+                && sup.params.iter().zip(sub.params.iter()).all(|(a, b)| is_subtype(types, *a, *b))
+                && sub.results.iter().zip(sup.results.iter()).all(|(a, b)| is_subtype(types, *a, *b))
+        },
+        (ty::Composite::Struct(sub), ty::Composite::Struct(sup)) => {
+            sub.fields.len() >= sup.fields.len()
+                && sub.fields.iter().zip(sup.fields.iter()).all(|(a, b)| is_field_subtype(types, a, b))
+        },
+        (ty::Composite::Array(sub), ty::Composite::Array(sup)) => {
+            is_field_subtype(types, &sub.field, &sup.field)
+        },
+        _ => false,
+    }
+}
+
+fn is_field_subtype(types: &[&ty::Sub], sub: &ty::Field, sup: &ty::Field) -> bool {
+    match (sub.storage, sup.storage, sub.mt, sup.mt) {
+        (ty::Storage::Val(a), ty::Storage::Val(b), ty::Mut::Const, ty::Mut::Const) => is_subtype(types, a, b),
+        (a, b, sub_mt, sup_mt) => a == b && sub_mt == sup_mt,
+    }
+}
+
 impl<'a> Validate<'a> for ty::Table {
     type ValidationResult = ();
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        if self.elem != ty::Ref::FUNC {
+            ctx.features.check_val(self.elem.into())?;
+        }
+        self.elem.heap.validate(ctx)?;
         let range = self.lim.validate(ctx)?;
-        if !(range <= std::u32::MAX) {
-            return Err(Error::UnexpectedEndOfFile);
+        if range > u64::from(u32::MAX) {
+            return Err(Error::LimitsOutOfRange { max: range, range: u64::from(u32::MAX) });
         }
         Ok(())
     }
@@ -37,24 +215,57 @@ impl<'a> Validate<'a> for ty::Table {
 impl<'a> Validate<'a> for ty::Mem {
     type ValidationResult = ();
     fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        if self.addr == ty::Addr::I64 {
+            require(ctx.features.memory64, "memory64")?;
+        }
+        if self.shared {
+            require(ctx.features.threads, "threads")?;
+        }
         let range = self.lim.validate(ctx)?;
-        if !(range <= (std::u16::MAX as u32)) {
-            return Err(Error::UnexpectedEndOfFile);
+        // memories are limited to 2^16 pages of 64 KiB, or 2^48 pages for 64-bit memories
+        let pages = match self.addr {
+            ty::Addr::I32 => 1 << 16,
+            ty::Addr::I64 => 1 << 48,
+        };
+        if range > pages {
+            return Err(Error::LimitsOutOfRange { max: range, range: pages });
+        }
+        if self.shared && self.lim.max.is_none() {
+            return Err(Error::SharedMemoryWithoutMax);
         }
         Ok(())
     }
 }
 
+impl<'a> Validate<'a> for ty::Tag {
+    type ValidationResult = &'a ty::Func;
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        require(ctx.features.exceptions, "exceptions")?;
+        let ty = self.ty.validate(ctx)?;
+        if !ty.results.is_empty() {
+            return Err(Error::InvalidTagType { results: ty.results.clone() });
+        }
+        Ok(ty)
+    }
+}
+
 impl<'a> Validate<'a> for ty::Global {
     type ValidationResult = ();
-    fn validate(&self, _ctx: &mut Context) -> Result<Self::ValidationResult> {
-        Ok(())
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        self.val.validate(ctx)
     }
 }
 
 impl<'a> Validate<'a> for ty::RetVal {
-    type ValidationResult = &'a [ty::Val];
-    fn validate(&'a self, _ctx: &mut Context) -> Result<Self::ValidationResult> {
-        Ok(&self.0[..])
+    type ValidationResult = (&'a [ty::Val], &'a [ty::Val]);
+    fn validate(&'a self, ctx: &mut Context<'a>) -> Result<Self::ValidationResult> {
+        match self {
+            ty::RetVal::Empty => Ok((&[], &[])),
+            ty::RetVal::Val(val) => Ok((&[], std::slice::from_ref(val))),
+            ty::RetVal::Type(idx) => {
+                let ty = idx.validate(ctx)?;
+                Ok((&ty.params[..], &ty.results[..]))
+            },
+        }
     }
-}
+}
\ No newline at end of file